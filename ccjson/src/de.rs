@@ -0,0 +1,486 @@
+use std::fmt;
+
+use serde::{
+    de::{
+        value::StringDeserializer, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer,
+        MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize,
+};
+
+use crate::{parse, Error, JsonValue, Map, Number};
+
+pub fn from_str<T: DeserializeOwned>(value: &str) -> Result<T, Error> {
+    from_value(parse(value)?)
+}
+
+pub fn from_value<T: DeserializeOwned>(value: JsonValue) -> Result<T, Error> {
+    T::deserialize(Deserializer(value))
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(v.into()))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(v.into()))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<JsonValue, E> {
+        Ok(JsonValue::Number(v.into()))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(self, d: D) -> Result<JsonValue, D::Error> {
+        JsonValue::deserialize(d)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(JsonValue::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut object = Map::new();
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(JsonValue::Object(object))
+    }
+}
+
+pub struct Deserializer(JsonValue);
+
+impl Deserializer {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match &self.0 {
+            JsonValue::String(s) => Unexpected::Str(s),
+            JsonValue::Number(Number::Unsigned(n)) => Unexpected::Unsigned(*n),
+            JsonValue::Number(Number::Integer(n)) => Unexpected::Signed(*n),
+            JsonValue::Number(n) => Unexpected::Float(n.as_f64()),
+            JsonValue::Object(_) => Unexpected::Map,
+            JsonValue::Array(_) => Unexpected::Seq,
+            JsonValue::Bool(b) => Unexpected::Bool(*b),
+            JsonValue::Null => Unexpected::Unit,
+        }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            JsonValue::String(s) => visitor.visit_string(s),
+            JsonValue::Number(Number::Unsigned(n)) => visitor.visit_u64(n),
+            JsonValue::Number(Number::Integer(n)) => visitor.visit_i64(n),
+            JsonValue::Number(n) => visitor.visit_f64(n.as_f64()),
+            JsonValue::Object(object) => visitor.visit_map(ObjectAccess {
+                entries: object.into_iter(),
+                key: None,
+                value: None,
+            }),
+            JsonValue::Array(values) => visitor.visit_seq(ArrayAccess {
+                values: values.into_iter(),
+                index: 0,
+            }),
+            JsonValue::Bool(b) => visitor.visit_bool(b),
+            JsonValue::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            JsonValue::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            JsonValue::String(variant) => visitor.visit_enum(VariantDeserializer {
+                variant,
+                value: None,
+            }),
+            JsonValue::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.into_iter().next().expect("one entry");
+                visitor
+                    .visit_enum(VariantDeserializer {
+                        variant: variant.clone(),
+                        value: Some(value),
+                    })
+                    .map_err(|e| e.within(&format!(".{variant}")))
+            }
+            _ => Err(serde::de::Error::invalid_type(
+                self.unexpected(),
+                &"string or single-key object",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct ArrayAccess {
+    values: std::vec::IntoIter<JsonValue>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> for ArrayAccess {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        let Some(value) = self.values.next() else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(Deserializer(value))
+            .map(Some)
+            .map_err(|e| e.within(&format!("[{index}]")))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+struct ObjectAccess {
+    entries: std::vec::IntoIter<(String, JsonValue)>,
+    key: Option<String>,
+    value: Option<JsonValue>,
+}
+
+impl<'de> MapAccess<'de> for ObjectAccess {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        self.key = Some(key.clone());
+        seed.deserialize(MapKey(key.clone()))
+            .map(Some)
+            .map_err(|e| e.within(&format!(".{key}")))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self.value.take().expect("next_key_seed is called first");
+        let key = self.key.take().unwrap_or_default();
+        seed.deserialize(Deserializer(value))
+            .map_err(|e| e.within(&format!(".{key}")))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct MapKey(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse() {
+                    Ok(parsed) => visitor.$visit(parsed),
+                    Err(_) => visitor.visit_string(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for MapKey {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant: StringDeserializer<Error> = self.0.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+struct VariantDeserializer {
+    variant: String,
+    value: Option<JsonValue>,
+}
+
+impl<'de> EnumAccess<'de> for VariantDeserializer {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant: StringDeserializer<Error> = self.variant.clone().into_deserializer();
+        Ok((seed.deserialize(variant)?, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.value {
+            None | Some(JsonValue::Null) => Ok(()),
+            Some(value) => Err(serde::de::Error::invalid_type(
+                Deserializer(value).unexpected(),
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(Deserializer(self.value.unwrap_or(JsonValue::Null)))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        serde::Deserializer::deserialize_seq(
+            Deserializer(self.value.unwrap_or(JsonValue::Null)),
+            visitor,
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        serde::Deserializer::deserialize_map(
+            Deserializer(self.value.unwrap_or(JsonValue::Null)),
+            visitor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Config {
+        name: String,
+        servers: Vec<Server>,
+        limits: HashMap<String, i64>,
+        owner: Option<String>,
+        shape: Shape,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        weight: f64,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Shape {
+        Dot,
+        Circle(f64),
+        Point(i32, i32),
+        Rect { w: u8, h: u8 },
+    }
+
+    #[test]
+    fn deserialize() {
+        let input = r#"{
+            "name": "api",
+            "servers": [{"host": "a", "port": 80, "weight": 1}, {"host": "b", "port": 81, "weight": 0.5}],
+            "limits": {"rps": -1},
+            "owner": null,
+            "shape": {"Rect": {"w": 2, "h": 3}},
+            "ignored": [true]
+        }"#;
+        let config: Config = from_str(input).unwrap();
+        assert_eq!(
+            Config {
+                name: "api".into(),
+                servers: vec![
+                    Server {
+                        host: "a".into(),
+                        port: 80,
+                        weight: 1.0
+                    },
+                    Server {
+                        host: "b".into(),
+                        port: 81,
+                        weight: 0.5
+                    },
+                ],
+                limits: HashMap::from([("rps".into(), -1)]),
+                owner: None,
+                shape: Shape::Rect { w: 2, h: 3 },
+            },
+            config
+        );
+        assert_eq!(Shape::Dot, from_str::<Vec<Shape>>(r#"["Dot"]"#).unwrap()[0]);
+        assert_eq!(
+            vec![Shape::Circle(1.5), Shape::Point(1, -1)],
+            from_str::<Vec<Shape>>(r#"[{"Circle": 1.5}, {"Point": [1, -1]}]"#).unwrap()
+        );
+        let value = crate::parse(input).unwrap();
+        assert_eq!(value, from_str::<JsonValue>(input).unwrap());
+    }
+
+    #[test]
+    fn error_paths() {
+        fn error(input: &str) -> String {
+            from_str::<Config>(input).unwrap_err().to_string()
+        }
+        let base = r#""name": "api", "limits": {}, "owner": null, "shape": "Dot""#;
+        assert_eq!(
+            "invalid value: integer `70000`, expected u16 at servers[1].port",
+            error(&format!(
+                r#"{{{base}, "servers": [{{"host": "a", "port": 80, "weight": 1}}, {{"host": "b", "port": 70000, "weight": 1}}]}}"#
+            ))
+        );
+        assert_eq!(
+            "missing field `weight` at servers[0]",
+            error(&format!(
+                r#"{{{base}, "servers": [{{"host": "a", "port": 80}}]}}"#
+            ))
+        );
+        assert_eq!(
+            "invalid type: string \"x\", expected i64 at limits.rps",
+            error(
+                r#"{"name": "api", "servers": [], "limits": {"rps": "x"}, "owner": null, "shape": "Dot"}"#
+            )
+        );
+        assert_eq!(
+            "invalid type: string \"x\", expected u8 at shape.Rect.w",
+            error(
+                r#"{"name": "api", "servers": [], "limits": {}, "owner": null, "shape": {"Rect": {"w": "x", "h": 1}}}"#
+            )
+        );
+        assert!(error("{").starts_with("expected string key or '}'"));
+    }
+
+    #[test]
+    fn non_string_keys() {
+        use std::collections::BTreeMap;
+
+        let numbers = BTreeMap::from([(1u32, -1i64), (20, 2)]);
+        let json = crate::to_string(&numbers).unwrap();
+        assert_eq!(numbers, from_str::<BTreeMap<u32, i64>>(&json).unwrap());
+        let flags = HashMap::from([(true, 1u8), (false, 0)]);
+        let json = crate::to_string(&flags).unwrap();
+        assert_eq!(flags, from_str::<HashMap<bool, u8>>(&json).unwrap());
+        let signed = from_str::<BTreeMap<i8, f64>>(r#"{"-3": 1.5}"#).unwrap();
+        assert_eq!(BTreeMap::from([(-3, 1.5)]), signed);
+
+        assert_eq!(
+            "invalid type: string \"x\", expected u32 at limits.x",
+            from_str::<HashMap<String, HashMap<u32, i64>>>(r#"{"limits": {"x": 1}}"#)
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "invalid type: string \"yes\", expected a boolean at yes",
+            from_str::<HashMap<bool, u8>>(r#"{"yes": 1}"#)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
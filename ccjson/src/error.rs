@@ -0,0 +1,198 @@
+use std::{borrow::Cow, error, fmt::Display};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+    pub found: Option<char>,
+    pub expected: Cow<'static, str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Position {
+    pub offset: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl Position {
+    pub fn advance(&mut self, c: char) {
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+}
+
+impl ParseError {
+    pub(crate) fn at(
+        position: Position,
+        found: Option<char>,
+        expected: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            offset: position.offset,
+            line: position.line,
+            column: position.column,
+            found,
+            expected: expected.into(),
+        }
+    }
+
+    pub fn snippet(&self, input: &str) -> String {
+        let mut offset = self.offset.min(input.len());
+        while !input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let text = input[line_start..line_end].trim_end_matches('\r');
+        let padding: String = input[line_start..offset]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        format!(
+            "{gutter}--> line {}, column {}\n{gutter} |\n{number} | {text}\n{gutter} | {padding}^ expected {}\n",
+            self.line, self.column, self.expected
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {}, found ", self.expected)?;
+        match self.found {
+            Some(c) => write!(f, "{c:?}")?,
+            None => write!(f, "end of input")?,
+        }
+        write!(f, " at line {}, column {}", self.line, self.column)
+    }
+}
+
+impl error::Error for ParseError {}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub message: String,
+    pub path: String,
+}
+
+#[cfg(feature = "serde")]
+impl Error {
+    pub(crate) fn new(message: impl Display) -> Self {
+        Self {
+            message: message.to_string(),
+            path: String::new(),
+        }
+    }
+
+    pub(crate) fn within(mut self, segment: &str) -> Self {
+        self.path.insert_str(0, segment);
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.path.strip_prefix('.').unwrap_or(&self.path) {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "{} at {path}", self.message),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl error::Error for Error {}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new(msg)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new(msg)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
+        Error::new(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(input: &str, offset: usize, expected: &'static str) -> ParseError {
+        let mut position = Position::default();
+        input[..offset].chars().for_each(|c| position.advance(c));
+        ParseError::at(position, input[offset..].chars().next(), expected)
+    }
+
+    #[test]
+    fn position() {
+        let input = "{\n  \"ключ\": x\n}";
+        let unexpected = error(input, 16, "value after ':'");
+        assert_eq!(2, unexpected.line);
+        assert_eq!(11, unexpected.column);
+        assert_eq!(Some('x'), unexpected.found);
+        assert_eq!(
+            "expected value after ':', found 'x' at line 2, column 11",
+            unexpected.to_string()
+        );
+        let end = error(input, input.len(), "end of input");
+        assert_eq!((3, 2, None), (end.line, end.column, end.found));
+    }
+
+    #[test]
+    fn snippet() {
+        let input = "{\n\t\"key\": x\n}";
+        let error = error(input, 10, "value after ':'");
+        assert_eq!(
+            " --> line 2, column 9\n  |\n2 | \t\"key\": x\n  | \t       ^ expected value after ':'\n",
+            error.snippet(input)
+        );
+    }
+
+    #[test]
+    fn snippet_input() {
+        let input = "[\"é\", x]";
+        let error = crate::parse(input).unwrap_err();
+        assert_eq!(
+            " --> line 1, column 7\n  |\n1 | [\"é\", x]\n  |       ^ expected value after ','\n",
+            error.snippet(input)
+        );
+        assert!(error
+            .snippet("[")
+            .ends_with("1 | [\n  |  ^ expected value after ','\n"));
+        assert!(error.snippet("").contains("1 | \n"));
+        let error = crate::parse("[1, x]").unwrap_err();
+        assert!(error.snippet("aaaé").contains("1 | aaaé\n  |    ^"));
+    }
+}
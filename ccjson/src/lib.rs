@@ -1,14 +1,67 @@
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
-#[derive(Debug)]
+#[cfg(feature = "serde")]
+mod de;
+mod error;
+mod map;
+mod number;
+mod path;
+mod pointer;
+mod reader;
+#[cfg(feature = "serde")]
+mod ser;
+mod writer;
+
+#[cfg(feature = "serde")]
+pub use de::{from_str, from_value, Deserializer};
+#[cfg(feature = "serde")]
+pub use error::Error;
+pub use error::ParseError;
+use error::Position;
+pub use map::Map;
+pub use number::Number;
+pub use path::{JsonPath, Node, PathError};
+pub use pointer::PointerError;
+pub use reader::{Event, ReadError, Reader};
+#[cfg(feature = "serde")]
+pub use ser::{to_string, to_string_pretty, to_value, to_writer, Serializer};
+pub use writer::WriteOptions;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum JsonValue {
     String(String),
-    Number(u32),
-    Object(HashMap<String, JsonValue>),
+    Number(Number),
+    Object(Map),
     Array(Vec<JsonValue>),
     Bool(bool),
     Null,
 }
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub arbitrary_precision: bool,
+    pub duplicate_keys: DuplicateKeys,
+    pub max_depth: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            arbitrary_precision: false,
+            duplicate_keys: DuplicateKeys::default(),
+            max_depth: 19,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    #[default]
+    KeepLast,
+    KeepFirst,
+    Error,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Token {
     LeftBrace,
@@ -26,10 +79,31 @@ enum Token {
     Escape,
     DoubleQuote,
     Character(char),
-    Digit,
+    Digit(char),
     Minus,
+    Whitespace(char),
+}
+
+impl From<char> for Token {
+    fn from(c: char) -> Self {
+        match c {
+            '{' => Token::LeftBrace,
+            '}' => Token::RightBrace,
+            ':' => Token::Colon,
+            ',' => Token::Comma,
+            '[' => Token::LeftBracket,
+            ']' => Token::RightBracket,
+            '\\' => Token::Escape,
+            '"' => Token::DoubleQuote,
+            '0'..='9' => Token::Digit(c),
+            '-' => Token::Minus,
+            ' ' | '\t' | '\n' | '\r' => Token::Whitespace(c),
+            _ => Token::Character(c),
+        }
+    }
 }
-#[derive(Debug)]
+
+#[derive(Debug, Clone, Copy)]
 enum Validator {
     Object(ObjectValidator),
     Array(ArrayValidator),
@@ -85,6 +159,28 @@ impl Validator {
             }
         }
     }
+    fn kind(&self) -> Token {
+        match self {
+            Validator::Object(_) => Token::Object,
+            Validator::Array(_) => Token::Array,
+            Validator::String(_) => Token::String,
+            Validator::Number(_) => Token::Number,
+            Validator::Bool(_) => Token::Bool,
+            Validator::Null(_) => Token::Null,
+        }
+    }
+
+    fn expected(&self) -> &'static str {
+        match self {
+            Validator::Object(p) => p.expected(),
+            Validator::Array(p) => p.expected(),
+            Validator::String(p) => p.expected(),
+            Validator::Number(p) => p.expected(),
+            Validator::Bool(p) => p.expected(),
+            Validator::Null(p) => p.expected(),
+        }
+    }
+
     fn is_done(&self) -> Option<Token> {
         match self {
             Validator::Object(p) => p.is_done(),
@@ -97,77 +193,257 @@ impl Validator {
     }
 }
 
-pub fn validate(value: &str) -> bool {
-    let mut validators = Vec::new();
-    for c in value.chars() {
-        if c == ' ' || c == '\n' {
-            continue;
+fn is_scalar(validator: &Validator) -> bool {
+    !matches!(validator, Validator::Object(_) | Validator::Array(_))
+}
+
+pub(crate) struct Machine {
+    validators: Vec<Validator>,
+    text: String,
+    start: Position,
+    done: bool,
+    arbitrary_precision: bool,
+    max_depth: usize,
+}
+
+impl Machine {
+    pub(crate) fn new(options: &ParseOptions) -> Self {
+        Self {
+            validators: Vec::new(),
+            text: String::new(),
+            start: Position::default(),
+            done: false,
+            arbitrary_precision: options.arbitrary_precision,
+            max_depth: options.max_depth,
         }
-        let token = match c {
-            '{' => Token::LeftBrace,
-            '}' => Token::RightBrace,
-            ':' => Token::Colon,
-            ',' => Token::Comma,
-            '[' => Token::LeftBracket,
-            ']' => Token::RightBracket,
-            '\\' => Token::Escape,
-            '"' => Token::DoubleQuote,
-            '0'..='9' => Token::Digit,
-            '-' => Token::Minus,
-            _ => Token::Character(c),
+    }
+
+    pub(crate) fn next(
+        &mut self,
+        c: char,
+        position: Position,
+        events: &mut VecDeque<(Position, Event)>,
+    ) -> Result<(), ParseError> {
+        let token = Token::from(c);
+        let error = |expected| ParseError::at(position, Some(c), expected);
+        let Some(mut current) = self.validators.pop() else {
+            if let Token::Whitespace(_) = token {
+                return Ok(());
+            }
+            if self.done {
+                return Err(error("end of input"));
+            }
+            return match Validator::new(token) {
+                Ok(new @ (Validator::Object(_) | Validator::Array(_))) => {
+                    self.start(new, c, position, events);
+                    Ok(())
+                }
+                _ => Err(error("object or array")),
+            };
         };
-        let mut current = match validators.pop() {
-            Some(current) => current,
-            None =>{
-                match Validator::new(token){
-                    Ok(new) => match new{
-                        Validator::Object(_) => Validator::Object(ObjectValidator::Initial),
-                        Validator::Array(_) => Validator::Array(ArrayValidator::Initial),
-                        _ => return false,
-                    },
-                    Err(_) => return false,
+        loop {
+            if let Ok(next) = current.next(token) {
+                if is_scalar(&next) {
+                    self.text.push(c);
                 }
+                match next.is_done() {
+                    Some(Token::Object) => events.push_back((position, Event::EndObject)),
+                    Some(Token::Array) => events.push_back((position, Event::EndArray)),
+                    _ => {}
+                }
+                self.validators.push(next);
+                return Ok(());
             }
-            // match Validator::new(token) {
-            //     Ok(new) => {
-            //         validators.push(new);
-            //         continue;
-            //     }
-            //     Err(_) => return false,
-            // },
-            // },
-        };
-        // let mut current = validators
-        //     .pop()
-        //     .expect("Validator stack should not be empty");
-        while let Some(validated) = current.is_done() {
-            if let (Token::Number, Token::Digit) = (validated, token) {
-                break;
+            if let Some(validated) = current.is_done() {
+                self.complete(current, events);
+                current = match self.validators.pop() {
+                    Some(parent) => parent,
+                    None => {
+                        self.done = true;
+                        if let Token::Whitespace(_) = token {
+                            return Ok(());
+                        }
+                        return Err(error("end of input"));
+                    }
+                };
+                current = current.next(validated).expect("parent accepts the value");
+                continue;
             }
-            current = match validators.pop() {
-                Some(current) => current,
-                None => return false,
+            return match Validator::new(token) {
+                Ok(new) if current.next(new.kind()).is_ok() => {
+                    if !is_scalar(&new) && self.validators.len() + 1 >= self.max_depth {
+                        let limit = format!("at most {} levels of nesting", self.max_depth);
+                        return Err(ParseError::at(position, Some(c), limit));
+                    }
+                    self.validators.push(current);
+                    self.start(new, c, position, events);
+                    Ok(())
+                }
+                _ => Err(error(current.expected())),
             };
-            current = match current.next(validated) {
-                Ok(next) => next,
-                Err(_) => return false,
+        }
+    }
+
+    pub(crate) fn finish(
+        &mut self,
+        position: Position,
+        events: &mut VecDeque<(Position, Event)>,
+    ) -> Result<(), ParseError> {
+        while let Some(current) = self.validators.pop() {
+            let Some(validated) = current.is_done() else {
+                return Err(ParseError::at(position, None, current.expected()));
+            };
+            self.complete(current, events);
+            match self.validators.last_mut() {
+                Some(parent) => *parent = parent.next(validated).expect("parent accepts the value"),
+                None => self.done = true,
+            }
+        }
+        if self.done {
+            Ok(())
+        } else {
+            Err(ParseError::at(position, None, "object or array"))
+        }
+    }
+
+    fn start(
+        &mut self,
+        validator: Validator,
+        c: char,
+        position: Position,
+        events: &mut VecDeque<(Position, Event)>,
+    ) {
+        match validator {
+            Validator::Object(_) => events.push_back((position, Event::StartObject)),
+            Validator::Array(_) => events.push_back((position, Event::StartArray)),
+            _ => {
+                self.text.clear();
+                self.text.push(c);
+                self.start = position;
+            }
+        }
+        self.validators.push(validator);
+    }
+
+    fn complete(&mut self, validator: Validator, events: &mut VecDeque<(Position, Event)>) {
+        let text = &self.text;
+        let value = match validator {
+            Validator::Object(_) | Validator::Array(_) => return,
+            Validator::String(_) => JsonValue::String(unescape(&text[1..text.len() - 1])),
+            Validator::Number(_) => {
+                JsonValue::Number(Number::from_literal(text, self.arbitrary_precision))
             }
+            Validator::Bool(_) => JsonValue::Bool(text == "true"),
+            Validator::Null(_) => JsonValue::Null,
+        };
+        let event = match (self.validators.last(), value) {
+            (
+                Some(Validator::Object(ObjectValidator::Open | ObjectValidator::Comma)),
+                JsonValue::String(key),
+            ) => Event::Key(key),
+            (_, value) => Event::Value(value),
+        };
+        events.push_back((self.start, event));
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
         }
-        match current.next(token) {
-            Ok(next) => validators.push(next),
-            Err(token) => match Validator::new(token) {
-                Ok(new) => {
-                    validators.push(current);
-                    validators.push(new);
+        let escaped = match chars.next() {
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let unit = hex_unit(&mut chars);
+                let code = if (0xD800..0xDC00).contains(&unit) {
+                    chars.nth(1);
+                    0x10000 + ((unit - 0xD800) << 10) + (hex_unit(&mut chars) - 0xDC00)
+                } else {
+                    unit
+                };
+                char::from_u32(code).expect("validated unicode escape")
+            }
+            Some(c) => c,
+            None => break,
+        };
+        result.push(escaped);
+    }
+    result
+}
+
+fn hex_unit(chars: &mut std::str::Chars) -> u32 {
+    chars.take(4).fold(0, |unit, c| {
+        unit << 4 | c.to_digit(16).expect("validated hex digit")
+    })
+}
+
+pub fn validate(value: &str) -> bool {
+    Reader::new(value.as_bytes()).all(|event| event.is_ok())
+}
+
+pub fn parse(value: &str) -> Result<JsonValue, ParseError> {
+    parse_with_options(value, &ParseOptions::default())
+}
+
+pub fn parse_with_options(value: &str, options: &ParseOptions) -> Result<JsonValue, ParseError> {
+    let mut reader = Reader::with_options(value.as_bytes(), options);
+    let mut containers: Vec<(JsonValue, Option<String>)> = Vec::new();
+    let mut result = None;
+    loop {
+        let (position, event) = match reader.next_positioned() {
+            Ok(Some(next)) => next,
+            Ok(None) => return Ok(result.expect("the reader ends after the top-level value")),
+            Err(ReadError::Parse(error)) => return Err(error),
+            Err(ReadError::Io(error)) => unreachable!("reading from a slice failed: {error}"),
+        };
+        let value = match event {
+            Event::StartObject => {
+                containers.push((JsonValue::Object(Map::new()), None));
+                continue;
+            }
+            Event::StartArray => {
+                containers.push((JsonValue::Array(Vec::new()), None));
+                continue;
+            }
+            Event::Key(key) => {
+                let Some((JsonValue::Object(members), pending)) = containers.last_mut() else {
+                    unreachable!("keys only appear in objects");
+                };
+                if options.duplicate_keys == DuplicateKeys::Error && members.contains_key(&key) {
+                    return Err(ParseError::at(position, Some('"'), "unique object key"));
                 }
-                Err(_) => return false,
-            },
+                *pending = Some(key);
+                continue;
+            }
+            Event::EndObject | Event::EndArray => {
+                containers.pop().expect("container was started").0
+            }
+            Event::Value(value) => value,
+        };
+        match containers.last_mut() {
+            Some((JsonValue::Object(members), key)) => {
+                let key = key.take().expect("object value follows a key");
+                if options.duplicate_keys != DuplicateKeys::KeepFirst || !members.contains_key(&key)
+                {
+                    members.insert(key, value);
+                }
+            }
+            Some((JsonValue::Array(values), _)) => values.push(value),
+            Some(_) => unreachable!("only objects and arrays are containers"),
+            None => result = Some(value),
         }
     }
-    validators.len() == 1 && validators.last().unwrap().is_done().is_some()
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum ObjectValidator {
     Initial,
     Open,
@@ -179,6 +455,14 @@ enum ObjectValidator {
 }
 impl ObjectValidator {
     fn next(&self, token: Token) -> Result<ObjectValidator, Token> {
+        match self {
+            ObjectValidator::Initial | ObjectValidator::Done => {}
+            _ => {
+                if let Token::Whitespace(_) = token {
+                    return Ok(*self);
+                }
+            }
+        }
         match self {
             ObjectValidator::Open => match token {
                 Token::RightBrace => Ok(ObjectValidator::Done),
@@ -215,6 +499,18 @@ impl ObjectValidator {
         }
     }
 
+    fn expected(&self) -> &'static str {
+        match self {
+            ObjectValidator::Initial => "'{'",
+            ObjectValidator::Open => "string key or '}'",
+            ObjectValidator::Name => "':' after object key",
+            ObjectValidator::Colon => "value after ':'",
+            ObjectValidator::Value => "',' or '}' after object member",
+            ObjectValidator::Comma => "string key after ','",
+            ObjectValidator::Done => "end of object",
+        }
+    }
+
     fn is_done(&self) -> Option<Token> {
         match self {
             ObjectValidator::Done => Some(Token::Object),
@@ -223,7 +519,7 @@ impl ObjectValidator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum ArrayValidator {
     Initial,
     Open,
@@ -233,6 +529,14 @@ enum ArrayValidator {
 }
 impl ArrayValidator {
     fn next(&self, token: Token) -> Result<ArrayValidator, Token> {
+        match self {
+            ArrayValidator::Initial | ArrayValidator::Done => {}
+            _ => {
+                if let Token::Whitespace(_) = token {
+                    return Ok(*self);
+                }
+            }
+        }
         match self {
             ArrayValidator::Initial => match token {
                 Token::LeftBracket => Ok(ArrayValidator::Open),
@@ -266,6 +570,16 @@ impl ArrayValidator {
         }
     }
 
+    fn expected(&self) -> &'static str {
+        match self {
+            ArrayValidator::Initial => "'['",
+            ArrayValidator::Open => "value or ']'",
+            ArrayValidator::Value => "',' or ']' after array element",
+            ArrayValidator::Comma => "value after ','",
+            ArrayValidator::Done => "end of array",
+        }
+    }
+
     fn is_done(&self) -> Option<Token> {
         match self {
             &ArrayValidator::Done => Some(Token::Array),
@@ -274,13 +588,16 @@ impl ArrayValidator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum StringValidator {
     Initial,
     Open,
     Done,
     Character,
-    //TODO escape symbols
+    Escape,
+    Unicode(u8, u16, bool),
+    HighSurrogate,
+    SurrogateEscape,
 }
 impl StringValidator {
     fn next(&self, token: Token) -> Result<StringValidator, Token> {
@@ -289,18 +606,72 @@ impl StringValidator {
                 Token::DoubleQuote => Ok(StringValidator::Open),
                 _ => Err(token),
             },
-            StringValidator::Open => match token {
+            StringValidator::Open | StringValidator::Character => match token {
                 Token::DoubleQuote => Ok(StringValidator::Done),
+                Token::Escape => Ok(StringValidator::Escape),
+                Token::Whitespace(' ') => Ok(StringValidator::Character),
+                Token::Whitespace(_) => Err(token),
+                Token::Character(c) if c < ' ' => Err(token),
                 _ => Ok(StringValidator::Character),
             },
-            StringValidator::Character => match token {
-                Token::DoubleQuote => Ok(StringValidator::Done),
-                _ => Ok(StringValidator::Character),
+            StringValidator::Escape => match token {
+                Token::DoubleQuote
+                | Token::Escape
+                | Token::Character('/' | 'b' | 'f' | 'n' | 'r' | 't') => {
+                    Ok(StringValidator::Character)
+                }
+                Token::Character('u') => Ok(StringValidator::Unicode(0, 0, false)),
+                _ => Err(token),
+            },
+            StringValidator::Unicode(n, unit, low) => {
+                let digit = match token {
+                    Token::Digit(c) | Token::Character(c) => c.to_digit(16),
+                    _ => None,
+                };
+                let Some(digit) = digit else {
+                    return Err(token);
+                };
+                let unit = unit << 4 | digit as u16;
+                if *n < 3 {
+                    return Ok(StringValidator::Unicode(n + 1, unit, *low));
+                }
+                match (unit, low) {
+                    (0xD800..=0xDBFF, false) => Ok(StringValidator::HighSurrogate),
+                    (0xDC00..=0xDFFF, true) => Ok(StringValidator::Character),
+                    (0xDC00..=0xDFFF, false) | (_, true) => Err(token),
+                    _ => Ok(StringValidator::Character),
+                }
+            }
+            StringValidator::HighSurrogate => match token {
+                Token::Escape => Ok(StringValidator::SurrogateEscape),
+                _ => Err(token),
+            },
+            StringValidator::SurrogateEscape => match token {
+                Token::Character('u') => Ok(StringValidator::Unicode(0, 0, true)),
+                _ => Err(token),
             },
             StringValidator::Done => Err(token),
         }
     }
 
+    fn expected(&self) -> &'static str {
+        match self {
+            StringValidator::Initial => "'\"'",
+            StringValidator::Open | StringValidator::Character => {
+                "closing '\"' or escaped control character"
+            }
+            StringValidator::Escape => "escape character (one of \"\\/bfnrtu)",
+            StringValidator::Unicode(3, unit, false) if (0xDC0..=0xDFF).contains(unit) => {
+                "high surrogate before low surrogate escape"
+            }
+            StringValidator::Unicode(_, _, false) => "four hex digits after '\\u'",
+            StringValidator::Unicode(_, _, true)
+            | StringValidator::HighSurrogate
+            | StringValidator::SurrogateEscape => "low surrogate escape after high surrogate",
+            StringValidator::Done => "end of string",
+        }
+    }
+
     fn is_done(&self) -> Option<Token> {
         match self {
             StringValidator::Done => Some(Token::String),
@@ -309,41 +680,88 @@ impl StringValidator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum NumberValidator {
     Initial,
     Minus,
-    Digit, //TODO Floating point and exponent
+    Zero,
+    Digit,
+    Point,
+    Fraction,
+    Exponent,
+    ExponentSign,
+    ExponentDigit,
 }
 
 impl NumberValidator {
     fn next(&self, token: Token) -> Result<NumberValidator, Token> {
         match self {
             NumberValidator::Initial => match token {
-                Token::Digit => Ok(NumberValidator::Digit),
+                Token::Digit('0') => Ok(NumberValidator::Zero),
+                Token::Digit(_) => Ok(NumberValidator::Digit),
                 Token::Minus => Ok(NumberValidator::Minus),
                 _ => Err(token),
             },
             NumberValidator::Minus => match token {
-                Token::Digit => Ok(NumberValidator::Digit),
+                Token::Digit('0') => Ok(NumberValidator::Zero),
+                Token::Digit(_) => Ok(NumberValidator::Digit),
+                _ => Err(token),
+            },
+            NumberValidator::Zero => match token {
+                Token::Character('.') => Ok(NumberValidator::Point),
+                Token::Character('e' | 'E') => Ok(NumberValidator::Exponent),
                 _ => Err(token),
             },
             NumberValidator::Digit => match token {
-                Token::Digit => Ok(NumberValidator::Digit),
+                Token::Digit(_) => Ok(NumberValidator::Digit),
+                Token::Character('.') => Ok(NumberValidator::Point),
+                Token::Character('e' | 'E') => Ok(NumberValidator::Exponent),
+                _ => Err(token),
+            },
+            NumberValidator::Point => match token {
+                Token::Digit(_) => Ok(NumberValidator::Fraction),
+                _ => Err(token),
+            },
+            NumberValidator::Fraction => match token {
+                Token::Digit(_) => Ok(NumberValidator::Fraction),
+                Token::Character('e' | 'E') => Ok(NumberValidator::Exponent),
+                _ => Err(token),
+            },
+            NumberValidator::Exponent => match token {
+                Token::Minus | Token::Character('+') => Ok(NumberValidator::ExponentSign),
+                Token::Digit(_) => Ok(NumberValidator::ExponentDigit),
                 _ => Err(token),
             },
+            NumberValidator::ExponentSign | NumberValidator::ExponentDigit => match token {
+                Token::Digit(_) => Ok(NumberValidator::ExponentDigit),
+                _ => Err(token),
+            },
+        }
+    }
+
+    fn expected(&self) -> &'static str {
+        match self {
+            NumberValidator::Initial => "'-' or digit",
+            NumberValidator::Minus => "digit after '-'",
+            NumberValidator::Point => "digit after '.'",
+            NumberValidator::Exponent => "sign or digit in exponent",
+            NumberValidator::ExponentSign => "digit in exponent",
+            _ => "end of number",
         }
     }
 
     fn is_done(&self) -> Option<Token> {
         match self {
-            NumberValidator::Digit => Some(Token::Number),
+            NumberValidator::Zero
+            | NumberValidator::Digit
+            | NumberValidator::Fraction
+            | NumberValidator::ExponentDigit => Some(Token::Number),
             _ => None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum BoolValidator {
     Initial,
     T,
@@ -398,6 +816,15 @@ impl BoolValidator {
         }
     }
 
+    fn expected(&self) -> &'static str {
+        match self {
+            BoolValidator::Initial => "'true' or 'false'",
+            BoolValidator::T | BoolValidator::R | BoolValidator::U => "'true'",
+            BoolValidator::F | BoolValidator::A | BoolValidator::L | BoolValidator::S => "'false'",
+            BoolValidator::Done => "end of boolean",
+        }
+    }
+
     fn is_done(&self) -> Option<Token> {
         match self {
             BoolValidator::Done => Some(Token::Bool),
@@ -406,7 +833,7 @@ impl BoolValidator {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum NullValidator {
     Initial,
     N,
@@ -439,6 +866,13 @@ impl NullValidator {
             _ => Err(token),
         }
     }
+    fn expected(&self) -> &'static str {
+        match self {
+            NullValidator::Done => "end of null",
+            _ => "'null'",
+        }
+    }
+
     fn is_done(&self) -> Option<Token> {
         match self {
             NullValidator::Done => Some(Token::Null),
@@ -449,18 +883,29 @@ impl NullValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::{borrow::Cow, fs};
+
+    fn check_folder(folder: &str) {
+        let folder = format!("{}/{folder}", env!("CARGO_MANIFEST_DIR"));
+        for entry in fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            let valid = name.starts_with("pass") || name.starts_with("valid");
+            let json = fs::read_to_string(&path).unwrap();
+            assert_eq!(valid, parse(&json).is_ok(), "{}", path.display());
+        }
+    }
 
     #[test]
     fn fail1() {
         let input = r#"
         "A JSON payload should be an object or array, not a string."
        "#;
-        assert_eq!(validate(input), false);
+        assert!(!validate(input));
     }
     #[test]
     fn pass1() {
-        todo!("Add support for floating point and exponential numbers");
-        let input = r#"[
+        let input = r##"[
     "JSON Test Pattern pass1",
     {"object with 1 member":["array with 1 element"]},
     {},
@@ -496,7 +941,7 @@ mod tests {
         "address": "50 St. James Street",
         "url": "http://www.JSON.org/",
         "comment": "// /* <!-- --",
-        "\# -- --> */": " ",
+        "# -- --> */": " ",
         " s p a c e d " :[1,2 , 3
 
 ,
@@ -518,9 +963,9 @@ mod tests {
 1e-1,
 1e00,2e+00,2e-00
 ,"rosebud"]
-       "#;
+       "##;
         let actual = validate(input);
-        assert_eq!(actual, true);
+        assert!(actual);
     }
     #[test]
     fn pass2() {
@@ -528,6 +973,181 @@ mod tests {
 [[[[[[[[[[[[[[[[[[["Not too deep"]]]]]]]]]]]]]]]]]]]
        "#;
         let actual = validate(input);
-        assert_eq!(actual, true);
+        assert!(actual);
+    }
+
+    #[test]
+    fn test_suites() {
+        check_folder("test");
+        check_folder("tests_john/step1");
+        check_folder("tests_john/step2");
+        check_folder("tests_john/step3");
+        check_folder("tests_john/step4");
+    }
+
+    #[test]
+    fn parse_tree() {
+        let input = r#"{"name": "ccjson", "tags": ["a\tb", "é"], "size": -1.5e1, "ok": true, "none": null}"#;
+        let JsonValue::Object(object) = parse(input).unwrap() else {
+            panic!("expected an object");
+        };
+        assert_eq!(
+            Some(&JsonValue::String("ccjson".into())),
+            object.get("name")
+        );
+        assert_eq!(
+            Some(&JsonValue::Array(vec![
+                JsonValue::String("a\tb".into()),
+                JsonValue::String("é".into())
+            ])),
+            object.get("tags")
+        );
+        assert_eq!(
+            Some(&JsonValue::Number(Number::Float(-15.0))),
+            object.get("size")
+        );
+        assert_eq!(Some(&JsonValue::Bool(true)), object.get("ok"));
+        assert_eq!(Some(&JsonValue::Null), object.get("none"));
+        assert_eq!(JsonValue::Array(vec![]), parse(" [ ] ").unwrap());
+    }
+
+    #[test]
+    fn parse_errors() {
+        fn error(input: &str) -> (usize, Option<char>, Cow<'static, str>) {
+            let error = parse(input).unwrap_err();
+            (error.offset, error.found, error.expected)
+        }
+        assert_eq!(
+            (7, None, "',' or '}' after object member".into()),
+            error(r#"{"a": 1"#)
+        );
+        assert_eq!((4, Some('x'), "value after ','".into()), error("[1, x]"));
+        assert_eq!(
+            (19, Some('['), "at most 19 levels of nesting".into()),
+            error(&"[".repeat(20))
+        );
+        assert_eq!(
+            (3, Some('2'), "',' or ']' after array element".into()),
+            error("[1 2]")
+        );
+        assert_eq!((3, Some('['), "end of input".into()), error("[] []"));
+        assert_eq!(
+            (5, Some('"'), "':' after object key".into()),
+            error(r#"{"a" "b"}"#)
+        );
+        assert_eq!((1, Some('1'), "string key or '}'".into()), error("{1: 2}"));
+        assert_eq!((3, Some(']'), "digit after '.'".into()), error("[1.]"));
+        assert_eq!((2, Some('u'), "'true'".into()), error("[tuue]"));
+        assert_eq!((0, Some('"'), "object or array".into()), error(r#""text""#));
+        assert_eq!((1, None, "object or array".into()), error(" "));
+    }
+
+    #[test]
+    fn max_depth() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse(&nested(19)).is_ok());
+        let options = ParseOptions {
+            max_depth: 64,
+            ..ParseOptions::default()
+        };
+        assert!(parse_with_options(&nested(64), &options).is_ok());
+        let error = parse_with_options(&nested(65), &options).unwrap_err();
+        assert_eq!(
+            (64, "at most 64 levels of nesting".into()),
+            (error.offset, error.expected)
+        );
+    }
+
+    #[test]
+    fn parse_numbers() {
+        let numbers =
+            parse("[0, -0.5, 1.5e10, -1.5E-10, 4294967296, -9223372036854775808]").unwrap();
+        assert_eq!(
+            JsonValue::Array(vec![
+                JsonValue::Number(Number::Unsigned(0)),
+                JsonValue::Number(Number::Float(-0.5)),
+                JsonValue::Number(Number::Float(1.5e10)),
+                JsonValue::Number(Number::Float(-1.5e-10)),
+                JsonValue::Number(Number::Unsigned(4294967296)),
+                JsonValue::Number(Number::Integer(i64::MIN)),
+            ]),
+            numbers
+        );
+        for invalid in [
+            "[01]", "[-]", "[1.]", "[.5]", "[1e]", "[1e+]", "[+1]", "[-01]", "[0x1]",
+        ] {
+            assert!(parse(invalid).is_err(), "{invalid}");
+        }
+        let options = ParseOptions {
+            arbitrary_precision: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            Ok(JsonValue::Array(vec![JsonValue::Number(Number::Decimal(
+                "1.00000000000000000001".into()
+            ))])),
+            parse_with_options("[1.00000000000000000001]", &options)
+        );
+    }
+
+    #[test]
+    fn parse_strings() {
+        let escaped = r#"["\"\\\/\b\f\n\r\t", "U00A9", "UD83DUDE00", "é"]"#.replace('U', "\\u");
+        assert_eq!(
+            Ok(JsonValue::Array(vec![
+                JsonValue::String("\"\\/\u{8}\u{c}\n\r\t".into()),
+                JsonValue::String("\u{a9}".into()),
+                JsonValue::String("\u{1f600}".into()),
+                JsonValue::String("é".into()),
+            ])),
+            parse(&escaped)
+        );
+        for invalid in [
+            "UD83D",
+            "UD83Dx",
+            "UD83DU0041",
+            "UDE00",
+            "U12",
+            "U12G4",
+            "\\x",
+            "\u{1}",
+            "\t",
+        ] {
+            let input = format!(r#"["{}"]"#, invalid.replace('U', "\\u"));
+            assert!(parse(&input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let input = r#"{"b": 1, "a": 2, "b": 3}"#;
+        let parse_keys = |duplicate_keys| {
+            let options = ParseOptions {
+                duplicate_keys,
+                ..ParseOptions::default()
+            };
+            parse_with_options(input, &options).map(|value| value.to_string())
+        };
+        assert_eq!(
+            Ok(r#"{"b":3,"a":2}"#.into()),
+            parse_keys(DuplicateKeys::KeepLast)
+        );
+        assert_eq!(
+            Ok(r#"{"b":1,"a":2}"#.into()),
+            parse_keys(DuplicateKeys::KeepFirst)
+        );
+        let error = parse_keys(DuplicateKeys::Error).unwrap_err();
+        assert_eq!(
+            (17, "unique object key".into()),
+            (error.offset, error.expected)
+        );
+        assert!(parse_with_options(
+            r#"{"a": {"a": 1}, "b": [{"a": 2}]}"#,
+            &ParseOptions {
+                duplicate_keys: DuplicateKeys::Error,
+                ..ParseOptions::default()
+            }
+        )
+        .is_ok());
     }
 }
@@ -1,4 +1,4 @@
-use ccjson::validate;
+use ccjson::parse;
 use std::fs;
 
 fn main() {
@@ -20,7 +20,12 @@ fn run_tests(folder: &str) {
             let fullpath = folder.to_owned() + path.to_str().unwrap();
             let json = fs::read_to_string(&fullpath).unwrap();
             println!("{fullpath}");
-            let is_valid = validate(&json);
-            println!("  is {is_valid}");
+            match parse(&json) {
+                Ok(_) => println!("  is true"),
+                Err(error) => {
+                    println!("  is false: {error}");
+                    print!("{}", error.snippet(&json));
+                }
+            }
         });
 }
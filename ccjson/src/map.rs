@@ -0,0 +1,139 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use crate::JsonValue;
+
+#[derive(Clone, Default)]
+pub struct Map {
+    entries: Vec<(String, JsonValue)>,
+    index: HashMap<String, usize>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.index.get(key).map(|&i| &mut self.entries[i].1)
+    }
+
+    pub fn insert(&mut self, key: String, value: JsonValue) -> Option<JsonValue> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let i = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+        for (key, _) in &self.entries[i..] {
+            *self.index.get_mut(key).expect("indexed key") -= 1;
+        }
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &JsonValue)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut JsonValue)> {
+        self.entries.iter_mut().map(|(key, value)| (&*key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl Debug for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for Map {
+    fn from_iter<T: IntoIterator<Item = (String, JsonValue)>>(iter: T) -> Self {
+        let mut map = Map::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, JsonValue);
+    type IntoIter = std::vec::IntoIter<(String, JsonValue)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(map: &Map) -> Vec<&str> {
+        map.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn insertion_order() {
+        let mut map = Map::new();
+        for key in ["c", "a", "b"] {
+            map.insert(key.into(), JsonValue::Null);
+        }
+        assert_eq!(vec!["c", "a", "b"], keys(&map));
+
+        assert_eq!(
+            Some(JsonValue::Null),
+            map.insert("a".into(), JsonValue::Bool(true))
+        );
+        assert_eq!(vec!["c", "a", "b"], keys(&map));
+        assert_eq!(Some(&JsonValue::Bool(true)), map.get("a"));
+
+        assert_eq!(Some(JsonValue::Null), map.remove("c"));
+        assert_eq!(None, map.remove("c"));
+        assert_eq!(vec!["a", "b"], keys(&map));
+        assert_eq!(Some(&JsonValue::Null), map.get("b"));
+
+        let reversed: Map = map.clone().into_iter().rev().collect();
+        assert_eq!(vec!["b", "a"], keys(&reversed));
+        assert_eq!(map, reversed);
+    }
+}
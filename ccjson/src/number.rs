@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    Unsigned(u64),
+    Integer(i64),
+    Float(f64),
+    Decimal(String),
+}
+
+impl Number {
+    pub(crate) fn from_literal(text: &str, arbitrary_precision: bool) -> Number {
+        if !text.contains(['.', 'e', 'E']) {
+            if let Ok(n) = text.parse::<u64>() {
+                return Number::Unsigned(n);
+            }
+            match text.parse::<i64>() {
+                Ok(n) if n != 0 => return Number::Integer(n),
+                _ => {}
+            }
+        }
+        let float: f64 = text.parse().expect("validated number");
+        if arbitrary_precision || float.is_infinite() {
+            Number::Decimal(text.to_string())
+        } else {
+            Number::Float(float)
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Number::Unsigned(_) | Number::Integer(_))
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Number::Unsigned(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Number::Unsigned(n) => i64::try_from(*n).ok(),
+            Number::Integer(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Number::Unsigned(n) => *n as f64,
+            Number::Integer(n) => *n as f64,
+            Number::Float(n) => *n,
+            Number::Decimal(text) => text.parse().unwrap_or(f64::NAN),
+        }
+    }
+}
+
+impl From<u64> for Number {
+    fn from(value: u64) -> Self {
+        Number::Unsigned(value)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        match u64::try_from(value) {
+            Ok(n) => Number::Unsigned(n),
+            Err(_) => Number::Integer(value),
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Float(value)
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Number::Unsigned(n) => write!(f, "{n}"),
+            Number::Integer(n) => write!(f, "{n}"),
+            Number::Float(n) => write!(f, "{n}"),
+            Number::Decimal(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_are_exact() {
+        assert_eq!(Number::Unsigned(0), Number::from_literal("0", false));
+        assert_eq!(
+            Number::Unsigned(u64::MAX),
+            Number::from_literal("18446744073709551615", false)
+        );
+        assert_eq!(
+            Number::Integer(i64::MIN),
+            Number::from_literal("-9223372036854775808", false)
+        );
+        assert_eq!(
+            Number::Float(18446744073709551616.0),
+            Number::from_literal("18446744073709551616", false)
+        );
+        assert_eq!(Number::Float(-0.0), Number::from_literal("-0", false));
+        assert_eq!(Number::from(42i64), Number::from(42u64));
+        assert_eq!(Some(-7), Number::from(-7i64).as_i64());
+        assert_eq!(None, Number::Unsigned(u64::MAX).as_i64());
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(
+            Number::Float(-1.5e10),
+            Number::from_literal("-1.5e10", false)
+        );
+        assert_eq!(Number::Float(0.1), Number::from_literal("1e-1", false));
+        assert_eq!(Number::Float(2.0), Number::from_literal("2E+00", false));
+        assert_eq!(Number::Float(100.0), Number::from_literal("100.0", false));
+        assert!(!Number::from_literal("1.0", false).is_integer());
+        assert_eq!(
+            Number::Decimal("-1e400".into()),
+            Number::from_literal("-1e400", false)
+        );
+        assert_eq!(f64::INFINITY, Number::from_literal("1e400", false).as_f64());
+    }
+
+    #[test]
+    fn arbitrary_precision() {
+        let literal = "3.14159265358979323846264338327950288";
+        assert_eq!(
+            Number::Decimal(literal.into()),
+            Number::from_literal(literal, true)
+        );
+        assert_eq!(literal, Number::from_literal(literal, true).to_string());
+        assert_eq!(
+            Number::Decimal("123456789012345678901234567890".into()),
+            Number::from_literal("123456789012345678901234567890", true)
+        );
+        assert_eq!(Number::Unsigned(12), Number::from_literal("12", true));
+        assert_eq!(1.5, Number::from_literal("15e-1", true).as_f64());
+        assert!(Number::Decimal("abc".into()).as_f64().is_nan());
+    }
+}
@@ -0,0 +1,909 @@
+use std::{cmp::Ordering, error::Error, fmt::Display};
+
+use crate::{JsonValue, Number};
+
+const MAX_INTEGER: i64 = (1 << 53) - 1;
+const MAX_FILTER_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    pub offset: usize,
+    pub found: Option<char>,
+    pub expected: &'static str,
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "expected {}, found ", self.expected)?;
+        match self.found {
+            Some(c) => write!(f, "{c:?}")?,
+            None => write!(f, "end of query")?,
+        }
+        write!(f, " at offset {}", self.offset)
+    }
+}
+
+impl Error for PathError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<'a> {
+    pub path: String,
+    pub value: &'a JsonValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Or(Vec<Filter>),
+    And(Vec<Filter>),
+    Not(Box<Filter>),
+    Exists(Query),
+    Compare(Comparable, Operator, Comparable),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    current: bool,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Comparable {
+    Literal(JsonValue),
+    Query(Query),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl JsonPath {
+    pub fn parse(query: &str) -> Result<Self, PathError> {
+        let mut parser = Parser {
+            query,
+            offset: 0,
+            depth: 0,
+        };
+        if !parser.eat('$') {
+            return Err(parser.error("'$'"));
+        }
+        let segments = parser.segments()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("'.', '[' or end of query"));
+        }
+        Ok(Self { segments })
+    }
+
+    pub fn query<'a>(&self, value: &'a JsonValue) -> Vec<Node<'a>> {
+        let root = Node {
+            path: "$".to_string(),
+            value,
+        };
+        select(&self.segments, value, vec![root])
+    }
+}
+
+impl JsonValue {
+    pub fn query(&self, path: &str) -> Result<Vec<Node<'_>>, PathError> {
+        Ok(JsonPath::parse(path)?.query(self))
+    }
+}
+
+fn select<'a>(
+    segments: &[Segment],
+    root: &'a JsonValue,
+    mut nodes: Vec<Node<'a>>,
+) -> Vec<Node<'a>> {
+    for segment in segments {
+        let mut visited = Vec::new();
+        for node in nodes {
+            if segment.descendant {
+                descendants(node, &mut visited);
+            } else {
+                visited.push(node);
+            }
+        }
+        nodes = Vec::new();
+        for node in &visited {
+            for selector in &segment.selectors {
+                selector.apply(root, node, &mut nodes);
+            }
+        }
+    }
+    nodes
+}
+
+fn descendants<'a>(node: Node<'a>, nodes: &mut Vec<Node<'a>>) {
+    let children = children(&node);
+    nodes.push(node);
+    for child in children {
+        descendants(child, nodes);
+    }
+}
+
+fn children<'a>(node: &Node<'a>) -> Vec<Node<'a>> {
+    match node.value {
+        JsonValue::Object(object) => object
+            .iter()
+            .map(|(key, value)| member(node, key, value))
+            .collect(),
+        JsonValue::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| element(node, i, value))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn member<'a>(parent: &Node, key: &str, value: &'a JsonValue) -> Node<'a> {
+    let mut path = parent.path.clone();
+    path.push_str("['");
+    for c in key.chars() {
+        match c {
+            '\'' => path.push_str("\\'"),
+            '\\' => path.push_str("\\\\"),
+            '\u{8}' => path.push_str("\\b"),
+            '\u{c}' => path.push_str("\\f"),
+            '\n' => path.push_str("\\n"),
+            '\r' => path.push_str("\\r"),
+            '\t' => path.push_str("\\t"),
+            c if c < ' ' => path.push_str(&format!("\\u{:04x}", c as u32)),
+            c => path.push(c),
+        }
+    }
+    path.push_str("']");
+    Node { path, value }
+}
+
+fn element<'a>(parent: &Node, index: usize, value: &'a JsonValue) -> Node<'a> {
+    Node {
+        path: format!("{}[{index}]", parent.path),
+        value,
+    }
+}
+
+impl Selector {
+    fn apply<'a>(&self, root: &'a JsonValue, node: &Node<'a>, nodes: &mut Vec<Node<'a>>) {
+        match (self, node.value) {
+            (Selector::Name(name), JsonValue::Object(object)) => {
+                if let Some(value) = object.get(name) {
+                    nodes.push(member(node, name, value));
+                }
+            }
+            (Selector::Wildcard, _) => nodes.extend(children(node)),
+            (Selector::Index(i), JsonValue::Array(values)) => {
+                let len = values.len() as i64;
+                let i = if *i < 0 { len + i } else { *i };
+                if (0..len).contains(&i) {
+                    nodes.push(element(node, i as usize, &values[i as usize]));
+                }
+            }
+            (Selector::Slice(start, end, step), JsonValue::Array(values)) => {
+                let len = values.len() as i64;
+                let step = step.unwrap_or(1);
+                let normalize = |i: i64| if i < 0 { len + i } else { i };
+                let indices: Vec<i64> = match step.cmp(&0) {
+                    Ordering::Equal => Vec::new(),
+                    Ordering::Greater => {
+                        let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+                        let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+                        (lower..upper).step_by(step as usize).collect()
+                    }
+                    Ordering::Less => {
+                        let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+                        let lower = normalize(end.unwrap_or(-len - 1)).clamp(-1, len - 1);
+                        (lower + 1..=upper)
+                            .rev()
+                            .step_by(step.unsigned_abs() as usize)
+                            .collect()
+                    }
+                };
+                for i in indices {
+                    nodes.push(element(node, i as usize, &values[i as usize]));
+                }
+            }
+            (Selector::Filter(filter), _) => nodes.extend(
+                children(node)
+                    .into_iter()
+                    .filter(|child| filter.test(root, child.value)),
+            ),
+            _ => {}
+        }
+    }
+}
+
+impl Filter {
+    fn test(&self, root: &JsonValue, current: &JsonValue) -> bool {
+        match self {
+            Filter::Or(operands) => operands.iter().any(|f| f.test(root, current)),
+            Filter::And(operands) => operands.iter().all(|f| f.test(root, current)),
+            Filter::Not(operand) => !operand.test(root, current),
+            Filter::Exists(query) => !query.evaluate(root, current).is_empty(),
+            Filter::Compare(left, operator, right) => {
+                let left = left.value(root, current);
+                let right = right.value(root, current);
+                match operator {
+                    Operator::Equal => equal(left, right),
+                    Operator::NotEqual => !equal(left, right),
+                    Operator::Less => less(left, right),
+                    Operator::LessOrEqual => less(left, right) || equal(left, right),
+                    Operator::Greater => less(right, left),
+                    Operator::GreaterOrEqual => less(right, left) || equal(left, right),
+                }
+            }
+        }
+    }
+}
+
+impl Query {
+    fn evaluate<'a>(&self, root: &'a JsonValue, current: &'a JsonValue) -> Vec<Node<'a>> {
+        let (path, value) = if self.current {
+            ("@", current)
+        } else {
+            ("$", root)
+        };
+        let start = Node {
+            path: path.to_string(),
+            value,
+        };
+        select(&self.segments, root, vec![start])
+    }
+
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors[..],
+                    [Selector::Name(_)] | [Selector::Index(_)]
+                )
+        })
+    }
+}
+
+impl Comparable {
+    fn value<'a>(&'a self, root: &'a JsonValue, current: &'a JsonValue) -> Option<&'a JsonValue> {
+        match self {
+            Comparable::Literal(value) => Some(value),
+            Comparable::Query(query) => query.evaluate(root, current).pop().map(|node| node.value),
+        }
+    }
+}
+
+fn equal(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (None, None) => true,
+        (Some(left), Some(right)) => equal_values(left, right),
+        _ => false,
+    }
+}
+
+fn equal_values(left: &JsonValue, right: &JsonValue) -> bool {
+    match (left, right) {
+        (JsonValue::Number(a), JsonValue::Number(b)) => compare(a, b) == Some(Ordering::Equal),
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal_values(a, b))
+        }
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.get(key).is_some_and(|b| equal_values(a, b)))
+        }
+        _ => left == right,
+    }
+}
+
+fn less(left: Option<&JsonValue>, right: Option<&JsonValue>) -> bool {
+    match (left, right) {
+        (Some(JsonValue::Number(a)), Some(JsonValue::Number(b))) => {
+            compare(a, b) == Some(Ordering::Less)
+        }
+        (Some(JsonValue::String(a)), Some(JsonValue::String(b))) => a < b,
+        _ => false,
+    }
+}
+
+fn compare(a: &Number, b: &Number) -> Option<Ordering> {
+    let integer = |n: &Number| match n {
+        Number::Unsigned(n) => Some(i128::from(*n)),
+        Number::Integer(n) => Some(i128::from(*n)),
+        _ => None,
+    };
+    match (integer(a), integer(b)) {
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        _ => a.as_f64().partial_cmp(&b.as_f64()),
+    }
+}
+
+struct Parser<'q> {
+    query: &'q str,
+    offset: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.query[self.offset..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.eat_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let found = self.query[self.offset..].starts_with(s);
+        if found {
+            self.offset += s.len();
+        }
+        found
+    }
+
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.offset += 1;
+        }
+    }
+
+    fn error(&self, expected: &'static str) -> PathError {
+        self.error_at(self.offset, expected)
+    }
+
+    fn error_at(&self, offset: usize, expected: &'static str) -> PathError {
+        PathError {
+            offset,
+            found: self.query[offset..].chars().next(),
+            expected,
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, PathError> {
+        let mut segments = Vec::new();
+        loop {
+            let start = self.offset;
+            self.skip_blank();
+            match self.peek() {
+                Some('.' | '[') => segments.push(self.segment()?),
+                _ => {
+                    self.offset = start;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn segment(&mut self) -> Result<Segment, PathError> {
+        if self.eat('[') {
+            let selectors = self.bracketed()?;
+            return Ok(Segment {
+                descendant: false,
+                selectors,
+            });
+        }
+        self.bump();
+        let descendant = self.eat('.');
+        let selectors = match self.peek() {
+            Some('[') if descendant => {
+                self.bump();
+                self.bracketed()?
+            }
+            Some('*') => {
+                self.bump();
+                vec![Selector::Wildcard]
+            }
+            Some(c) if is_name_first(c) => {
+                let start = self.offset;
+                while self
+                    .peek()
+                    .is_some_and(|c| is_name_first(c) || c.is_ascii_digit())
+                {
+                    self.bump();
+                }
+                vec![Selector::Name(self.query[start..self.offset].to_string())]
+            }
+            _ => return Err(self.error("a member name or '*'")),
+        };
+        Ok(Segment {
+            descendant,
+            selectors,
+        })
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, PathError> {
+        let mut selectors = Vec::new();
+        loop {
+            self.skip_blank();
+            selectors.push(self.selector()?);
+            self.skip_blank();
+            if self.eat(']') {
+                return Ok(selectors);
+            }
+            if !self.eat(',') {
+                return Err(self.error("',' or ']'"));
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, PathError> {
+        match self.peek() {
+            Some('\'' | '"') => return Ok(Selector::Name(self.string()?)),
+            Some('*') => {
+                self.bump();
+                return Ok(Selector::Wildcard);
+            }
+            Some('?') => {
+                self.bump();
+                self.skip_blank();
+                return Ok(Selector::Filter(self.or()?));
+            }
+            _ => {}
+        }
+        let start = self.integer()?;
+        self.skip_blank();
+        if !self.eat(':') {
+            return match start {
+                Some(i) => Ok(Selector::Index(i)),
+                None => Err(self.error("a selector")),
+            };
+        }
+        self.skip_blank();
+        let end = self.integer()?;
+        self.skip_blank();
+        let step = if self.eat(':') {
+            self.skip_blank();
+            self.integer()?
+        } else {
+            None
+        };
+        Ok(Selector::Slice(start, end, step))
+    }
+
+    fn integer(&mut self) -> Result<Option<i64>, PathError> {
+        let start = self.offset;
+        let negative = self.eat('-');
+        let digits = self.offset;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        let text = &self.query[digits..self.offset];
+        if text.is_empty() && !negative {
+            return Ok(None);
+        }
+        if text.is_empty() || (text.starts_with('0') && (text.len() > 1 || negative)) {
+            return Err(self.error_at(start, "an integer without leading zeros"));
+        }
+        match self.query[start..self.offset].parse() {
+            Ok(i) if (-MAX_INTEGER..=MAX_INTEGER).contains(&i) => Ok(Some(i)),
+            _ => Err(self.error_at(start, "an integer within 2^53 - 1")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, PathError> {
+        let quote = self.bump().expect("peeked quote");
+        let mut result = String::new();
+        loop {
+            let start = self.offset;
+            let c = match self.bump() {
+                Some('\\') => match self.bump() {
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('n') => '\n',
+                    Some('r') => '\r',
+                    Some('t') => '\t',
+                    Some(c @ ('/' | '\\')) => c,
+                    Some(c) if c == quote => c,
+                    Some('u') => self.unicode(start)?,
+                    _ => return Err(self.error_at(start, "an escape sequence")),
+                },
+                Some(c) if c == quote => return Ok(result),
+                Some(c) if c >= ' ' => c,
+                _ => return Err(self.error_at(start, "a closing quote")),
+            };
+            result.push(c);
+        }
+    }
+
+    fn unicode(&mut self, start: usize) -> Result<char, PathError> {
+        let unit = self.hex()?;
+        let code = match unit {
+            0xD800..=0xDBFF => {
+                let low = if self.eat_str("\\u") { self.hex()? } else { 0 };
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error_at(start, "a surrogate pair"));
+                }
+                0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(self.error_at(start, "a surrogate pair")),
+            unit => unit,
+        };
+        Ok(char::from_u32(code).expect("valid scalar value"))
+    }
+
+    fn hex(&mut self) -> Result<u32, PathError> {
+        let mut unit = 0;
+        for _ in 0..4 {
+            let start = self.offset;
+            match self.bump().and_then(|c| c.to_digit(16)) {
+                Some(digit) => unit = unit << 4 | digit,
+                None => return Err(self.error_at(start, "a hex digit")),
+            }
+        }
+        Ok(unit)
+    }
+
+    fn or(&mut self) -> Result<Filter, PathError> {
+        self.depth += 1;
+        if self.depth > MAX_FILTER_DEPTH {
+            return Err(self.error("fewer nested filter expressions"));
+        }
+        let mut operands = vec![self.and()?];
+        loop {
+            let start = self.offset;
+            self.skip_blank();
+            if !self.eat_str("||") {
+                self.offset = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.and()?);
+        }
+        self.depth -= 1;
+        Ok(match operands.len() {
+            1 => operands.pop().expect("one operand"),
+            _ => Filter::Or(operands),
+        })
+    }
+
+    fn and(&mut self) -> Result<Filter, PathError> {
+        let mut operands = vec![self.basic()?];
+        loop {
+            let start = self.offset;
+            self.skip_blank();
+            if !self.eat_str("&&") {
+                self.offset = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.basic()?);
+        }
+        Ok(match operands.len() {
+            1 => operands.pop().expect("one operand"),
+            _ => Filter::And(operands),
+        })
+    }
+
+    fn basic(&mut self) -> Result<Filter, PathError> {
+        if self.eat('!') {
+            self.skip_blank();
+            let operand = if self.eat('(') {
+                self.parenthesized()?
+            } else if matches!(self.peek(), Some('@' | '$')) {
+                Filter::Exists(self.filter_query()?)
+            } else {
+                return Err(self.error("'(' or a query after '!'"));
+            };
+            return Ok(Filter::Not(Box::new(operand)));
+        }
+        if self.eat('(') {
+            return self.parenthesized();
+        }
+        let left_start = self.offset;
+        let left = self.comparable()?;
+        let end = self.offset;
+        self.skip_blank();
+        let Some(operator) = self.operator() else {
+            self.offset = end;
+            return match left {
+                Comparable::Query(query) => Ok(Filter::Exists(query)),
+                Comparable::Literal(_) => Err(self.error("a comparison operator")),
+            };
+        };
+        self.skip_blank();
+        let right_start = self.offset;
+        let right = self.comparable()?;
+        for (start, comparable) in [(left_start, &left), (right_start, &right)] {
+            if matches!(comparable, Comparable::Query(query) if !query.is_singular()) {
+                return Err(self.error_at(start, "a singular query in a comparison"));
+            }
+        }
+        Ok(Filter::Compare(left, operator, right))
+    }
+
+    fn parenthesized(&mut self) -> Result<Filter, PathError> {
+        self.skip_blank();
+        let filter = self.or()?;
+        self.skip_blank();
+        if !self.eat(')') {
+            return Err(self.error("')'"));
+        }
+        Ok(filter)
+    }
+
+    fn operator(&mut self) -> Option<Operator> {
+        let operators = [
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ];
+        operators
+            .into_iter()
+            .find(|(text, _)| self.eat_str(text))
+            .map(|(_, operator)| operator)
+    }
+
+    fn comparable(&mut self) -> Result<Comparable, PathError> {
+        let literal = match self.peek() {
+            Some('@' | '$') => return Ok(Comparable::Query(self.filter_query()?)),
+            Some('\'' | '"') => JsonValue::String(self.string()?),
+            Some('-' | '0'..='9') => JsonValue::Number(self.number()?),
+            _ if self.eat_str("true") => JsonValue::Bool(true),
+            _ if self.eat_str("false") => JsonValue::Bool(false),
+            _ if self.eat_str("null") => JsonValue::Null,
+            _ => return Err(self.error("a query or literal")),
+        };
+        Ok(Comparable::Literal(literal))
+    }
+
+    fn filter_query(&mut self) -> Result<Query, PathError> {
+        let current = self.bump() == Some('@');
+        let segments = self.segments()?;
+        Ok(Query { current, segments })
+    }
+
+    fn number(&mut self) -> Result<Number, PathError> {
+        let start = self.offset;
+        self.eat('-');
+        if !self.eat('0') && !self.digits() {
+            return Err(self.error("a digit"));
+        }
+        if self.eat('.') && !self.digits() {
+            return Err(self.error("a digit"));
+        }
+        if self.eat('e') || self.eat('E') {
+            let _ = self.eat('+') || self.eat('-');
+            if !self.digits() {
+                return Err(self.error("a digit"));
+            }
+        }
+        Ok(Number::from_literal(&self.query[start..self.offset], false))
+    }
+
+    fn digits(&mut self) -> bool {
+        let start = self.offset;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        self.offset > start
+    }
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    fn paths(value: &JsonValue, query: &str) -> Vec<String> {
+        let nodes = value.query(query).unwrap();
+        nodes.into_iter().map(|node| node.path).collect()
+    }
+
+    #[test]
+    fn selectors() {
+        let value = parse(
+            r#"{"store": {"book": [
+                {"category": "reference", "author": "Nigel Rees", "price": 8.95},
+                {"category": "fiction", "author": "Evelyn Waugh", "price": 12.99},
+                {"category": "fiction", "author": "Herman Melville", "isbn": "0-553-21311-3", "price": 8.99},
+                {"category": "fiction", "author": "J. R. R. Tolkien", "isbn": "0-395-19395-8", "price": 22.99}
+            ], "bicycle": {"color": "red", "price": 399}}}"#,
+        )
+        .unwrap();
+        let cases: &[(&str, &[&str])] = &[
+            ("$", &["$"]),
+            ("$.store.bicycle.color", &["$['store']['bicycle']['color']"]),
+            (
+                "$.store.book[*].author",
+                &[
+                    "$['store']['book'][0]['author']",
+                    "$['store']['book'][1]['author']",
+                    "$['store']['book'][2]['author']",
+                    "$['store']['book'][3]['author']",
+                ],
+            ),
+            (
+                "$..price",
+                &[
+                    "$['store']['book'][0]['price']",
+                    "$['store']['book'][1]['price']",
+                    "$['store']['book'][2]['price']",
+                    "$['store']['book'][3]['price']",
+                    "$['store']['bicycle']['price']",
+                ],
+            ),
+            (
+                "$.store.*",
+                &["$['store']['book']", "$['store']['bicycle']"],
+            ),
+            ("$..book[2]", &["$['store']['book'][2]"]),
+            ("$..book[-1]", &["$['store']['book'][3]"]),
+            (
+                "$..book[0,1]",
+                &["$['store']['book'][0]", "$['store']['book'][1]"],
+            ),
+            (
+                "$..book[:2]",
+                &["$['store']['book'][0]", "$['store']['book'][1]"],
+            ),
+            (
+                "$..book[?@.isbn]",
+                &["$['store']['book'][2]", "$['store']['book'][3]"],
+            ),
+            (
+                "$..book[?@.price<10]",
+                &["$['store']['book'][0]", "$['store']['book'][2]"],
+            ),
+            (
+                "$.store.book[?@.category == 'fiction' && !(@.price > 20)]",
+                &["$['store']['book'][1]", "$['store']['book'][2]"],
+            ),
+            (
+                "$.store.book[?@.price == $.store.bicycle.price || @.author >= 'J']",
+                &["$['store']['book'][0]", "$['store']['book'][3]"],
+            ),
+            (
+                "$[ 'store' ][\"bicycle\"] ['color']",
+                &["$['store']['bicycle']['color']"],
+            ),
+            ("$.missing", &[]),
+            ("$.store.book.author", &[]),
+        ];
+        for (query, expected) in cases {
+            assert_eq!(*expected, paths(&value, query), "{query}");
+        }
+        let nodes = value.query("$.store.bicycle.price").unwrap();
+        assert_eq!(
+            vec![Node {
+                path: "$['store']['bicycle']['price']".into(),
+                value: &JsonValue::Number(Number::Unsigned(399)),
+            }],
+            nodes
+        );
+    }
+
+    #[test]
+    fn slices() {
+        let value = parse("[0, 1, 2, 3, 4, 5, 6]").unwrap();
+        let cases: &[(&str, &[usize])] = &[
+            ("$[1:3]", &[1, 2]),
+            ("$[5:]", &[5, 6]),
+            ("$[1:5:2]", &[1, 3]),
+            ("$[5:1:-2]", &[5, 3]),
+            ("$[::-1]", &[6, 5, 4, 3, 2, 1, 0]),
+            ("$[-2:]", &[5, 6]),
+            ("$[-100:100:3]", &[0, 3, 6]),
+            ("$[::0]", &[]),
+            ("$[3:1]", &[]),
+            ("$[0, 0, -1]", &[0, 0, 6]),
+            ("$[7]", &[]),
+        ];
+        for (query, expected) in cases {
+            let expected: Vec<_> = expected.iter().map(|i| format!("$[{i}]")).collect();
+            assert_eq!(expected, paths(&value, query), "{query}");
+        }
+    }
+
+    #[test]
+    fn filters() {
+        let value = parse(
+            r#"[{"a": 1}, {"a": 1.0}, {"a": "b"}, {"a": [1, {"b": null}]}, {"a": null}, {}, {"a": {"x": true}}]"#,
+        )
+        .unwrap();
+        let cases: &[(&str, &[usize])] = &[
+            ("$[?@.a == 1]", &[0, 1]),
+            ("$[?@.a != 1]", &[2, 3, 4, 5, 6]),
+            ("$[?@.a == null]", &[4]),
+            ("$[?@.a == @.missing]", &[5]),
+            ("$[?@.a <= 1]", &[0, 1]),
+            ("$[?@.a > 'a']", &[2]),
+            ("$[?@.a == $[3].a]", &[3]),
+            ("$[?@.a == $[6].a]", &[6]),
+            ("$[?@..b]", &[3]),
+            ("$[?!@.a]", &[5]),
+            ("$[?@.a.x == true || @.a == 'b']", &[2, 6]),
+            ("$[?(@.a >= 1) && (@.a < 2)]", &[0, 1]),
+            ("$[?@.a[?@.b == null]]", &[3]),
+            ("$[?1 == 1]", &[0, 1, 2, 3, 4, 5, 6]),
+            ("$[?-0.5e1 < @.a]", &[0, 1]),
+        ];
+        for (query, expected) in cases {
+            let expected: Vec<_> = expected.iter().map(|i| format!("$[{i}]")).collect();
+            assert_eq!(expected, paths(&value, query), "{query}");
+        }
+    }
+
+    #[test]
+    fn normalized_paths() {
+        let value = parse(r#"{"it's": {"a\\b\n\u0001": 0}}"#).unwrap();
+        assert_eq!(
+            vec![r"$['it\'s']['a\\b\n\u0001']"],
+            paths(&value, "$..*")[1..]
+        );
+        assert_eq!(1, value.query(r"$['it\'s'][?@ == 0]").unwrap().len());
+        assert_eq!(1, value.query(r#"$["it's"]"#).unwrap().len());
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            ("", 0, "'$'"),
+            ("$.", 2, "a member name or '*'"),
+            (" $", 0, "'$'"),
+            ("$ ", 1, "'.', '[' or end of query"),
+            ("$.a.[0]", 4, "a member name or '*'"),
+            ("$[01]", 2, "an integer without leading zeros"),
+            ("$[-0]", 2, "an integer without leading zeros"),
+            ("$[9007199254740992]", 2, "an integer within 2^53 - 1"),
+            ("$[0 1]", 4, "',' or ']'"),
+            ("$[]", 2, "a selector"),
+            ("$['a]", 5, "a closing quote"),
+            ("$['\\\"']", 3, "an escape sequence"),
+            ("$[?@.a == 1 && 2]", 16, "a comparison operator"),
+            ("$[?@.* == 1]", 3, "a singular query in a comparison"),
+            ("$[?!@.a == 1]", 8, "',' or ']'"),
+            ("$[?(@.a]", 7, "')'"),
+            ("$[?length(@) == 1]", 3, "a query or literal"),
+        ];
+        for (query, offset, expected) in cases {
+            let error = JsonPath::parse(query).unwrap_err();
+            assert_eq!(
+                (offset, expected),
+                (error.offset, error.expected),
+                "{query}"
+            );
+        }
+        let nested = |depth| format!("$[?{}@{}]", "(".repeat(depth), ")".repeat(depth));
+        assert!(JsonPath::parse(&nested(MAX_FILTER_DEPTH - 1)).is_ok());
+        let error = JsonPath::parse(&nested(MAX_FILTER_DEPTH)).unwrap_err();
+        assert_eq!(
+            (3 + MAX_FILTER_DEPTH, "fewer nested filter expressions"),
+            (error.offset, error.expected)
+        );
+    }
+}
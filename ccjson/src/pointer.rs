@@ -0,0 +1,265 @@
+use std::{error::Error, fmt::Display, ops::Index};
+
+use crate::JsonValue;
+
+static NULL: JsonValue = JsonValue::Null;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PointerError {
+    pub pointer: String,
+    pub expected: &'static str,
+}
+
+impl PointerError {
+    fn new(pointer: &str, expected: &'static str) -> Self {
+        Self {
+            pointer: pointer.to_string(),
+            expected,
+        }
+    }
+}
+
+impl Display for PointerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} in pointer {:?}",
+            self.expected, self.pointer
+        )
+    }
+}
+
+impl Error for PointerError {}
+
+impl JsonValue {
+    pub fn get_pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        tokens(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |value, token| value.child(token))
+    }
+
+    pub fn get_pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        tokens(pointer)
+            .ok()?
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))
+    }
+
+    /// Adds `value` at `pointer` the way a JSON Patch "add" does: object
+    /// members are inserted or replaced, array elements are inserted before
+    /// the given index, and `-` appends. Returns the replaced value, if any.
+    pub fn insert_pointer(
+        &mut self,
+        pointer: &str,
+        value: JsonValue,
+    ) -> Result<Option<JsonValue>, PointerError> {
+        let tokens = tokens(pointer)?;
+        let Some((last, parents)) = tokens.split_last() else {
+            return Ok(Some(std::mem::replace(self, value)));
+        };
+        let parent = parents
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))
+            .ok_or_else(|| PointerError::new(pointer, "an existing parent"))?;
+        match parent {
+            JsonValue::Object(object) => Ok(object.insert(last.clone(), value)),
+            JsonValue::Array(values) if last == "-" => {
+                values.push(value);
+                Ok(None)
+            }
+            JsonValue::Array(values) => {
+                let i = array_index(last, values.len() + 1)
+                    .ok_or_else(|| PointerError::new(pointer, "an array index in bounds"))?;
+                values.insert(i, value);
+                Ok(None)
+            }
+            _ => Err(PointerError::new(pointer, "an object or array parent")),
+        }
+    }
+
+    pub fn remove_pointer(&mut self, pointer: &str) -> Option<JsonValue> {
+        let tokens = tokens(pointer).ok()?;
+        let (last, parents) = tokens.split_last()?;
+        let parent = parents
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))?;
+        match parent {
+            JsonValue::Object(object) => object.remove(last),
+            JsonValue::Array(values) => {
+                let i = array_index(last, values.len())?;
+                Some(values.remove(i))
+            }
+            _ => None,
+        }
+    }
+
+    fn child(&self, token: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(object) => object.get(token),
+            JsonValue::Array(values) => values.get(array_index(token, values.len())?),
+            _ => None,
+        }
+    }
+
+    fn child_mut(&mut self, token: &str) -> Option<&mut JsonValue> {
+        match self {
+            JsonValue::Object(object) => object.get_mut(token),
+            JsonValue::Array(values) => {
+                let i = array_index(token, values.len())?;
+                values.get_mut(i)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        match self {
+            JsonValue::Object(object) => object.get(key).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        match self {
+            JsonValue::Array(values) => values.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+fn tokens(pointer: &str) -> Result<Vec<String>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(PointerError::new(pointer, "'/' at the start"));
+    };
+    rest.split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                unescaped.push(match c {
+                    '~' => match chars.next() {
+                        Some('0') => '~',
+                        Some('1') => '/',
+                        _ => return Err(PointerError::new(pointer, "'~0' or '~1'")),
+                    },
+                    c => c,
+                });
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+fn array_index(token: &str, len: usize) -> Option<usize> {
+    let digits = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit());
+    if !digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok().filter(|&i| i < len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, Number};
+
+    #[test]
+    fn get() {
+        let value = parse(
+            r#"{"foo": ["bar", "baz"], "": 0, "a/b": 1, "c%d": 2, "e^f": 3, "g|h": 4, "i\\j": 5, "k\"l": 6, " ": 7, "m~n": 8}"#,
+        )
+        .unwrap();
+        assert_eq!(Some(&value), value.get_pointer(""));
+        assert_eq!(Some(&value["foo"]), value.get_pointer("/foo"));
+        let cases = [
+            ("/foo/0", JsonValue::String("bar".into())),
+            ("/", JsonValue::Number(Number::Unsigned(0))),
+            ("/a~1b", JsonValue::Number(Number::Unsigned(1))),
+            ("/c%d", JsonValue::Number(Number::Unsigned(2))),
+            ("/e^f", JsonValue::Number(Number::Unsigned(3))),
+            ("/g|h", JsonValue::Number(Number::Unsigned(4))),
+            ("/i\\j", JsonValue::Number(Number::Unsigned(5))),
+            ("/k\"l", JsonValue::Number(Number::Unsigned(6))),
+            ("/ ", JsonValue::Number(Number::Unsigned(7))),
+            ("/m~0n", JsonValue::Number(Number::Unsigned(8))),
+        ];
+        for (pointer, expected) in cases {
+            assert_eq!(Some(&expected), value.get_pointer(pointer), "{pointer}");
+        }
+        for pointer in ["foo", "/foo/2", "/foo/01", "/foo/-", "/foo/0/x", "/m~2n"] {
+            assert_eq!(None, value.get_pointer(pointer), "{pointer}");
+        }
+        assert_eq!(JsonValue::String("baz".into()), value["foo"][1]);
+        assert_eq!(JsonValue::Null, value["missing"][0]["x"]);
+    }
+
+    #[test]
+    fn mutate() {
+        let mut value = parse(r#"{"a": {"b": [1, 2]}}"#).unwrap();
+        *value.get_pointer_mut("/a/b/0").unwrap() = JsonValue::Bool(true);
+        assert_eq!(Ok(None), value.insert_pointer("/a/b/1", JsonValue::Null));
+        assert_eq!(Ok(None), value.insert_pointer("/a/b/-", JsonValue::Null));
+        assert_eq!(
+            Ok(None),
+            value.insert_pointer("/a/c~1d", JsonValue::Array(vec![]))
+        );
+        assert_eq!(
+            Ok(Some(JsonValue::Array(vec![]))),
+            value.insert_pointer("/a/c~1d", JsonValue::Bool(false))
+        );
+        assert_eq!(
+            parse(r#"{"a": {"b": [true, null, 2, null], "c/d": false}}"#).unwrap(),
+            value
+        );
+
+        assert_eq!(Some(JsonValue::Null), value.remove_pointer("/a/b/1"));
+        assert_eq!(
+            Some(JsonValue::Bool(false)),
+            value.remove_pointer("/a/c~1d")
+        );
+        assert_eq!(None, value.remove_pointer("/a/b/3"));
+        assert_eq!(None, value.remove_pointer(""));
+        assert_eq!(parse(r#"{"a": {"b": [true, 2, null]}}"#).unwrap(), value);
+
+        let error = |pointer, expected| {
+            Err(PointerError {
+                pointer: String::from(pointer),
+                expected,
+            })
+        };
+        assert_eq!(
+            error("/x/y", "an existing parent"),
+            value.insert_pointer("/x/y", JsonValue::Null)
+        );
+        assert_eq!(
+            error("/a/b/4", "an array index in bounds"),
+            value.insert_pointer("/a/b/4", JsonValue::Null)
+        );
+        assert_eq!(
+            error("/a/b/0/x", "an object or array parent"),
+            value.insert_pointer("/a/b/0/x", JsonValue::Null)
+        );
+        assert_eq!(
+            error("a", "'/' at the start"),
+            value.insert_pointer("a", JsonValue::Null)
+        );
+
+        assert_eq!(
+            Ok(Some(value.clone())),
+            value.insert_pointer("", JsonValue::Null)
+        );
+        assert_eq!(JsonValue::Null, value);
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::Display,
+    io::{self, Read},
+};
+
+use crate::{error::Position, JsonValue, Machine, ParseError, ParseOptions};
+
+const CHUNK_SIZE: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    Value(JsonValue),
+}
+
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Io(e) => write!(f, "{e}"),
+            ReadError::Parse(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for ReadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadError::Io(e) => Some(e),
+            ReadError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ReadError {
+    fn from(value: io::Error) -> Self {
+        ReadError::Io(value)
+    }
+}
+
+impl From<ParseError> for ReadError {
+    fn from(value: ParseError) -> Self {
+        ReadError::Parse(value)
+    }
+}
+
+pub struct Reader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    consumed: usize,
+    machine: Machine,
+    events: VecDeque<(Position, Event)>,
+    position: Position,
+    error: Option<ReadError>,
+    finished: bool,
+}
+
+impl<R: Read> Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, &ParseOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: &ParseOptions) -> Self {
+        Self {
+            reader,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            consumed: 0,
+            machine: Machine::new(options),
+            events: VecDeque::new(),
+            position: Position::default(),
+            error: None,
+            finished: false,
+        }
+    }
+
+    pub fn next_event(&mut self) -> Result<Option<Event>, ReadError> {
+        Ok(self.next_positioned()?.map(|(_, event)| event))
+    }
+
+    pub(crate) fn next_positioned(&mut self) -> Result<Option<(Position, Event)>, ReadError> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(Some(event));
+            }
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+            if self.finished {
+                return Ok(None);
+            }
+            let result = match self.next_char() {
+                Ok(Some(c)) => {
+                    let result = self.machine.next(c, self.position, &mut self.events);
+                    self.position.advance(c);
+                    result.map_err(ReadError::from)
+                }
+                Ok(None) => {
+                    self.finished = true;
+                    self.machine
+                        .finish(self.position, &mut self.events)
+                        .map_err(ReadError::from)
+                }
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                self.finished = true;
+                self.error = Some(error);
+            }
+        }
+    }
+
+    fn next_char(&mut self) -> Result<Option<char>, ReadError> {
+        if !self.fill(1)? {
+            return Ok(None);
+        }
+        let width = match self.buffer[self.consumed] {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => 0,
+        };
+        let position = self.position;
+        let invalid = || ParseError::at(position, None, "valid UTF-8");
+        if width == 0 || !self.fill(width)? {
+            return Err(invalid().into());
+        }
+        let bytes = &self.buffer[self.consumed..self.consumed + width];
+        let c = match std::str::from_utf8(bytes) {
+            Ok(s) => s.chars().next().expect("one character"),
+            Err(_) => return Err(invalid().into()),
+        };
+        self.consumed += width;
+        Ok(Some(c))
+    }
+
+    fn fill(&mut self, needed: usize) -> io::Result<bool> {
+        let mut chunk = [0; CHUNK_SIZE];
+        while self.buffer.len() - self.consumed < needed {
+            self.buffer.drain(..self.consumed);
+            self.consumed = 0;
+            let read = match self.reader.read(&mut chunk) {
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if read == 0 {
+                return Ok(false);
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for Reader<R> {
+    type Item = Result<Event, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Number;
+
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = *first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn events() {
+        let input = r#"{"name": "Zoë", "tags": [1, true, null, {}], "nested": {"a": []}}"#;
+        let events: Result<Vec<_>, _> = Reader::new(Trickle(input.as_bytes())).collect();
+        assert_eq!(
+            vec![
+                Event::StartObject,
+                Event::Key("name".into()),
+                Event::Value(JsonValue::String("Zoë".into())),
+                Event::Key("tags".into()),
+                Event::StartArray,
+                Event::Value(JsonValue::Number(Number::Unsigned(1))),
+                Event::Value(JsonValue::Bool(true)),
+                Event::Value(JsonValue::Null),
+                Event::StartObject,
+                Event::EndObject,
+                Event::EndArray,
+                Event::Key("nested".into()),
+                Event::StartObject,
+                Event::Key("a".into()),
+                Event::StartArray,
+                Event::EndArray,
+                Event::EndObject,
+                Event::EndObject,
+            ],
+            events.unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut reader = Reader::new(&b"[1, 2x]"[..]);
+        assert_eq!(Some(Event::StartArray), reader.next_event().unwrap());
+        assert_eq!(
+            Some(Event::Value(JsonValue::Number(Number::Unsigned(1)))),
+            reader.next_event().unwrap()
+        );
+        assert_eq!(
+            Some(Event::Value(JsonValue::Number(Number::Unsigned(2)))),
+            reader.next_event().unwrap()
+        );
+        let Err(ReadError::Parse(error)) = reader.next_event() else {
+            panic!("expected a parse error");
+        };
+        assert_eq!((5, Some('x')), (error.offset, error.found));
+        assert!(reader.next().is_none());
+
+        let mut reader = Reader::new(&b"[\"\xff\"]"[..]);
+        let error = reader.find_map(Result::err);
+        assert!(matches!(error, Some(ReadError::Parse(e)) if e.expected == "valid UTF-8"));
+
+        let mut reader = Reader::new(&b"[\"\xc3"[..]);
+        let error = reader.find_map(Result::err);
+        assert!(matches!(error, Some(ReadError::Parse(e)) if e.offset == 2));
+    }
+
+    struct Elements {
+        opened: bool,
+        remaining: usize,
+    }
+
+    impl Read for Elements {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let chunk: &[u8] = if !self.opened {
+                self.opened = true;
+                b"["
+            } else if self.remaining > 1 {
+                self.remaining -= 1;
+                b"0,"
+            } else if self.remaining == 1 {
+                self.remaining = 0;
+                b"0]"
+            } else {
+                b""
+            };
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn bounded_memory() {
+        let elements = 100_000;
+        let mut reader = Reader::new(Elements {
+            opened: false,
+            remaining: elements,
+        });
+        let mut values = 0;
+        while let Some(event) = reader.next_event().unwrap() {
+            if let Event::Value(_) = event {
+                values += 1;
+            }
+            assert!(reader.buffer.capacity() <= CHUNK_SIZE);
+            assert!(reader.events.len() <= 2);
+        }
+        assert_eq!(elements, values);
+    }
+}
@@ -0,0 +1,437 @@
+use std::io;
+
+use serde::{
+    ser::{
+        SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize,
+};
+
+use crate::{Error, JsonValue, Map, Number, WriteOptions};
+
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<JsonValue, Error> {
+    value.serialize(Serializer)
+}
+
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    Ok(to_value(value)?.to_string())
+}
+
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    Ok(to_value(value)?.to_string_pretty())
+}
+
+pub fn to_writer<W: io::Write, T: Serialize + ?Sized>(
+    writer: &mut W,
+    value: &T,
+    options: &WriteOptions,
+) -> Result<(), Error> {
+    to_value(value)?
+        .write_to(writer, options)
+        .map_err(Error::new)
+}
+
+impl Serialize for JsonValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::String(s) => serializer.serialize_str(s),
+            JsonValue::Number(Number::Unsigned(n)) => serializer.serialize_u64(*n),
+            JsonValue::Number(Number::Integer(n)) => serializer.serialize_i64(*n),
+            JsonValue::Number(n) => serializer.serialize_f64(n.as_f64()),
+            JsonValue::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (key, value) in object.iter() {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            JsonValue::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            JsonValue::Bool(b) => serializer.serialize_bool(*b),
+            JsonValue::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
+pub struct Serializer;
+
+impl serde::Serializer for Serializer {
+    type Ok = JsonValue;
+    type Error = Error;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeVariant<SerializeArray>;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeVariant<SerializeObject>;
+
+    fn serialize_bool(self, v: bool) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<JsonValue, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<JsonValue, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<JsonValue, Error> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Number(v.into()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<JsonValue, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<JsonValue, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<JsonValue, Error> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Number(v.into()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<JsonValue, Error> {
+        self.serialize_f64(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Number(v.into()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<JsonValue, Error> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<JsonValue, Error> {
+        Ok(JsonValue::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Array(
+            v.iter()
+                .map(|&b| JsonValue::Number(u64::from(b).into()))
+                .collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<JsonValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<JsonValue, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<JsonValue, Error> {
+        let mut object = Map::new();
+        object.insert(variant.to_string(), value.serialize(self)?);
+        Ok(JsonValue::Object(object))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            values: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeArray>, Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            object: Map::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeObject>, Error> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+pub struct SerializeArray {
+    values: Vec<JsonValue>,
+}
+
+impl SerializeSeq for SerializeArray {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let index = self.values.len();
+        let value = to_value(value).map_err(|e| e.within(&format!("[{index}]")))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Array(self.values))
+    }
+}
+
+impl SerializeTuple for SerializeArray {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SerializeArray {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+pub struct SerializeObject {
+    object: Map,
+    key: Option<String>,
+}
+
+impl SerializeMap for SerializeObject {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = match to_value(key)? {
+            JsonValue::String(s) => s,
+            JsonValue::Number(n) => n.to_string(),
+            JsonValue::Bool(b) => b.to_string(),
+            _ => return Err(Error::new("object key must be a string")),
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().expect("serialize_key is called first");
+        let value = to_value(value).map_err(|e| e.within(&format!(".{key}")))?;
+        self.object.insert(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        Ok(JsonValue::Object(self.object))
+    }
+}
+
+impl SerializeStruct for SerializeObject {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        SerializeMap::end(self)
+    }
+}
+
+pub struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl<T> SerializeVariant<T> {
+    fn wrap(variant: &'static str, value: JsonValue) -> Result<JsonValue, Error> {
+        let mut object = Map::new();
+        object.insert(variant.to_string(), value);
+        Ok(JsonValue::Object(object))
+    }
+}
+
+impl SerializeTupleVariant for SerializeVariant<SerializeArray> {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(&mut self.inner, value)
+            .map_err(|e| e.within(&format!(".{}", self.variant)))
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        Self::wrap(self.variant, SerializeSeq::end(self.inner)?)
+    }
+}
+
+impl SerializeStructVariant for SerializeVariant<SerializeObject> {
+    type Ok = JsonValue;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        SerializeMap::serialize_entry(&mut self.inner, key, value)
+            .map_err(|e| e.within(&format!(".{}", self.variant)))
+    }
+
+    fn end(self) -> Result<JsonValue, Error> {
+        Self::wrap(self.variant, SerializeMap::end(self.inner)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Config {
+        name: String,
+        port: u16,
+        ratio: f64,
+        tags: Vec<&'static str>,
+        limits: BTreeMap<u32, i64>,
+        owner: Option<String>,
+        mode: Mode,
+        shapes: Vec<Shape>,
+    }
+
+    #[derive(Serialize)]
+    enum Mode {
+        Fast,
+    }
+
+    #[derive(Serialize)]
+    enum Shape {
+        Circle(f64),
+        Point(i32, i32),
+        Rect { w: u8, h: u8 },
+    }
+
+    #[test]
+    fn serialize() {
+        let config = Config {
+            name: "api \"v2\"".into(),
+            port: 8080,
+            ratio: 0.5,
+            tags: vec!["a", "b"],
+            limits: BTreeMap::from([(1, -1), (2, 2)]),
+            owner: None,
+            mode: Mode::Fast,
+            shapes: vec![
+                Shape::Circle(1.0),
+                Shape::Point(1, -2),
+                Shape::Rect { w: 3, h: 4 },
+            ],
+        };
+        assert_eq!(
+            r#"{"name":"api \"v2\"","port":8080,"ratio":0.5,"tags":["a","b"],"limits":{"1":-1,"2":2},"owner":null,"mode":"Fast","shapes":[{"Circle":1.0},{"Point":[1,-2]},{"Rect":{"w":3,"h":4}}]}"#,
+            to_string(&config).unwrap()
+        );
+        let value = crate::parse(&to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(to_value(&config).unwrap(), value);
+        assert_eq!(value, to_value(&value).unwrap());
+    }
+
+    #[test]
+    fn errors() {
+        let keys = BTreeMap::from([((1, 2), 3)]);
+        let nested = BTreeMap::from([("outer", vec![keys])]);
+        let error = to_string(&nested).unwrap_err();
+        assert_eq!("object key must be a string at outer[0]", error.to_string());
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use crate::{JsonValue, Number};
+
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+    pub indent: Option<String>,
+    pub sort_keys: bool,
+}
+
+impl WriteOptions {
+    pub fn compact() -> Self {
+        Self::default()
+    }
+
+    pub fn pretty(indent: usize) -> Self {
+        Self {
+            indent: Some(" ".repeat(indent)),
+            sort_keys: false,
+        }
+    }
+}
+
+impl JsonValue {
+    pub fn write_to<W: Write>(&self, writer: &mut W, options: &WriteOptions) -> io::Result<()> {
+        write_value(writer, self, options, 0)
+    }
+
+    pub fn to_string_with_options(&self, options: &WriteOptions) -> String {
+        let mut buffer = Vec::new();
+        self.write_to(&mut buffer, options)
+            .expect("writing to a Vec does not fail");
+        String::from_utf8(buffer).expect("serialized JSON is UTF-8")
+    }
+
+    pub fn to_string_pretty(&self) -> String {
+        self.to_string_with_options(&WriteOptions::pretty(2))
+    }
+}
+
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.to_string_with_options(&WriteOptions::compact())
+        )
+    }
+}
+
+fn write_value<W: Write>(
+    writer: &mut W,
+    value: &JsonValue,
+    options: &WriteOptions,
+    depth: usize,
+) -> io::Result<()> {
+    match value {
+        JsonValue::String(s) => write_string(writer, s),
+        JsonValue::Number(n) => write_number(writer, n),
+        JsonValue::Bool(b) => write!(writer, "{b}"),
+        JsonValue::Null => write!(writer, "null"),
+        JsonValue::Array(values) => {
+            if values.is_empty() {
+                return write!(writer, "[]");
+            }
+            write!(writer, "[")?;
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    write!(writer, ",")?;
+                }
+                write_newline(writer, options, depth + 1)?;
+                write_value(writer, value, options, depth + 1)?;
+            }
+            write_newline(writer, options, depth)?;
+            write!(writer, "]")
+        }
+        JsonValue::Object(object) => {
+            if object.is_empty() {
+                return write!(writer, "{{}}");
+            }
+            let mut members: Vec<_> = object.iter().collect();
+            if options.sort_keys {
+                members.sort_by_key(|(key, _)| *key);
+            }
+            write!(writer, "{{")?;
+            for (i, (key, value)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    write!(writer, ",")?;
+                }
+                write_newline(writer, options, depth + 1)?;
+                write_string(writer, key)?;
+                if options.indent.is_some() {
+                    write!(writer, ": ")?;
+                } else {
+                    write!(writer, ":")?;
+                }
+                write_value(writer, value, options, depth + 1)?;
+            }
+            write_newline(writer, options, depth)?;
+            write!(writer, "}}")
+        }
+    }
+}
+
+fn write_newline<W: Write>(writer: &mut W, options: &WriteOptions, depth: usize) -> io::Result<()> {
+    if let Some(indent) = &options.indent {
+        writeln!(writer)?;
+        for _ in 0..depth {
+            write!(writer, "{indent}")?;
+        }
+    }
+    Ok(())
+}
+
+fn write_number<W: Write>(writer: &mut W, number: &Number) -> io::Result<()> {
+    match number {
+        Number::Float(n) if !n.is_finite() => write!(writer, "null"),
+        Number::Float(n) => write!(writer, "{n:?}"),
+        n => write!(writer, "{n}"),
+    }
+}
+
+fn write_string<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            c if c < ' ' => "",
+            _ => continue,
+        };
+        writer.write_all(&s.as_bytes()[start..i])?;
+        if escaped.is_empty() {
+            write!(writer, "\\u{:04x}", c as u32)?;
+        } else {
+            write!(writer, "{escaped}")?;
+        }
+        start = i + c.len_utf8();
+    }
+    writer.write_all(&s.as_bytes()[start..])?;
+    write!(writer, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use std::fs;
+
+    #[test]
+    fn compact() {
+        let value = parse(r#"{"a": [1, -2, 2.0, 1.5e-7, 1e300, {}, []], "b": null}"#).unwrap();
+        let options = WriteOptions {
+            sort_keys: true,
+            ..WriteOptions::compact()
+        };
+        assert_eq!(
+            r#"{"a":[1,-2,2.0,1.5e-7,1e300,{},[]],"b":null}"#,
+            value.to_string_with_options(&options)
+        );
+        assert_eq!(
+            "null",
+            JsonValue::Number(Number::Float(f64::NAN)).to_string()
+        );
+        assert_eq!(
+            "[1e400,-2E+999]",
+            parse("[1e400, -2E+999]").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn pretty() {
+        let value = parse(r#"{"b": {"c": [true, false]}, "a": []}"#).unwrap();
+        let options = WriteOptions {
+            sort_keys: true,
+            ..WriteOptions::pretty(4)
+        };
+        let expected = r#"{
+    "a": [],
+    "b": {
+        "c": [
+            true,
+            false
+        ]
+    }
+}"#;
+        assert_eq!(expected, value.to_string_with_options(&options));
+        let mut buffer = Vec::new();
+        value.write_to(&mut buffer, &options).unwrap();
+        assert_eq!(expected.as_bytes(), buffer);
+    }
+
+    #[test]
+    fn escapes() {
+        let value = JsonValue::String("\"\\/\u{8}\u{c}\n\r\t\u{1}\u{1f}é\u{1f600}".into());
+        let expected = r#""\"\\/\b\f\n\r\t"#.to_string() + "\\u0001\\u001fé\u{1f600}\"";
+        assert_eq!(expected, value.to_string());
+        assert_eq!(
+            Ok(JsonValue::Array(vec![value.clone()])),
+            parse(&format!("[{value}]"))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let folder = concat!(env!("CARGO_MANIFEST_DIR"), "/test");
+        for entry in fs::read_dir(folder).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_str().unwrap();
+            if !name.starts_with("pass") {
+                continue;
+            }
+            let value = parse(&fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(Ok(&value), parse(&value.to_string()).as_ref());
+            assert_eq!(Ok(&value), parse(&value.to_string_pretty()).as_ref());
+        }
+    }
+}
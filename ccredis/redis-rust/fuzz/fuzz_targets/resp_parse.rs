@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use redis_rust::resp::{Resp, MAX_INLINE_LEN};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(resps) = Resp::parse(data) {
+        let bytes: Vec<u8> = resps.iter().cloned().flat_map(Vec::from).collect();
+        assert_eq!(Ok(resps), Resp::parse(&bytes));
+    }
+    let mut remaining = data;
+    while let Ok(Some((_, rest))) = Resp::parse_one(remaining) {
+        remaining = rest;
+    }
+    let inline: Vec<u8> = data.iter().copied().filter(|b| *b != b'\n').collect();
+    if inline.first().is_some_and(|b| !b"+-:$*%>".contains(b)) {
+        let oversized = inline.repeat(MAX_INLINE_LEN / inline.len() + 1);
+        assert!(Resp::parse_one(&oversized).is_err());
+    }
+});
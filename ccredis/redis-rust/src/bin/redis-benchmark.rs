@@ -0,0 +1,418 @@
+use std::{
+    env,
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
+
+use redis_rust::{
+    client::Client,
+    config::Config,
+    resp::Resp,
+    server::{Server, ServerThread},
+    worker::Worker,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Test {
+    Set,
+    Get,
+    Incr,
+    LPush,
+}
+
+impl Test {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "set" => Ok(Test::Set),
+            "get" => Ok(Test::Get),
+            "incr" => Ok(Test::Incr),
+            "lpush" => Ok(Test::LPush),
+            _ => Err(format!("unknown test '{name}'")),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Test::Set => "SET",
+            Test::Get => "GET",
+            Test::Incr => "INCR",
+            Test::LPush => "LPUSH",
+        }
+    }
+
+    fn args(self, key: u64, data: &[u8]) -> Vec<Vec<u8>> {
+        let key = format!("{key:012}");
+        match self {
+            Test::Set => vec![b"SET".to_vec(), format!("key:{key}").into(), data.to_vec()],
+            Test::Get => vec![b"GET".to_vec(), format!("key:{key}").into()],
+            Test::Incr => vec![b"INCR".to_vec(), format!("counter:{key}").into()],
+            Test::LPush => vec![b"LPUSH".to_vec(), b"mylist".to_vec(), data.to_vec()],
+        }
+    }
+}
+
+type Workload = Vec<(Test, u32)>;
+
+struct Options {
+    host: String,
+    port: u16,
+    clients: usize,
+    requests: usize,
+    pipeline: usize,
+    data_size: usize,
+    keyspace: u64,
+    workloads: Vec<Workload>,
+    local: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            host: String::from("127.0.0.1"),
+            port: 6379,
+            clients: 50,
+            requests: 100_000,
+            pipeline: 1,
+            data_size: 3,
+            keyspace: 0,
+            workloads: Vec::new(),
+            local: false,
+        };
+        let mut tests = None;
+        let mut mix = None;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+            match arg.as_str() {
+                "-h" => options.host = value()?,
+                "-p" => options.port = number(&value()?)?,
+                "-c" => options.clients = number(&value()?)?,
+                "-n" => options.requests = number(&value()?)?,
+                "-P" => options.pipeline = number(&value()?)?,
+                "-d" => options.data_size = number(&value()?)?,
+                "-r" => options.keyspace = number(&value()?)?,
+                "-t" => tests = Some(value()?),
+                "--mix" => mix = Some(value()?),
+                "--local" => options.local = true,
+                _ => return Err(format!("unknown option '{arg}'")),
+            }
+        }
+        if options.clients == 0 || options.pipeline == 0 {
+            return Err(String::from("clients and pipeline depth must be positive"));
+        }
+        if let Some(tests) = tests {
+            for test in tests.split(',') {
+                options.workloads.push(vec![(Test::parse(test)?, 1)]);
+            }
+        }
+        if let Some(mix) = mix {
+            let workload = parse_mix(&mix)?;
+            if workload.is_empty() {
+                return Err(String::from(
+                    "a command mix needs at least one positive weight",
+                ));
+            }
+            options.workloads.push(workload);
+        }
+        if options.workloads.is_empty() {
+            let tests = [Test::Set, Test::Get, Test::Incr, Test::LPush];
+            options.workloads = tests.iter().map(|test| vec![(*test, 1)]).collect();
+        }
+        Ok(options)
+    }
+
+    fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number '{value}'"))
+}
+
+fn parse_mix(mix: &str) -> Result<Workload, String> {
+    mix.split(',')
+        .map(|entry| match entry.split_once(['=', ':']) {
+            Some((test, weight)) => Ok((Test::parse(test)?, number(weight)?)),
+            None => Ok((Test::parse(entry)?, 1)),
+        })
+        .filter(|entry| !matches!(entry, Ok((_, 0))))
+        .collect()
+}
+
+fn workload_name(workload: &Workload) -> String {
+    workload
+        .iter()
+        .map(|(test, weight)| match workload.len() {
+            1 => test.name().to_string(),
+            _ => format!("{}={weight}", test.name()),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+struct Report {
+    elapsed: Duration,
+    latencies: Vec<Duration>,
+    errors: usize,
+}
+
+impl Report {
+    fn throughput(&self) -> f64 {
+        self.latencies.len() as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+fn percentile(sorted: &[Duration], percentile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = (percentile / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn run_client(options: &Options, workload: &Workload, id: usize, requests: usize) -> Report {
+    let mut report = Report {
+        elapsed: Duration::ZERO,
+        latencies: Vec::with_capacity(requests),
+        errors: 0,
+    };
+    let mut client = match Client::connect(options.address()) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("client {id}: {err}");
+            report.errors = requests;
+            return report;
+        }
+    };
+    let data = vec![b'x'; options.data_size];
+    let weights: u32 = workload.iter().map(|(_, weight)| weight).sum();
+    let mut seed = (id as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+    let mut sent = 0;
+    while sent < requests {
+        let batch = options.pipeline.min(requests - sent);
+        let mut pipeline = client.pipeline();
+        for i in 0..batch {
+            let mut slot = ((id + sent + i) as u32) % weights;
+            let test = workload
+                .iter()
+                .find(|(_, weight)| {
+                    let found = slot < *weight;
+                    slot = slot.saturating_sub(*weight);
+                    found
+                })
+                .map_or(Test::Set, |(test, _)| *test);
+            let key = match options.keyspace {
+                0 => 0,
+                keyspace => xorshift(&mut seed) % keyspace,
+            };
+            pipeline.command(&test.args(key, &data));
+        }
+        let start = Instant::now();
+        match pipeline.execute() {
+            Ok(replies) => {
+                let latency = start.elapsed();
+                for reply in &replies {
+                    match reply {
+                        Resp::SimpleError(_) => report.errors += 1,
+                        _ => report.latencies.push(latency),
+                    }
+                }
+            }
+            Err(err) => {
+                eprintln!("client {id}: {err}");
+                report.errors += requests - sent;
+                return report;
+            }
+        }
+        sent += batch;
+    }
+    report
+}
+
+fn run(options: &Options, workload: &Workload) -> Report {
+    let start = Instant::now();
+    let reports: Vec<Report> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..options.clients)
+            .map(|id| {
+                let requests = options.requests / options.clients
+                    + usize::from(id < options.requests % options.clients);
+                scope.spawn(move || run_client(options, workload, id, requests))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    });
+    let mut latencies: Vec<Duration> = reports
+        .iter()
+        .flat_map(|report| report.latencies.iter().copied())
+        .collect();
+    latencies.sort_unstable();
+    Report {
+        elapsed: start.elapsed(),
+        latencies,
+        errors: reports.iter().map(|report| report.errors).sum(),
+    }
+}
+
+fn print_report(options: &Options, name: &str, report: &Report) {
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let latencies = &report.latencies;
+    let average = latencies.iter().sum::<Duration>() / latencies.len().max(1) as u32;
+    println!("====== {name} ======");
+    println!(
+        "  {} requests completed in {:.2} seconds",
+        latencies.len(),
+        report.elapsed.as_secs_f64()
+    );
+    println!("  {} parallel clients", options.clients);
+    println!("  {} bytes payload", options.data_size);
+    println!("  pipeline depth {}", options.pipeline);
+    println!("  {} error replies", report.errors);
+    println!();
+    println!("Summary:");
+    println!(
+        "  throughput summary: {:.2} requests per second",
+        report.throughput()
+    );
+    println!("  latency summary (msec):");
+    println!(
+        "  {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        "avg", "min", "p50", "p95", "p99", "max"
+    );
+    println!(
+        "  {:>9.3} {:>9.3} {:>9.3} {:>9.3} {:>9.3} {:>9.3}",
+        millis(average),
+        millis(percentile(latencies, 0.0)),
+        millis(percentile(latencies, 50.0)),
+        millis(percentile(latencies, 95.0)),
+        millis(percentile(latencies, 99.0)),
+        millis(percentile(latencies, 100.0)),
+    );
+    println!();
+}
+
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!(
+                "Usage: redis-benchmark [-h host] [-p port] [-c clients] [-n requests] \
+                 [-P pipeline] [-d size] [-r keyspace] [-t tests] [--mix test=weight,...] [--local]"
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let _server = if options.local {
+        match Server::new(&options.address(), Worker::new(Config::default())) {
+            Ok(server) => {
+                let mut server = ServerThread::new(server);
+                server.start();
+                Some(server)
+            }
+            Err(err) => {
+                eprintln!("Could not start a server at {}: {err}", options.address());
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        None
+    };
+    if let Err(err) = Client::connect(options.address()) {
+        eprintln!("Could not connect to Redis at {}: {err}", options.address());
+        return ExitCode::FAILURE;
+    }
+    for workload in &options.workloads {
+        let report = run(&options, workload);
+        print_report(&options, &workload_name(workload), &report);
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_workloads() {
+        let args = [
+            "-c",
+            "4",
+            "-P",
+            "16",
+            "-t",
+            "set,get",
+            "--mix",
+            "set=1,get=9,incr=2,lpush=0",
+        ];
+        let options = Options::parse(args.map(String::from).into_iter()).unwrap();
+        assert_eq!(4, options.clients);
+        assert_eq!(16, options.pipeline);
+        assert_eq!(
+            vec![
+                vec![(Test::Set, 1)],
+                vec![(Test::Get, 1)],
+                vec![(Test::Set, 1), (Test::Get, 9), (Test::Incr, 2)],
+            ],
+            options.workloads
+        );
+        assert_eq!("SET=1,GET=9,INCR=2", workload_name(&options.workloads[2]));
+        assert!(Options::parse(["-t", "flushall"].map(String::from).into_iter()).is_err());
+        let options = Options::parse(std::iter::empty()).unwrap();
+        assert_eq!(
+            vec![
+                vec![(Test::Set, 1)],
+                vec![(Test::Get, 1)],
+                vec![(Test::Incr, 1)],
+                vec![(Test::LPush, 1)],
+            ],
+            options.workloads
+        );
+    }
+
+    #[test]
+    fn command_arguments() {
+        assert_eq!(
+            vec![
+                b"SET".to_vec(),
+                b"key:000000000042".to_vec(),
+                b"xx".to_vec()
+            ],
+            Test::Set.args(42, b"xx")
+        );
+        assert_eq!(
+            vec![b"GET".to_vec(), b"key:000000000000".to_vec()],
+            Test::Get.args(0, b"xx")
+        );
+        assert_eq!(
+            vec![b"INCR".to_vec(), b"counter:000000000000".to_vec()],
+            Test::Incr.args(0, b"xx")
+        );
+        assert_eq!(
+            vec![b"LPUSH".to_vec(), b"mylist".to_vec(), b"xx".to_vec()],
+            Test::LPush.args(7, b"xx")
+        );
+    }
+
+    #[test]
+    fn latency_percentiles() {
+        let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(Duration::from_millis(1), percentile(&latencies, 0.0));
+        assert_eq!(Duration::from_millis(50), percentile(&latencies, 50.0));
+        assert_eq!(Duration::from_millis(99), percentile(&latencies, 99.0));
+        assert_eq!(Duration::from_millis(100), percentile(&latencies, 100.0));
+        assert_eq!(Duration::ZERO, percentile(&[], 50.0));
+    }
+}
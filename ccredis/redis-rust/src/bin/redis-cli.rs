@@ -0,0 +1,325 @@
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use redis_rust::{
+    client::Client,
+    resp::{split_args, Resp},
+};
+use rustyline::{error::ReadlineError, DefaultEditor};
+
+struct Options {
+    host: String,
+    port: u16,
+    db: usize,
+    raw: bool,
+    command: Vec<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            host: String::from("127.0.0.1"),
+            port: 6379,
+            db: 0,
+            raw: !io::stdout().is_terminal(),
+            command: Vec::new(),
+        };
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or(format!("missing value for {name}"));
+            match arg.as_str() {
+                "-h" => options.host = value("-h")?,
+                "-p" => options.port = value("-p")?.parse().map_err(|_| "invalid port")?,
+                "-n" => options.db = value("-n")?.parse().map_err(|_| "invalid db")?,
+                "--raw" => options.raw = true,
+                "--no-raw" => options.raw = false,
+                _ => {
+                    options.command.push(arg);
+                    options.command.extend(args);
+                    break;
+                }
+            }
+        }
+        Ok(options)
+    }
+
+    fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("Usage: redis-cli [-h host] [-p port] [-n db] [--raw] [cmd [arg ...]]");
+            return ExitCode::FAILURE;
+        }
+    };
+    if options.command.is_empty() {
+        repl(options);
+        return ExitCode::SUCCESS;
+    }
+    let mut client = match connect(&options) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Could not connect to Redis at {}: {e}", options.address());
+            return ExitCode::FAILURE;
+        }
+    };
+    match client.request(&options.command) {
+        Ok(reply) => {
+            print_reply(&reply, options.raw);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn connect(options: &Options) -> io::Result<Client> {
+    let mut client = Client::connect(options.address())?;
+    if options.db != 0 {
+        if let Err(e) = client.select(options.db) {
+            return Err(io::Error::other(e.to_string()));
+        }
+    }
+    Ok(client)
+}
+
+fn repl(mut options: Options) {
+    let Ok(mut editor) = DefaultEditor::new() else {
+        eprintln!("Could not initialize the line editor");
+        return;
+    };
+    let history = history_file();
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+    let mut client = None;
+    loop {
+        if client.is_none() {
+            client = match connect(&options) {
+                Ok(client) => Some(client),
+                Err(e) => {
+                    eprintln!("Could not connect to Redis at {}: {e}", options.address());
+                    None
+                }
+            };
+        }
+        let prompt = match (&client, options.db) {
+            (None, _) => String::from("not connected> "),
+            (Some(_), 0) => format!("{}> ", options.address()),
+            (Some(_), db) => format!("{}[{db}]> ", options.address()),
+        };
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted | ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("{e}");
+                break;
+            }
+        };
+        let Some(args) = split_args(line.as_bytes()) else {
+            println!("Invalid argument(s)");
+            continue;
+        };
+        if args.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line.trim());
+        let name = String::from_utf8_lossy(&args[0]).to_lowercase();
+        if name == "quit" || name == "exit" {
+            break;
+        }
+        let Some(connection) = client.as_mut() else {
+            continue;
+        };
+        match connection.request(&args) {
+            Ok(reply) => {
+                if name == "select" && reply == Resp::ok() {
+                    options.db = String::from_utf8_lossy(&args[1]).parse().unwrap_or(0);
+                }
+                for push in connection.take_pushes() {
+                    print_reply(&push, options.raw);
+                }
+                print_reply(&reply, options.raw);
+            }
+            Err(e) => {
+                eprintln!("Error: {e}");
+                client = None;
+            }
+        }
+    }
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+}
+
+fn history_file() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("REDISCLI_HISTFILE") {
+        return Some(PathBuf::from(path)).filter(|path| !path.as_os_str().is_empty());
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".rediscli_history"))
+}
+
+fn print_reply(reply: &Resp, raw: bool) {
+    let mut stdout = io::stdout().lock();
+    let output = if raw {
+        format_raw(reply)
+    } else {
+        format_reply(reply, 0).into_bytes()
+    };
+    let _ = stdout.write_all(&output);
+    let _ = stdout.write_all(b"\n");
+}
+
+fn format_reply(reply: &Resp, indent: usize) -> String {
+    match reply {
+        Resp::SimpleString(s) => s.clone(),
+        Resp::SimpleError(e) => format!("(error) {e}"),
+        Resp::Integer(i) => format!("(integer) {i}"),
+        Resp::BulkString(bytes) => quote(bytes),
+        Resp::Null => String::from("(nil)"),
+        Resp::Array(items) | Resp::Push(items) if items.is_empty() => String::from("(empty array)"),
+        Resp::Map(pairs) if pairs.is_empty() => String::from("(empty hash)"),
+        Resp::Map(pairs) => {
+            let width = pairs.len().to_string().len();
+            pairs
+                .iter()
+                .enumerate()
+                .map(|(i, (key, value))| {
+                    let label = format!("{:>width$}# ", i + 1);
+                    let padding = if i == 0 {
+                        String::new()
+                    } else {
+                        " ".repeat(indent)
+                    };
+                    let key = format_reply(key, indent + label.len());
+                    let value = format_reply(value, indent + label.len() + key.len() + 4);
+                    format!("{padding}{label}{key} => {value}")
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Resp::Array(items) | Resp::Push(items) => {
+            let width = items.len().to_string().len();
+            items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let label = format!("{:>width$}) ", i + 1);
+                    let padding = if i == 0 {
+                        String::new()
+                    } else {
+                        " ".repeat(indent)
+                    };
+                    let item = format_reply(item, indent + label.len());
+                    format!("{padding}{label}{item}")
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+fn format_raw(reply: &Resp) -> Vec<u8> {
+    match reply {
+        Resp::SimpleString(s) | Resp::SimpleError(s) => s.clone().into_bytes(),
+        Resp::Integer(i) => i.to_string().into_bytes(),
+        Resp::BulkString(bytes) => bytes.clone(),
+        Resp::Null => Vec::new(),
+        Resp::Array(items) | Resp::Push(items) => items
+            .iter()
+            .map(format_raw)
+            .collect::<Vec<_>>()
+            .join(&b'\n'),
+        Resp::Map(pairs) => pairs
+            .iter()
+            .flat_map(|(key, value)| [format_raw(key), format_raw(value)])
+            .collect::<Vec<_>>()
+            .join(&b'\n'),
+    }
+}
+
+fn quote(bytes: &[u8]) -> String {
+    let mut quoted = String::from("\"");
+    for byte in bytes {
+        match byte {
+            b'\\' => quoted += "\\\\",
+            b'"' => quoted += "\\\"",
+            b'\n' => quoted += "\\n",
+            b'\r' => quoted += "\\r",
+            b'\t' => quoted += "\\t",
+            0x07 => quoted += "\\a",
+            0x08 => quoted += "\\b",
+            b if b.is_ascii_graphic() || *b == b' ' => quoted.push(*b as char),
+            b => quoted += &format!("\\x{b:02x}"),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatted_output() {
+        assert_eq!("OK", format_reply(&Resp::ok(), 0));
+        assert_eq!("(integer) 3", format_reply(&Resp::Integer(3), 0));
+        assert_eq!("(nil)", format_reply(&Resp::Null, 0));
+        assert_eq!(
+            "\"a\\\"b\\x00\"",
+            format_reply(&Resp::BulkString(b"a\"b\x00".to_vec()), 0)
+        );
+        let nested = Resp::Array(vec![
+            Resp::BulkString("a".into()),
+            Resp::Array(vec![Resp::Integer(1), Resp::Null]),
+            Resp::Array(vec![]),
+        ]);
+        assert_eq!(
+            "1) \"a\"\n2) 1) (integer) 1\n   2) (nil)\n3) (empty array)",
+            format_reply(&nested, 0)
+        );
+        let map = Resp::Map(vec![
+            (Resp::BulkString("proto".into()), Resp::Integer(3)),
+            (
+                Resp::BulkString("modules".into()),
+                Resp::Array(vec![Resp::Integer(1), Resp::Integer(2)]),
+            ),
+        ]);
+        assert_eq!(
+            "1# \"proto\" => (integer) 3\n2# \"modules\" => 1) (integer) 1\n                2) (integer) 2",
+            format_reply(&map, 0)
+        );
+    }
+
+    #[test]
+    fn raw_output() {
+        let array = Resp::Array(vec![
+            Resp::BulkString("a".into()),
+            Resp::Integer(2),
+            Resp::SimpleError(String::from("ERR")),
+        ]);
+        assert_eq!(b"a\n2\nERR".to_vec(), format_raw(&array));
+    }
+
+    #[test]
+    fn parse_options() {
+        let args = ["-p", "7000", "-n", "2", "--raw", "get", "-p"].map(String::from);
+        let options = Options::parse(args.into_iter()).unwrap();
+        assert_eq!("127.0.0.1:7000", options.address());
+        assert_eq!(2, options.db);
+        assert!(options.raw);
+        assert_eq!(vec!["get", "-p"], options.command);
+        assert!(Options::parse([String::from("-p")].into_iter()).is_err());
+    }
+}
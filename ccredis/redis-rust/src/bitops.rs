@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+use crate::resp::Resp;
+
+const MAX_BIT_OFFSET: u64 = 4 * 1024 * 1024 * 1024 - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum BitOp {
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl FromStr for BitOp {
+    type Err = Resp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "AND" => Ok(BitOp::And),
+            "OR" => Ok(BitOp::Or),
+            "XOR" => Ok(BitOp::Xor),
+            "NOT" => Ok(BitOp::Not),
+            _ => Err(Resp::syntax_error()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub enum BitUnit {
+    #[default]
+    Byte,
+    Bit,
+}
+
+impl FromStr for BitUnit {
+    type Err = Resp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "BYTE" => Ok(BitUnit::Byte),
+            "BIT" => Ok(BitUnit::Bit),
+            _ => Err(Resp::syntax_error()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct BitRange {
+    pub start: i64,
+    pub end: Option<i64>,
+    pub unit: BitUnit,
+}
+
+pub fn parse_offset(offset: &str) -> Result<u64, Resp> {
+    match offset.parse::<u64>() {
+        Ok(offset) if offset <= MAX_BIT_OFFSET => Ok(offset),
+        _ => Err(Resp::SimpleError(String::from(
+            "ERR bit offset is not an integer or out of range",
+        ))),
+    }
+}
+
+pub fn parse_bit(bit: &str) -> Result<bool, Resp> {
+    match bit {
+        "0" => Ok(false),
+        "1" => Ok(true),
+        _ => Err(Resp::SimpleError(String::from(
+            "ERR bit is not an integer or out of range",
+        ))),
+    }
+}
+
+pub fn get_bit(bytes: &[u8], offset: u64) -> bool {
+    let byte = (offset / 8) as usize;
+    let bit = 7 - (offset % 8) as u8;
+    bytes.get(byte).is_some_and(|byte| byte & (1 << bit) != 0)
+}
+
+pub fn set_bit(bytes: &mut Vec<u8>, offset: u64, value: bool) -> bool {
+    let byte = (offset / 8) as usize;
+    let bit = 7 - (offset % 8) as u8;
+    if bytes.len() <= byte {
+        bytes.resize(byte + 1, 0);
+    }
+    let old = bytes[byte] & (1 << bit) != 0;
+    if value {
+        bytes[byte] |= 1 << bit;
+    } else {
+        bytes[byte] &= !(1 << bit);
+    }
+    old
+}
+
+pub fn bit_count(bytes: &[u8], range: Option<BitRange>) -> u64 {
+    let Some(range) = range else {
+        return bytes.iter().map(|byte| byte.count_ones() as u64).sum();
+    };
+    match bit_bounds(bytes, range) {
+        Some((start, end)) => (start..=end).filter(|bit| get_bit(bytes, *bit)).count() as u64,
+        None => 0,
+    }
+}
+
+pub fn bit_pos(bytes: &[u8], bit: bool, range: Option<BitRange>) -> i64 {
+    let end_given = range.is_some_and(|range| range.end.is_some());
+    if bytes.is_empty() {
+        return if bit { -1 } else { 0 };
+    }
+    let range = range.unwrap_or(BitRange {
+        start: 0,
+        end: None,
+        unit: BitUnit::Byte,
+    });
+    let Some((start, end)) = bit_bounds(bytes, range) else {
+        return -1;
+    };
+    match (start..=end).find(|offset| get_bit(bytes, *offset) == bit) {
+        Some(offset) => offset as i64,
+        None if !bit && !end_given => (end + 1) as i64,
+        None => -1,
+    }
+}
+
+pub fn bit_op(op: BitOp, sources: &[&[u8]]) -> Vec<u8> {
+    let len = sources.iter().map(|source| source.len()).max().unwrap_or(0);
+    let byte = |source: &[u8], i: usize| source.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| {
+            let mut bytes = sources.iter().map(|source| byte(source, i));
+            let first = bytes.next().unwrap_or(0);
+            match op {
+                BitOp::And => bytes.fold(first, |acc, byte| acc & byte),
+                BitOp::Or => bytes.fold(first, |acc, byte| acc | byte),
+                BitOp::Xor => bytes.fold(first, |acc, byte| acc ^ byte),
+                BitOp::Not => !first,
+            }
+        })
+        .collect()
+}
+
+fn bit_bounds(bytes: &[u8], range: BitRange) -> Option<(u64, u64)> {
+    let len = match range.unit {
+        BitUnit::Byte => bytes.len() as i64,
+        BitUnit::Bit => bytes.len() as i64 * 8,
+    };
+    let normalize = |index: i64| {
+        if index < 0 {
+            (len + index).max(0)
+        } else {
+            index
+        }
+    };
+    let start = normalize(range.start);
+    let end = normalize(range.end.unwrap_or(-1)).min(len - 1);
+    if start > end || len == 0 {
+        return None;
+    }
+    match range.unit {
+        BitUnit::Byte => Some((start as u64 * 8, end as u64 * 8 + 7)),
+        BitUnit::Bit => Some((start as u64, end as u64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_bits() {
+        let mut bytes = Vec::new();
+        assert!(!set_bit(&mut bytes, 7, true));
+        assert!(set_bit(&mut bytes, 7, true));
+        assert_eq!(vec![0x01], bytes);
+        set_bit(&mut bytes, 8, true);
+        assert_eq!(vec![0x01, 0x80], bytes);
+        assert!(get_bit(&bytes, 8));
+        assert!(!get_bit(&bytes, 100));
+    }
+
+    #[test]
+    fn count_bits() {
+        let bytes = b"foobar";
+        assert_eq!(26, bit_count(bytes, None));
+        let range = |start, end, unit| {
+            Some(BitRange {
+                start,
+                end: Some(end),
+                unit,
+            })
+        };
+        assert_eq!(4, bit_count(bytes, range(0, 0, BitUnit::Byte)));
+        assert_eq!(6, bit_count(bytes, range(1, 1, BitUnit::Byte)));
+        assert_eq!(17, bit_count(bytes, range(5, 30, BitUnit::Bit)));
+        assert_eq!(0, bit_count(bytes, range(3, 1, BitUnit::Byte)));
+    }
+
+    #[test]
+    fn find_bits() {
+        let range = |start, end: Option<i64>, unit| Some(BitRange { start, end, unit });
+        assert_eq!(12, bit_pos(&[0xff, 0xf0, 0x00], false, None));
+        assert_eq!(8, bit_pos(&[0x00, 0xff, 0xf0], true, None));
+        assert_eq!(
+            16,
+            bit_pos(&[0x00, 0xff, 0xf0], true, range(2, None, BitUnit::Byte))
+        );
+        assert_eq!(
+            8,
+            bit_pos(&[0x00, 0xff, 0xf0], true, range(7, Some(15), BitUnit::Bit))
+        );
+        assert_eq!(24, bit_pos(&[0xff, 0xff, 0xff], false, None));
+        assert_eq!(
+            -1,
+            bit_pos(
+                &[0xff, 0xff, 0xff],
+                false,
+                range(0, Some(-1), BitUnit::Byte)
+            )
+        );
+        assert_eq!(-1, bit_pos(&[0x00], true, None));
+        assert_eq!(0, bit_pos(&[], false, None));
+    }
+
+    #[test]
+    fn bit_operations() {
+        assert_eq!(
+            vec![0x60, 0x00],
+            bit_op(BitOp::And, &[&[0x61, 0xff], &[0x62]])
+        );
+        assert_eq!(
+            vec![0x63, 0xff],
+            bit_op(BitOp::Or, &[&[0x61, 0xff], &[0x62]])
+        );
+        assert_eq!(
+            vec![0x03, 0xff],
+            bit_op(BitOp::Xor, &[&[0x61, 0xff], &[0x62]])
+        );
+        assert_eq!(vec![0x9e], bit_op(BitOp::Not, &[&[0x61]]));
+    }
+}
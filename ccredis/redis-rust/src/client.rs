@@ -0,0 +1,331 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::Display,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use crate::resp::Resp;
+
+#[derive(Debug)]
+pub enum ClientError {
+    Io(io::Error),
+    Server(String),
+    UnexpectedReply(Resp),
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Io(e) => write!(f, "{e}"),
+            ClientError::Server(message) => write!(f, "{message}"),
+            ClientError::UnexpectedReply(reply) => write!(f, "unexpected reply: {reply:?}"),
+        }
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClientError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ClientError {
+    fn from(value: io::Error) -> Self {
+        ClientError::Io(value)
+    }
+}
+
+pub trait FromReply: Sized {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError>;
+}
+
+impl FromReply for Resp {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        Ok(reply)
+    }
+}
+
+impl FromReply for () {
+    fn from_reply(_reply: Resp) -> Result<Self, ClientError> {
+        Ok(())
+    }
+}
+
+impl FromReply for Vec<u8> {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        match reply {
+            Resp::BulkString(bytes) => Ok(bytes),
+            Resp::SimpleString(s) => Ok(s.into_bytes()),
+            Resp::Integer(i) => Ok(i.to_string().into_bytes()),
+            reply => Err(ClientError::UnexpectedReply(reply)),
+        }
+    }
+}
+
+impl FromReply for String {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        match reply {
+            Resp::BulkString(bytes) => String::from_utf8(bytes)
+                .map_err(|e| ClientError::UnexpectedReply(Resp::BulkString(e.into_bytes()))),
+            Resp::SimpleString(s) => Ok(s),
+            Resp::Integer(i) => Ok(i.to_string()),
+            reply => Err(ClientError::UnexpectedReply(reply)),
+        }
+    }
+}
+
+impl FromReply for i64 {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        match reply {
+            Resp::Integer(i) => Ok(i),
+            Resp::BulkString(bytes) => match std::str::from_utf8(&bytes).map(str::parse) {
+                Ok(Ok(i)) => Ok(i),
+                _ => Err(ClientError::UnexpectedReply(Resp::BulkString(bytes))),
+            },
+            reply => Err(ClientError::UnexpectedReply(reply)),
+        }
+    }
+}
+
+impl FromReply for bool {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        i64::from_reply(reply).map(|i| i != 0)
+    }
+}
+
+impl<T: FromReply> FromReply for Option<T> {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        match reply {
+            Resp::Null => Ok(None),
+            reply => T::from_reply(reply).map(Some),
+        }
+    }
+}
+
+impl<T: FromReply> FromReply for Vec<T> {
+    fn from_reply(reply: Resp) -> Result<Self, ClientError> {
+        match reply {
+            Resp::Array(items) | Resp::Push(items) => {
+                items.into_iter().map(T::from_reply).collect()
+            }
+            Resp::Null => Ok(Vec::new()),
+            reply => Err(ClientError::UnexpectedReply(reply)),
+        }
+    }
+}
+
+pub struct Client {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    pushes: VecDeque<Resp>,
+}
+
+impl Client {
+    pub fn connect(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            pushes: VecDeque::new(),
+        })
+    }
+
+    pub fn connect_timeout(address: &SocketAddr, timeout: Duration) -> io::Result<Self> {
+        let stream = TcpStream::connect_timeout(address, timeout)?;
+        stream.set_nodelay(true)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        Ok(Self {
+            stream,
+            buffer: Vec::new(),
+            pushes: VecDeque::new(),
+        })
+    }
+
+    pub fn send<A: AsRef<[u8]>>(&mut self, args: &[A]) -> io::Result<()> {
+        self.stream.write_all(&encode_command(args))
+    }
+
+    pub fn read_reply(&mut self) -> io::Result<Resp> {
+        loop {
+            match self.read_frame()? {
+                Resp::Push(push) => self.pushes.push_back(Resp::Push(push)),
+                reply => return Ok(reply),
+            }
+        }
+    }
+
+    pub fn read_push(&mut self) -> io::Result<Resp> {
+        if let Some(push) = self.pushes.pop_front() {
+            return Ok(push);
+        }
+        match self.read_frame()? {
+            Resp::Push(push) => Ok(Resp::Push(push)),
+            reply => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("expected a push, got {reply:?}"),
+            )),
+        }
+    }
+
+    pub fn take_pushes(&mut self) -> Vec<Resp> {
+        self.pushes.drain(..).collect()
+    }
+
+    fn read_frame(&mut self) -> io::Result<Resp> {
+        let mut chunk = [0; 4096];
+        loop {
+            match Resp::parse_one(&self.buffer) {
+                Ok(Some((reply, remaining))) => {
+                    let consumed = self.buffer.len() - remaining.len();
+                    self.buffer.drain(..consumed);
+                    return Ok(reply);
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+                }
+            }
+            let read = self.stream.read(&mut chunk)?;
+            if read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "connection closed by server",
+                ));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+
+    pub fn request<A: AsRef<[u8]>>(&mut self, args: &[A]) -> io::Result<Resp> {
+        self.send(args)?;
+        self.read_reply()
+    }
+
+    pub fn query<T: FromReply, A: AsRef<[u8]>>(&mut self, args: &[A]) -> Result<T, ClientError> {
+        match self.request(args)? {
+            Resp::SimpleError(message) => Err(ClientError::Server(message)),
+            reply => T::from_reply(reply),
+        }
+    }
+
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            client: self,
+            commands: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn ping(&mut self) -> Result<String, ClientError> {
+        self.query(&["PING"])
+    }
+
+    pub fn echo(&mut self, message: &[u8]) -> Result<Vec<u8>, ClientError> {
+        self.query(&[b"ECHO".as_slice(), message])
+    }
+
+    pub fn select(&mut self, db: usize) -> Result<(), ClientError> {
+        self.query(&["SELECT", &db.to_string()])
+    }
+
+    pub fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>, ClientError> {
+        self.query(&["GET", key])
+    }
+
+    pub fn set(&mut self, key: &str, value: &[u8]) -> Result<(), ClientError> {
+        self.query(&[b"SET".as_slice(), key.as_bytes(), value])
+    }
+
+    pub fn del(&mut self, keys: &[&str]) -> Result<i64, ClientError> {
+        let mut args = vec!["DEL"];
+        args.extend_from_slice(keys);
+        self.query(&args)
+    }
+
+    pub fn expire(&mut self, key: &str, seconds: i64) -> Result<bool, ClientError> {
+        self.query(&["EXPIRE", key, &seconds.to_string()])
+    }
+
+    pub fn ttl(&mut self, key: &str) -> Result<i64, ClientError> {
+        self.query(&["TTL", key])
+    }
+
+    pub fn dbsize(&mut self) -> Result<i64, ClientError> {
+        self.query(&["DBSIZE"])
+    }
+
+    pub fn publish(&mut self, channel: &str, message: &[u8]) -> Result<i64, ClientError> {
+        self.query(&[b"PUBLISH".as_slice(), channel.as_bytes(), message])
+    }
+}
+
+pub struct Pipeline<'a> {
+    client: &'a mut Client,
+    commands: Vec<u8>,
+    len: usize,
+}
+
+impl Pipeline<'_> {
+    pub fn command<A: AsRef<[u8]>>(&mut self, args: &[A]) -> &mut Self {
+        self.commands.extend(encode_command(args));
+        self.len += 1;
+        self
+    }
+
+    pub fn execute(&mut self) -> io::Result<Vec<Resp>> {
+        self.client.stream.write_all(&self.commands)?;
+        self.commands.clear();
+        let replies = (0..self.len).map(|_| self.client.read_reply()).collect();
+        self.len = 0;
+        replies
+    }
+}
+
+pub fn encode_command<A: AsRef<[u8]>>(args: &[A]) -> Vec<u8> {
+    let command = args
+        .iter()
+        .map(|arg| Resp::BulkString(arg.as_ref().to_vec()))
+        .collect();
+    Resp::Array(command).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_replies() {
+        assert_eq!(
+            Some(b"value".to_vec()),
+            Option::<Vec<u8>>::from_reply(Resp::BulkString("value".into())).unwrap()
+        );
+        assert_eq!(None, Option::<String>::from_reply(Resp::Null).unwrap());
+        assert_eq!(42, i64::from_reply(Resp::BulkString("42".into())).unwrap());
+        assert!(bool::from_reply(Resp::Integer(1)).unwrap());
+        let array = Resp::Array(vec![
+            Resp::BulkString("a".into()),
+            Resp::SimpleString(String::from("b")),
+        ]);
+        assert_eq!(
+            vec![String::from("a"), String::from("b")],
+            Vec::<String>::from_reply(array).unwrap()
+        );
+        assert!(i64::from_reply(Resp::SimpleString(String::from("OK"))).is_err());
+    }
+
+    #[test]
+    fn encode_commands() {
+        assert_eq!(
+            b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n".to_vec(),
+            encode_command(&["GET", "key"])
+        );
+    }
+}
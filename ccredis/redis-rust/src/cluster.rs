@@ -0,0 +1,486 @@
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, HashMap, HashSet},
+    hash::{BuildHasher, Hasher},
+    net::SocketAddr,
+    time::SystemTime,
+};
+
+use crate::resp::Resp;
+
+pub const SLOTS: u16 = 16384;
+pub const BUS_PORT_OFFSET: u16 = 10000;
+
+pub fn bus_address(address: SocketAddr) -> Option<SocketAddr> {
+    let port = address.port().checked_add(BUS_PORT_OFFSET)?;
+    Some(SocketAddr::new(address.ip(), port))
+}
+
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+pub fn key_hash_slot(key: &str) -> u16 {
+    let bytes = key.as_bytes();
+    let tag = bytes.iter().position(|b| *b == b'{').and_then(|start| {
+        bytes[start + 1..]
+            .iter()
+            .position(|b| *b == b'}')
+            .filter(|len| *len > 0)
+            .map(|len| &bytes[start + 1..start + 1 + len])
+    });
+    crc16(tag.unwrap_or(bytes)) & (SLOTS - 1)
+}
+
+pub fn parse_slot(slot: &str) -> Result<u16, Resp> {
+    match slot.parse::<u16>() {
+        Ok(slot) if slot < SLOTS => Ok(slot),
+        _ => Err(Resp::SimpleError(String::from(
+            "ERR Invalid or out of range slot",
+        ))),
+    }
+}
+
+pub fn format_slot_ranges(slots: &[u16]) -> String {
+    if slots.is_empty() {
+        return String::from("-");
+    }
+    slot_ranges(slots)
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn parse_slot_ranges(ranges: &str) -> Result<Vec<u16>, Resp> {
+    if ranges == "-" {
+        return Ok(Vec::new());
+    }
+    let mut slots = Vec::new();
+    for range in ranges.split(',') {
+        match range.split_once('-') {
+            Some((start, end)) => slots.extend(parse_slot(start)?..=parse_slot(end)?),
+            None => slots.push(parse_slot(range)?),
+        }
+    }
+    Ok(slots)
+}
+
+fn slot_ranges(slots: &[u16]) -> Vec<(u16, u16)> {
+    let mut ranges: Vec<(u16, u16)> = Vec::new();
+    for slot in slots {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == *slot => *end = *slot,
+            _ => ranges.push((*slot, *slot)),
+        }
+    }
+    ranges
+}
+
+pub enum Redirect {
+    Moved(u16, SocketAddr),
+    Ask(u16, SocketAddr),
+    CrossSlot,
+    Down,
+}
+
+impl From<Redirect> for Resp {
+    fn from(value: Redirect) -> Self {
+        let message = match value {
+            Redirect::Moved(slot, address) => format!("MOVED {slot} {address}"),
+            Redirect::Ask(slot, address) => format!("ASK {slot} {address}"),
+            Redirect::CrossSlot => {
+                String::from("CROSSSLOT Keys in request don't hash to the same slot")
+            }
+            Redirect::Down => String::from("CLUSTERDOWN Hash slot not served"),
+        };
+        Resp::SimpleError(message)
+    }
+}
+
+pub struct Node {
+    pub id: String,
+    pub address: SocketAddr,
+}
+
+pub struct Cluster {
+    myself: String,
+    nodes: BTreeMap<String, Node>,
+    slots: Vec<Option<String>>,
+    migrating: HashMap<u16, String>,
+    importing: HashMap<u16, String>,
+    handshakes: HashSet<SocketAddr>,
+}
+
+impl Cluster {
+    pub fn new(address: SocketAddr) -> Self {
+        let myself = node_id(address);
+        let node = Node {
+            id: myself.clone(),
+            address,
+        };
+        Self {
+            nodes: BTreeMap::from([(myself.clone(), node)]),
+            myself,
+            slots: vec![None; SLOTS as usize],
+            migrating: HashMap::new(),
+            importing: HashMap::new(),
+            handshakes: HashSet::new(),
+        }
+    }
+
+    pub fn myself(&self) -> &str {
+        &self.myself
+    }
+
+    pub fn owner(&self, slot: u16) -> Option<&Node> {
+        self.slots[slot as usize]
+            .as_ref()
+            .and_then(|id| self.nodes.get(id))
+    }
+
+    pub fn add_slots(&mut self, slots: &[u16]) -> Result<(), Resp> {
+        if let Some(slot) = slots
+            .iter()
+            .find(|slot| self.slots[**slot as usize].is_some())
+        {
+            return Err(Resp::SimpleError(format!(
+                "ERR Slot {slot} is already busy"
+            )));
+        }
+        for slot in slots {
+            self.slots[*slot as usize] = Some(self.myself.clone());
+        }
+        Ok(())
+    }
+
+    pub fn meet(&mut self, address: SocketAddr) {
+        if !self.nodes.values().any(|node| node.address == address) {
+            self.handshakes.insert(address);
+        }
+    }
+
+    pub fn set_migrating(&mut self, slot: u16, node: String) -> Result<(), Resp> {
+        if self.slots[slot as usize].as_deref() != Some(self.myself.as_str()) {
+            return Err(Resp::SimpleError(format!(
+                "ERR I'm not the owner of hash slot {slot}"
+            )));
+        }
+        self.known_node(&node)?;
+        self.migrating.insert(slot, node);
+        Ok(())
+    }
+
+    pub fn set_importing(&mut self, slot: u16, node: String) -> Result<(), Resp> {
+        if self.slots[slot as usize].as_deref() == Some(self.myself.as_str()) {
+            return Err(Resp::SimpleError(format!(
+                "ERR I'm already the owner of hash slot {slot}"
+            )));
+        }
+        self.known_node(&node)?;
+        self.importing.insert(slot, node);
+        Ok(())
+    }
+
+    pub fn set_stable(&mut self, slot: u16) {
+        self.migrating.remove(&slot);
+        self.importing.remove(&slot);
+    }
+
+    pub fn set_node(&mut self, slot: u16, node: String) -> Result<(), Resp> {
+        self.known_node(&node)?;
+        self.set_stable(slot);
+        self.slots[slot as usize] = Some(node);
+        Ok(())
+    }
+
+    pub fn route(&self, slot: u16, exists: bool, asking: bool) -> Result<(), Redirect> {
+        let importing = asking && self.importing.contains_key(&slot);
+        let Some(owner) = self.owner(slot) else {
+            if importing {
+                return Ok(());
+            }
+            return Err(Redirect::Down);
+        };
+        if owner.id == self.myself {
+            return match self.migrating.get(&slot).and_then(|id| self.nodes.get(id)) {
+                Some(target) if !exists => Err(Redirect::Ask(slot, target.address)),
+                _ => Ok(()),
+            };
+        }
+        if importing {
+            return Ok(());
+        }
+        Err(Redirect::Moved(slot, owner.address))
+    }
+
+    pub fn gossip(&self) -> Vec<(SocketAddr, Resp)> {
+        let myself = &self.nodes[&self.myself];
+        let mut message = vec![
+            String::from("CLUSTER"),
+            String::from("GOSSIP"),
+            myself.id.clone(),
+            myself.address.to_string(),
+            format_slot_ranges(&self.node_slots(&self.myself)),
+        ];
+        for node in self.nodes.values().filter(|node| node.id != self.myself) {
+            message.push(node.id.clone());
+            message.push(node.address.to_string());
+        }
+        let message = Resp::Array(
+            message
+                .into_iter()
+                .map(|s| Resp::BulkString(s.into()))
+                .collect(),
+        );
+        self.nodes
+            .values()
+            .filter(|node| node.id != self.myself)
+            .map(|node| node.address)
+            .chain(self.handshakes.iter().copied())
+            .filter_map(bus_address)
+            .map(|address| (address, message.clone()))
+            .collect()
+    }
+
+    pub fn receive_gossip(
+        &mut self,
+        sender: Node,
+        slots: Vec<u16>,
+        nodes: Vec<(String, SocketAddr)>,
+    ) {
+        if sender.id == self.myself {
+            return;
+        }
+        self.handshakes.remove(&sender.address);
+        self.nodes
+            .retain(|id, node| *id == sender.id || node.address != sender.address);
+        for owner in self.slots.iter_mut() {
+            if owner.as_deref() == Some(sender.id.as_str()) {
+                *owner = None;
+            }
+        }
+        for slot in slots {
+            let owner = &mut self.slots[slot as usize];
+            if owner.as_deref() != Some(self.myself.as_str()) {
+                *owner = Some(sender.id.clone());
+            }
+        }
+        for (id, address) in nodes {
+            if id != self.myself && !self.nodes.contains_key(&id) {
+                self.meet(address);
+            }
+        }
+        self.nodes.insert(sender.id.clone(), sender);
+    }
+
+    pub fn slots(&self) -> Resp {
+        let mut ranges = Vec::new();
+        for node in self.nodes.values() {
+            for (start, end) in slot_ranges(&self.node_slots(&node.id)) {
+                ranges.push((start, end, node));
+            }
+        }
+        ranges.sort_by_key(|(start, _, _)| *start);
+        Resp::Array(
+            ranges
+                .into_iter()
+                .map(|(start, end, node)| {
+                    Resp::Array(vec![
+                        Resp::Integer(start as i64),
+                        Resp::Integer(end as i64),
+                        Resp::Array(vec![
+                            Resp::BulkString(node.address.ip().to_string().into()),
+                            Resp::Integer(node.address.port() as i64),
+                            Resp::BulkString(node.id.clone().into()),
+                        ]),
+                    ])
+                })
+                .collect(),
+        )
+    }
+
+    pub fn shards(&self) -> Resp {
+        let shards = self.nodes.values().map(|node| {
+            let slots = slot_ranges(&self.node_slots(&node.id))
+                .into_iter()
+                .flat_map(|(start, end)| [Resp::Integer(start as i64), Resp::Integer(end as i64)])
+                .collect();
+            let description = vec![
+                ("id", Resp::BulkString(node.id.clone().into())),
+                ("port", Resp::Integer(node.address.port() as i64)),
+                ("ip", Resp::BulkString(node.address.ip().to_string().into())),
+                (
+                    "endpoint",
+                    Resp::BulkString(node.address.ip().to_string().into()),
+                ),
+                ("role", Resp::BulkString("master".into())),
+                ("replication-offset", Resp::Integer(0)),
+                ("health", Resp::BulkString("online".into())),
+            ];
+            Resp::Array(vec![
+                Resp::BulkString("slots".into()),
+                Resp::Array(slots),
+                Resp::BulkString("nodes".into()),
+                Resp::Array(vec![Resp::Array(
+                    description
+                        .into_iter()
+                        .flat_map(|(name, value)| [Resp::BulkString(name.into()), value])
+                        .collect(),
+                )]),
+            ])
+        });
+        Resp::Array(shards.collect())
+    }
+
+    pub fn nodes(&self) -> String {
+        let mut lines = String::new();
+        for node in self.nodes.values() {
+            let flags = if node.id == self.myself {
+                "myself,master"
+            } else {
+                "master"
+            };
+            lines.push_str(&format!(
+                "{} {}@{} {flags} - 0 0 0 connected",
+                node.id,
+                node.address,
+                bus_address(node.address).map_or(0, |bus| bus.port())
+            ));
+            let slots = format_slot_ranges(&self.node_slots(&node.id));
+            if slots != "-" {
+                lines.push(' ');
+                lines.push_str(&slots.replace(',', " "));
+            }
+            if node.id == self.myself {
+                for (slot, target) in self.migrating.iter() {
+                    lines.push_str(&format!(" [{slot}->-{target}]"));
+                }
+                for (slot, source) in self.importing.iter() {
+                    lines.push_str(&format!(" [{slot}-<-{source}]"));
+                }
+            }
+            lines.push('\n');
+        }
+        lines
+    }
+
+    fn node_slots(&self, id: &str) -> Vec<u16> {
+        (0..SLOTS)
+            .filter(|slot| self.slots[*slot as usize].as_deref() == Some(id))
+            .collect()
+    }
+
+    fn known_node(&self, id: &str) -> Result<(), Resp> {
+        if !self.nodes.contains_key(id) {
+            return Err(Resp::SimpleError(format!(
+                "ERR I don't know about node {id}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn node_id(address: SocketAddr) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let state = RandomState::new();
+    let mut id = String::new();
+    for part in 0..3u8 {
+        let mut hasher = state.build_hasher();
+        hasher.write(address.to_string().as_bytes());
+        hasher.write_u128(nanos);
+        hasher.write_u8(part);
+        id.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    id.truncate(40);
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_slots() {
+        assert_eq!(0x31c3, crc16(b"123456789"));
+        assert_eq!(12182, key_hash_slot("foo"));
+        assert_eq!(
+            key_hash_slot("{user1000}.following"),
+            key_hash_slot("{user1000}.followers")
+        );
+        assert_eq!(key_hash_slot("user1000"), key_hash_slot("{user1000}.x"));
+        assert_eq!(crc16(b"foo{}{bar}") & 16383, key_hash_slot("foo{}{bar}"));
+        assert_eq!(key_hash_slot("{bar"), key_hash_slot("foo{{bar}}zap"));
+    }
+
+    #[test]
+    fn slot_range_format() -> Result<(), Resp> {
+        let slots = parse_slot_ranges("0-3,5,7-8")?;
+        assert_eq!(vec![0, 1, 2, 3, 5, 7, 8], slots);
+        assert_eq!("0-3,5,7-8", format_slot_ranges(&slots));
+        assert_eq!("-", format_slot_ranges(&[]));
+        assert!(parse_slot_ranges("16384").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn routing() -> Result<(), Resp> {
+        let first = SocketAddr::from(([127, 0, 0, 1], 1));
+        let second = SocketAddr::from(([127, 0, 0, 1], 2));
+        let mut cluster = Cluster::new(first);
+        cluster.add_slots(&[0, 1])?;
+        assert!(cluster.add_slots(&[1]).is_err());
+        let other = String::from("other");
+        cluster.receive_gossip(
+            Node {
+                id: other.clone(),
+                address: second,
+            },
+            vec![2],
+            Vec::new(),
+        );
+        assert!(cluster.route(0, false, false).is_ok());
+        assert!(matches!(
+            cluster.route(2, false, false),
+            Err(Redirect::Moved(2, address)) if address == second
+        ));
+        assert!(matches!(
+            cluster.route(3, false, false),
+            Err(Redirect::Down)
+        ));
+
+        cluster.set_migrating(1, other.clone())?;
+        assert!(cluster.route(1, true, false).is_ok());
+        assert!(matches!(
+            cluster.route(1, false, false),
+            Err(Redirect::Ask(1, address)) if address == second
+        ));
+        cluster.set_importing(2, other.clone())?;
+        assert!(cluster.route(2, false, true).is_ok());
+        cluster.set_node(1, other)?;
+        assert!(matches!(
+            cluster.route(1, true, false),
+            Err(Redirect::Moved(1, _))
+        ));
+        Ok(())
+    }
+}
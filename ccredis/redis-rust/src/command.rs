@@ -1,13 +1,274 @@
-use crate::resp::Resp;
+use std::net::{IpAddr, SocketAddr};
 
-#[derive(Debug, PartialEq, PartialOrd)]
+use crate::{
+    bitops::{self, BitOp, BitRange},
+    cluster::{self, parse_slot},
+    geo::{self, Shape, Unit},
+    resp::Resp,
+    stream::{Fields, RangeBound, ReadId, StreamId, XAddId},
+};
+
+#[derive(Debug, PartialEq)]
 pub enum Command {
     Ping,
-    Echo(String),
+    Echo(Vec<u8>),
     Get(String),
-    Set { key: String, value: String },
-    ConfigGet,
+    Set {
+        key: String,
+        value: Vec<u8>,
+    },
+    Incr(String),
+    LPush {
+        key: String,
+        elements: Vec<Vec<u8>>,
+    },
+    ConfigGet(String),
+    ConfigSet {
+        parameter: String,
+        value: String,
+    },
     Client,
+    ClientId,
+    ClientTracking(Option<TrackingOptions>),
+    ClientCaching(bool),
+    ClientGetRedir,
+    ClientTrackingInfo,
+    Hello(Option<i64>),
+    SlowLogGet(Option<i64>),
+    SlowLogLen,
+    SlowLogReset,
+    Monitor,
+    Select(i64),
+    Move {
+        key: String,
+        db: i64,
+    },
+    SwapDb(i64, i64),
+    FlushAll,
+    FlushDb,
+    DbSize,
+    Info(Option<String>),
+    Shutdown(ShutdownMode),
+    Del(Vec<String>),
+    Expire {
+        key: String,
+        seconds: i64,
+    },
+    Ttl(String),
+    Publish {
+        channel: String,
+        message: String,
+    },
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+    PSubscribe(Vec<String>),
+    PUnsubscribe(Vec<String>),
+    XAdd {
+        key: String,
+        nomkstream: bool,
+        maxlen: Option<usize>,
+        id: XAddId,
+        fields: Fields,
+    },
+    XLen(String),
+    XRange {
+        key: String,
+        start: RangeBound,
+        end: RangeBound,
+        count: Option<usize>,
+        rev: bool,
+    },
+    XRead {
+        count: Option<usize>,
+        block: Option<u64>,
+        streams: Vec<(String, ReadId)>,
+    },
+    XGroupCreate {
+        key: String,
+        group: String,
+        id: ReadId,
+        mkstream: bool,
+    },
+    XGroupDestroy {
+        key: String,
+        group: String,
+    },
+    XReadGroup {
+        group: String,
+        consumer: String,
+        count: Option<usize>,
+        block: Option<u64>,
+        noack: bool,
+        streams: Vec<(String, ReadId)>,
+    },
+    XAck {
+        key: String,
+        group: String,
+        ids: Vec<StreamId>,
+    },
+    XPending {
+        key: String,
+        group: String,
+        details: Option<PendingRange>,
+    },
+    CommandCount,
+    CommandInfo(Vec<String>),
+    CommandDocs(Vec<String>),
+    CommandGetKeys(Vec<String>),
+    ClusterSlots,
+    ClusterShards,
+    ClusterNodes,
+    ClusterMyId,
+    ClusterKeySlot(String),
+    ClusterAddSlots(Vec<u16>),
+    ClusterMeet(SocketAddr),
+    ClusterSetSlot {
+        slot: u16,
+        state: SlotState,
+    },
+    ClusterGossip {
+        id: String,
+        address: SocketAddr,
+        slots: Vec<u16>,
+        nodes: Vec<(String, SocketAddr)>,
+    },
+    Asking,
+    Dump(String),
+    Restore {
+        key: String,
+        ttl: i64,
+        payload: Vec<u8>,
+        replace: bool,
+        absttl: bool,
+        idletime: Option<u64>,
+        freq: Option<u8>,
+    },
+    Migrate {
+        address: String,
+        keys: Vec<String>,
+        db: i64,
+        timeout: u64,
+        copy: bool,
+        replace: bool,
+    },
+    ObjectEncoding(String),
+    ObjectIdleTime(String),
+    ObjectFreq(String),
+    PfAdd {
+        key: String,
+        elements: Vec<Vec<u8>>,
+    },
+    PfCount(Vec<String>),
+    PfMerge {
+        destination: String,
+        sources: Vec<String>,
+    },
+    SetBit {
+        key: String,
+        offset: u64,
+        value: bool,
+    },
+    GetBit {
+        key: String,
+        offset: u64,
+    },
+    BitCount {
+        key: String,
+        range: Option<BitRange>,
+    },
+    BitOp {
+        op: BitOp,
+        destination: String,
+        keys: Vec<String>,
+    },
+    BitPos {
+        key: String,
+        bit: bool,
+        range: Option<BitRange>,
+    },
+    GeoAdd {
+        key: String,
+        nx: bool,
+        xx: bool,
+        ch: bool,
+        items: Vec<(f64, f64, String)>,
+    },
+    GeoDist {
+        key: String,
+        first: String,
+        second: String,
+        unit: Unit,
+    },
+    GeoPos {
+        key: String,
+        members: Vec<String>,
+    },
+    GeoHash {
+        key: String,
+        members: Vec<String>,
+    },
+    GeoSearch {
+        key: String,
+        search: GeoSearch,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShutdownMode {
+    Default,
+    NoSave,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum SlotState {
+    Importing(String),
+    Migrating(String),
+    Stable,
+    Node(String),
+}
+
+#[derive(Debug, Default, PartialEq, PartialOrd)]
+pub struct TrackingOptions {
+    pub redirect: Option<u64>,
+    pub prefixes: Vec<String>,
+    pub bcast: bool,
+    pub optin: bool,
+    pub optout: bool,
+    pub noloop: bool,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct PendingRange {
+    pub idle: Option<u128>,
+    pub start: RangeBound,
+    pub end: RangeBound,
+    pub count: usize,
+    pub consumer: Option<String>,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum GeoOrigin {
+    Member(String),
+    LonLat(f64, f64),
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub struct GeoSearch {
+    pub origin: GeoOrigin,
+    pub shape: Shape,
+    pub unit: Unit,
+    pub order: Option<Order>,
+    pub count: Option<usize>,
+    pub any: bool,
+    pub withcoord: bool,
+    pub withdist: bool,
+    pub withhash: bool,
 }
 
 impl TryFrom<Resp> for Command {
@@ -29,66 +290,1627 @@ impl TryFrom<Vec<Resp>> for Command {
     }
 }
 
+pub enum KeySpec {
+    None,
+    Range { first: i64, last: i64, step: i64 },
+    Movable(fn(&[String]) -> Vec<usize>),
+}
+
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    pub keys: KeySpec,
+    pub categories: &'static [&'static str],
+    pub group: &'static str,
+    pub summary: &'static str,
+    parse: fn(Vec<Resp>) -> Result<Command, Resp>,
+}
+
+const NO_KEYS: KeySpec = KeySpec::None;
+const FIRST_KEY: KeySpec = KeySpec::Range {
+    first: 1,
+    last: 1,
+    step: 1,
+};
+
+const ALL_KEYS: KeySpec = KeySpec::Range {
+    first: 1,
+    last: -1,
+    step: 1,
+};
+
+const COMMAND_TABLE: &[CommandSpec] = &[
+    CommandSpec {
+        name: "ping",
+        arity: -1,
+        flags: &["fast"],
+        keys: NO_KEYS,
+        categories: &["@fast", "@connection"],
+        group: "connection",
+        summary: "Returns the server's liveliness response.",
+        parse: |_| Ok(Command::Ping),
+    },
+    CommandSpec {
+        name: "echo",
+        arity: 2,
+        flags: &["fast"],
+        keys: NO_KEYS,
+        categories: &["@fast", "@connection"],
+        group: "connection",
+        summary: "Returns the given string.",
+        parse: create_echo,
+    },
+    CommandSpec {
+        name: "get",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@string", "@fast"],
+        group: "string",
+        summary: "Returns the string value of a key.",
+        parse: create_get,
+    },
+    CommandSpec {
+        name: "set",
+        arity: 3,
+        flags: &["write", "denyoom"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@string", "@slow"],
+        group: "string",
+        summary: "Sets the string value of a key.",
+        parse: create_set,
+    },
+    CommandSpec {
+        name: "incr",
+        arity: 2,
+        flags: &["write", "denyoom", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@string", "@fast"],
+        group: "string",
+        summary: "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist.",
+        parse: create_incr,
+    },
+    CommandSpec {
+        name: "lpush",
+        arity: -3,
+        flags: &["write", "denyoom", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@list", "@fast"],
+        group: "list",
+        summary: "Prepends one or more elements to a list. Creates the key if it doesn't exist.",
+        parse: create_lpush,
+    },
+    CommandSpec {
+        name: "config",
+        arity: -2,
+        flags: &["admin", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@admin", "@slow", "@dangerous"],
+        group: "server",
+        summary: "A container for server configuration commands.",
+        parse: create_config,
+    },
+    CommandSpec {
+        name: "client",
+        arity: -2,
+        flags: &["noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@slow", "@connection"],
+        group: "connection",
+        summary: "A container for client connection commands.",
+        parse: create_client,
+    },
+    CommandSpec {
+        name: "hello",
+        arity: -1,
+        flags: &["noscript", "loading", "stale", "fast", "no-auth"],
+        keys: NO_KEYS,
+        categories: &["@fast", "@connection"],
+        group: "connection",
+        summary: "Handshakes with the Redis server.",
+        parse: create_hello,
+    },
+    CommandSpec {
+        name: "slowlog",
+        arity: -2,
+        flags: &["admin", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@admin", "@slow", "@dangerous"],
+        group: "server",
+        summary: "A container for slow log commands.",
+        parse: create_slowlog,
+    },
+    CommandSpec {
+        name: "monitor",
+        arity: 1,
+        flags: &["admin", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@admin", "@slow", "@dangerous"],
+        group: "server",
+        summary: "Listens for all requests received by the server in real-time.",
+        parse: |_| Ok(Command::Monitor),
+    },
+    CommandSpec {
+        name: "select",
+        arity: 2,
+        flags: &["loading", "stale", "fast"],
+        keys: NO_KEYS,
+        categories: &["@fast", "@connection"],
+        group: "connection",
+        summary: "Changes the selected database.",
+        parse: create_select,
+    },
+    CommandSpec {
+        name: "move",
+        arity: 3,
+        flags: &["write", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@keyspace", "@write", "@fast"],
+        group: "generic",
+        summary: "Moves a key to another database.",
+        parse: create_move,
+    },
+    CommandSpec {
+        name: "swapdb",
+        arity: 3,
+        flags: &["write", "fast"],
+        keys: NO_KEYS,
+        categories: &["@keyspace", "@write", "@fast", "@dangerous"],
+        group: "server",
+        summary: "Swaps two Redis databases.",
+        parse: create_swapdb,
+    },
+    CommandSpec {
+        name: "flushall",
+        arity: -1,
+        flags: &["write"],
+        keys: NO_KEYS,
+        categories: &["@keyspace", "@write", "@slow", "@dangerous"],
+        group: "server",
+        summary: "Removes all keys from all databases.",
+        parse: |arr| create_flush(arr, Command::FlushAll),
+    },
+    CommandSpec {
+        name: "flushdb",
+        arity: -1,
+        flags: &["write"],
+        keys: NO_KEYS,
+        categories: &["@keyspace", "@write", "@slow", "@dangerous"],
+        group: "server",
+        summary: "Remove all keys from the current database.",
+        parse: |arr| create_flush(arr, Command::FlushDb),
+    },
+    CommandSpec {
+        name: "dbsize",
+        arity: 1,
+        flags: &["readonly", "fast"],
+        keys: NO_KEYS,
+        categories: &["@keyspace", "@read", "@fast"],
+        group: "server",
+        summary: "Returns the number of keys in the database.",
+        parse: |_| Ok(Command::DbSize),
+    },
+    CommandSpec {
+        name: "info",
+        arity: -1,
+        flags: &["loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@slow", "@dangerous"],
+        group: "server",
+        summary: "Returns information and statistics about the server.",
+        parse: create_info,
+    },
+    CommandSpec {
+        name: "shutdown",
+        arity: -1,
+        flags: &["admin", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@admin", "@slow", "@dangerous"],
+        group: "server",
+        summary: "Synchronously saves the database(s) to disk and shuts down the Redis server.",
+        parse: create_shutdown,
+    },
+    CommandSpec {
+        name: "del",
+        arity: -2,
+        flags: &["write"],
+        keys: ALL_KEYS,
+        categories: &["@keyspace", "@write", "@slow"],
+        group: "generic",
+        summary: "Deletes one or more keys.",
+        parse: |arr| Ok(Command::Del(bulk_strings(arr)?)),
+    },
+    CommandSpec {
+        name: "expire",
+        arity: 3,
+        flags: &["write", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@keyspace", "@write", "@fast"],
+        group: "generic",
+        summary: "Sets the expiration time of a key in seconds.",
+        parse: create_expire,
+    },
+    CommandSpec {
+        name: "ttl",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@keyspace", "@read", "@fast"],
+        group: "generic",
+        summary: "Returns the expiration time in seconds of a key.",
+        parse: create_ttl,
+    },
+    CommandSpec {
+        name: "dump",
+        arity: 2,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@keyspace", "@read", "@slow"],
+        group: "generic",
+        summary: "Returns a serialized representation of the value stored at a key.",
+        parse: |mut arr| Ok(Command::Dump(bulk_string(arr.remove(0))?)),
+    },
+    CommandSpec {
+        name: "restore",
+        arity: -4,
+        flags: &["write", "denyoom"],
+        keys: FIRST_KEY,
+        categories: &["@keyspace", "@write", "@slow", "@dangerous"],
+        group: "generic",
+        summary: "Creates a key from the serialized representation of a value.",
+        parse: create_restore,
+    },
+    CommandSpec {
+        name: "migrate",
+        arity: -6,
+        flags: &["write", "movablekeys"],
+        keys: KeySpec::Movable(migrate_keys),
+        categories: &["@keyspace", "@write", "@slow", "@dangerous"],
+        group: "generic",
+        summary: "Atomically transfers a key from one Redis instance to another.",
+        parse: create_migrate,
+    },
+    CommandSpec {
+        name: "object",
+        arity: -2,
+        flags: &["readonly"],
+        keys: KeySpec::Range {
+            first: 2,
+            last: 2,
+            step: 1,
+        },
+        categories: &["@keyspace", "@read", "@slow"],
+        group: "generic",
+        summary: "A container for object introspection commands.",
+        parse: create_object,
+    },
+    CommandSpec {
+        name: "publish",
+        arity: 3,
+        flags: &["pubsub", "loading", "stale", "fast"],
+        keys: NO_KEYS,
+        categories: &["@pubsub", "@fast"],
+        group: "pubsub",
+        summary: "Posts a message to a channel.",
+        parse: create_publish,
+    },
+    CommandSpec {
+        name: "subscribe",
+        arity: -2,
+        flags: &["pubsub", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@pubsub", "@slow"],
+        group: "pubsub",
+        summary: "Listens for messages published to channels.",
+        parse: |arr| Ok(Command::Subscribe(bulk_strings(arr)?)),
+    },
+    CommandSpec {
+        name: "unsubscribe",
+        arity: -1,
+        flags: &["pubsub", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@pubsub", "@slow"],
+        group: "pubsub",
+        summary: "Stops listening to messages posted to channels.",
+        parse: |arr| Ok(Command::Unsubscribe(bulk_strings(arr)?)),
+    },
+    CommandSpec {
+        name: "psubscribe",
+        arity: -2,
+        flags: &["pubsub", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@pubsub", "@slow"],
+        group: "pubsub",
+        summary: "Listens for messages published to channels that match one or more patterns.",
+        parse: |arr| Ok(Command::PSubscribe(bulk_strings(arr)?)),
+    },
+    CommandSpec {
+        name: "punsubscribe",
+        arity: -1,
+        flags: &["pubsub", "noscript", "loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@pubsub", "@slow"],
+        group: "pubsub",
+        summary: "Stops listening to messages published to channels that match one or more patterns.",
+        parse: |arr| Ok(Command::PUnsubscribe(bulk_strings(arr)?)),
+    },
+    CommandSpec {
+        name: "pfadd",
+        arity: -2,
+        flags: &["write", "denyoom", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@hyperloglog", "@fast"],
+        group: "hyperloglog",
+        summary: "Adds elements to a HyperLogLog key. Creates the key if it doesn't exist.",
+        parse: create_pfadd,
+    },
+    CommandSpec {
+        name: "pfcount",
+        arity: -2,
+        flags: &["readonly"],
+        keys: ALL_KEYS,
+        categories: &["@read", "@hyperloglog", "@slow"],
+        group: "hyperloglog",
+        summary: "Returns the approximated cardinality of the set(s) observed by the HyperLogLog key(s).",
+        parse: |arr| Ok(Command::PfCount(bulk_strings(arr)?)),
+    },
+    CommandSpec {
+        name: "pfmerge",
+        arity: -2,
+        flags: &["write", "denyoom"],
+        keys: ALL_KEYS,
+        categories: &["@write", "@hyperloglog", "@slow"],
+        group: "hyperloglog",
+        summary: "Merges one or more HyperLogLog values into a single key.",
+        parse: create_pfmerge,
+    },
+    CommandSpec {
+        name: "setbit",
+        arity: 4,
+        flags: &["write", "denyoom"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@bitmap", "@slow"],
+        group: "bitmap",
+        summary: "Sets or clears the bit at offset of the string value. Creates the key if it doesn't exist.",
+        parse: create_setbit,
+    },
+    CommandSpec {
+        name: "getbit",
+        arity: 3,
+        flags: &["readonly", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@bitmap", "@fast"],
+        group: "bitmap",
+        summary: "Returns a bit value by offset.",
+        parse: create_getbit,
+    },
+    CommandSpec {
+        name: "bitcount",
+        arity: -2,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@bitmap", "@slow"],
+        group: "bitmap",
+        summary: "Counts the number of set bits (population counting) in a string.",
+        parse: create_bitcount,
+    },
+    CommandSpec {
+        name: "bitop",
+        arity: -4,
+        flags: &["write", "denyoom"],
+        keys: KeySpec::Range {
+            first: 2,
+            last: -1,
+            step: 1,
+        },
+        categories: &["@write", "@bitmap", "@slow"],
+        group: "bitmap",
+        summary: "Performs bitwise operations on multiple strings, and stores the result.",
+        parse: create_bitop,
+    },
+    CommandSpec {
+        name: "bitpos",
+        arity: -3,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@bitmap", "@slow"],
+        group: "bitmap",
+        summary: "Finds the first set (1) or clear (0) bit in a string.",
+        parse: create_bitpos,
+    },
+    CommandSpec {
+        name: "geoadd",
+        arity: -5,
+        flags: &["write", "denyoom"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@geo", "@slow"],
+        group: "geo",
+        summary: "Adds one or more members to a geospatial index. The key is created if it doesn't exist.",
+        parse: create_geoadd,
+    },
+    CommandSpec {
+        name: "geodist",
+        arity: -4,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@geo", "@slow"],
+        group: "geo",
+        summary: "Returns the distance between two members of a geospatial index.",
+        parse: create_geodist,
+    },
+    CommandSpec {
+        name: "geopos",
+        arity: -2,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@geo", "@slow"],
+        group: "geo",
+        summary: "Returns the longitude and latitude of members from a geospatial index.",
+        parse: |arr| {
+            let (key, members) = key_and_members(arr)?;
+            Ok(Command::GeoPos { key, members })
+        },
+    },
+    CommandSpec {
+        name: "geohash",
+        arity: -2,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@geo", "@slow"],
+        group: "geo",
+        summary: "Returns members from a geospatial index as geohash strings.",
+        parse: |arr| {
+            let (key, members) = key_and_members(arr)?;
+            Ok(Command::GeoHash { key, members })
+        },
+    },
+    CommandSpec {
+        name: "geosearch",
+        arity: -7,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@geo", "@slow"],
+        group: "geo",
+        summary: "Queries a geospatial index for members inside an area of a box or a circle.",
+        parse: create_geosearch,
+    },
+    CommandSpec {
+        name: "xadd",
+        arity: -5,
+        flags: &["write", "denyoom", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@stream", "@fast"],
+        group: "stream",
+        summary: "Appends a new message to a stream. Creates the key if it doesn't exist.",
+        parse: create_xadd,
+    },
+    CommandSpec {
+        name: "xlen",
+        arity: 2,
+        flags: &["readonly", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@stream", "@fast"],
+        group: "stream",
+        summary: "Return the number of messages in a stream.",
+        parse: create_xlen,
+    },
+    CommandSpec {
+        name: "xrange",
+        arity: -4,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@stream", "@slow"],
+        group: "stream",
+        summary: "Returns the messages from a stream within a range of IDs.",
+        parse: |arr| create_xrange(arr, false),
+    },
+    CommandSpec {
+        name: "xrevrange",
+        arity: -4,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@stream", "@slow"],
+        group: "stream",
+        summary: "Returns the messages from a stream within a range of IDs in reverse order.",
+        parse: |arr| create_xrange(arr, true),
+    },
+    CommandSpec {
+        name: "xread",
+        arity: -4,
+        flags: &["readonly", "blocking", "movablekeys"],
+        keys: KeySpec::Movable(stream_keys),
+        categories: &["@read", "@stream", "@slow", "@blocking"],
+        group: "stream",
+        summary: "Returns messages from multiple streams with IDs greater than the ones requested.",
+        parse: create_xread,
+    },
+    CommandSpec {
+        name: "xgroup",
+        arity: -2,
+        flags: &["write", "denyoom"],
+        keys: KeySpec::Range {
+            first: 2,
+            last: 2,
+            step: 1,
+        },
+        categories: &["@write", "@stream", "@slow"],
+        group: "stream",
+        summary: "A container for consumer groups commands.",
+        parse: create_xgroup,
+    },
+    CommandSpec {
+        name: "xreadgroup",
+        arity: -7,
+        flags: &["write", "blocking", "movablekeys"],
+        keys: KeySpec::Movable(stream_keys),
+        categories: &["@write", "@stream", "@slow", "@blocking"],
+        group: "stream",
+        summary: "Returns new or historical messages from a stream for a consumer in a group.",
+        parse: create_xreadgroup,
+    },
+    CommandSpec {
+        name: "xack",
+        arity: -4,
+        flags: &["write", "fast"],
+        keys: FIRST_KEY,
+        categories: &["@write", "@stream", "@fast"],
+        group: "stream",
+        summary: "Returns the number of messages that were successfully acknowledged by the consumer group member of a stream.",
+        parse: create_xack,
+    },
+    CommandSpec {
+        name: "xpending",
+        arity: -3,
+        flags: &["readonly"],
+        keys: FIRST_KEY,
+        categories: &["@read", "@stream", "@slow"],
+        group: "stream",
+        summary: "Returns the information and entries from a stream consumer group's pending entries list.",
+        parse: create_xpending,
+    },
+    CommandSpec {
+        name: "cluster",
+        arity: -2,
+        flags: &["loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@slow"],
+        group: "cluster",
+        summary: "A container for Redis Cluster commands.",
+        parse: create_cluster,
+    },
+    CommandSpec {
+        name: "asking",
+        arity: 1,
+        flags: &["fast"],
+        keys: NO_KEYS,
+        categories: &["@fast", "@connection"],
+        group: "cluster",
+        summary: "Signals that a cluster client is following an -ASK redirect.",
+        parse: |_| Ok(Command::Asking),
+    },
+    CommandSpec {
+        name: "command",
+        arity: -1,
+        flags: &["loading", "stale"],
+        keys: NO_KEYS,
+        categories: &["@slow", "@connection"],
+        group: "server",
+        summary: "Returns detailed information about all commands.",
+        parse: create_command_introspection,
+    },
+];
+
+pub fn command_table() -> &'static [CommandSpec] {
+    COMMAND_TABLE
+}
+
+pub fn lookup(name: &str) -> Option<&'static CommandSpec> {
+    COMMAND_TABLE
+        .iter()
+        .find(|spec| spec.name.eq_ignore_ascii_case(name))
+}
+
+impl CommandSpec {
+    pub fn check_arity(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        match self.arity {
+            arity if arity < 0 => argc >= -arity,
+            arity => argc == arity,
+        }
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    pub fn keys(&self, args: &[String]) -> Vec<String> {
+        let positions = match self.keys {
+            KeySpec::None => Vec::new(),
+            KeySpec::Range { first, last, step } => {
+                let last = match last {
+                    last if last < 0 => args.len() as i64 + last,
+                    last => last,
+                };
+                (first..=last)
+                    .step_by(step as usize)
+                    .map(|i| i as usize)
+                    .collect()
+            }
+            KeySpec::Movable(keys) => keys(args),
+        };
+        positions
+            .into_iter()
+            .filter_map(|i| args.get(i).cloned())
+            .collect()
+    }
+
+    pub fn info(&self) -> Resp {
+        let (first, last, step) = match self.keys {
+            KeySpec::Range { first, last, step } => (first, last, step),
+            KeySpec::None | KeySpec::Movable(_) => (0, 0, 0),
+        };
+        Resp::Array(vec![
+            Resp::BulkString(self.name.into()),
+            Resp::Integer(self.arity),
+            Resp::Array(
+                self.flags
+                    .iter()
+                    .map(|flag| Resp::SimpleString(flag.to_string()))
+                    .collect(),
+            ),
+            Resp::Integer(first),
+            Resp::Integer(last),
+            Resp::Integer(step),
+            Resp::Array(
+                self.categories
+                    .iter()
+                    .map(|category| Resp::SimpleString(category.to_string()))
+                    .collect(),
+            ),
+            Resp::Array(Vec::new()),
+            Resp::Array(Vec::new()),
+            Resp::Array(Vec::new()),
+        ])
+    }
+
+    pub fn docs(&self) -> Resp {
+        Resp::Array(vec![
+            Resp::BulkString("summary".into()),
+            Resp::BulkString(self.summary.into()),
+            Resp::BulkString("group".into()),
+            Resp::BulkString(self.group.into()),
+        ])
+    }
+}
+
+fn stream_keys(args: &[String]) -> Vec<usize> {
+    match args
+        .iter()
+        .position(|arg| arg.eq_ignore_ascii_case("STREAMS"))
+    {
+        Some(streams) => {
+            let count = (args.len() - streams - 1) / 2;
+            (streams + 1..=streams + count).collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+fn migrate_keys(args: &[String]) -> Vec<usize> {
+    let keys = args
+        .iter()
+        .skip(6)
+        .position(|arg| arg.eq_ignore_ascii_case("KEYS"));
+    match keys {
+        Some(keys) if args.get(3).is_some_and(String::is_empty) => (keys + 7..args.len()).collect(),
+        _ if args.len() > 3 => vec![3],
+        _ => Vec::new(),
+    }
+}
+
 fn create_command(mut arr: Vec<Resp>) -> Result<Command, Resp> {
     let name = command_name(&mut arr)?;
-    match name.to_uppercase().as_str() {
-        "PING" => Ok(Command::Ping),
-        "ECHO" => create_echo(arr),
-        "GET" => create_get(arr),
-        "SET" => create_set(arr),
-        "CONFIG" => Ok(Command::ConfigGet),
-        "CLIENT" => Ok(Command::Client),
-        _ => Err(Resp::unkown_command(&name)),
+    let spec = lookup(&name).ok_or_else(|| Resp::unkown_command(&name))?;
+    if !spec.check_arity(arr.len() + 1) {
+        return Err(Resp::wrong_arity(spec.name));
     }
+    (spec.parse)(arr)
 }
 
-fn create_set(mut arr: Vec<Resp>) -> Result<Command, Resp> {
-    if arr.len() != 2 {
-        return Err(Resp::wrong_number_of_arguments());
+fn create_cluster(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    let subcommand = args[0].to_uppercase();
+    match (subcommand.as_str(), args.len()) {
+        ("SLOTS", 1) => Ok(Command::ClusterSlots),
+        ("SHARDS", 1) => Ok(Command::ClusterShards),
+        ("NODES", 1) => Ok(Command::ClusterNodes),
+        ("MYID", 1) => Ok(Command::ClusterMyId),
+        ("KEYSLOT", 2) => Ok(Command::ClusterKeySlot(args[1].clone())),
+        ("ADDSLOTS", n) if n > 1 => Ok(Command::ClusterAddSlots(
+            args[1..]
+                .iter()
+                .map(|slot| parse_slot(slot))
+                .collect::<Result<_, _>>()?,
+        )),
+        ("ADDSLOTSRANGE", n) if n > 1 && n % 2 == 1 => {
+            let mut slots = Vec::new();
+            for range in args[1..].chunks(2) {
+                slots.extend(parse_slot(&range[0])?..=parse_slot(&range[1])?);
+            }
+            Ok(Command::ClusterAddSlots(slots))
+        }
+        ("MEET", 3) => Ok(Command::ClusterMeet(node_address(&args[1], &args[2])?)),
+        ("SETSLOT", 3) | ("SETSLOT", 4) => {
+            let slot = parse_slot(&args[1])?;
+            let node = args.get(3).cloned();
+            let state = match (args[2].to_uppercase().as_str(), node) {
+                ("IMPORTING", Some(node)) => SlotState::Importing(node),
+                ("MIGRATING", Some(node)) => SlotState::Migrating(node),
+                ("NODE", Some(node)) => SlotState::Node(node),
+                ("STABLE", None) => SlotState::Stable,
+                _ => return Err(Resp::syntax_error()),
+            };
+            Ok(Command::ClusterSetSlot { slot, state })
+        }
+        ("GOSSIP", n) if n >= 4 && n % 2 == 0 => {
+            let (ip, port) = args[2].rsplit_once(':').ok_or_else(Resp::syntax_error)?;
+            let nodes = args[4..]
+                .chunks(2)
+                .map(|node| {
+                    let (ip, port) = node[1].rsplit_once(':').ok_or_else(Resp::syntax_error)?;
+                    Ok((node[0].clone(), node_address(ip, port)?))
+                })
+                .collect::<Result<_, Resp>>()?;
+            Ok(Command::ClusterGossip {
+                id: args[1].clone(),
+                address: node_address(ip, port)?,
+                slots: cluster::parse_slot_ranges(&args[3])?,
+                nodes,
+            })
+        }
+        (
+            "SLOTS" | "SHARDS" | "NODES" | "MYID" | "KEYSLOT" | "ADDSLOTS" | "ADDSLOTSRANGE"
+            | "MEET" | "SETSLOT" | "GOSSIP",
+            _,
+        ) => Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::unkown_subcommand(&args[0])),
     }
-    let key = match arr.remove(0) {
-        Resp::BulkString(s) => s,
-        _ => return Err(Resp::invalid_arguments()),
-    };
-    let value = match arr.remove(0) {
-        Resp::BulkString(s) => s,
-        _ => return Err(Resp::invalid_arguments()),
-    };
+}
+
+fn node_address(ip: &str, port: &str) -> Result<SocketAddr, Resp> {
+    match (ip.parse::<IpAddr>(), port.parse::<u16>()) {
+        (Ok(ip), Ok(port)) => Ok(SocketAddr::new(ip, port)),
+        _ => Err(Resp::SimpleError(format!(
+            "ERR Invalid node address specified: {ip}:{port}"
+        ))),
+    }
+}
+
+fn create_command_introspection(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?;
+    if args.is_empty() {
+        return Ok(Command::CommandInfo(Vec::new()));
+    }
+    let subcommand = args.remove(0);
+    match subcommand.to_uppercase().as_str() {
+        "COUNT" if args.is_empty() => Ok(Command::CommandCount),
+        "INFO" => Ok(Command::CommandInfo(args)),
+        "DOCS" => Ok(Command::CommandDocs(args)),
+        "GETKEYS" if !args.is_empty() => Ok(Command::CommandGetKeys(args)),
+        "COUNT" | "GETKEYS" => Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::unkown_subcommand(&subcommand)),
+    }
+}
+
+fn create_set(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let key = bulk_string(arr.remove(0))?;
+    let value = bytes(arr.remove(0))?;
     Ok(Command::Set { key, value })
 }
 fn create_get(mut arr: Vec<Resp>) -> Result<Command, Resp> {
-    if arr.len() != 1 {
+    Ok(Command::Get(bulk_string(arr.remove(0))?))
+}
+
+fn create_incr(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    Ok(Command::Incr(bulk_string(arr.remove(0))?))
+}
+
+fn create_lpush(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let key = bulk_string(arr.remove(0))?;
+    let elements = arr.into_iter().map(bytes).collect::<Result<_, _>>()?;
+    Ok(Command::LPush { key, elements })
+}
+
+fn command_name(arr: &mut Vec<Resp>) -> Result<String, Resp> {
+    if arr.is_empty() {
         return Err(Resp::wrong_number_of_arguments());
     }
     match arr.remove(0) {
-        Resp::BulkString(s) => Ok(Command::Get(s)),
+        Resp::BulkString(s) => Ok(String::from_utf8_lossy(&s).into_owned()),
         _ => Err(Resp::wrong_number_of_arguments()),
     }
 }
 
-fn command_name(arr: &mut Vec<Resp>) -> Result<String, Resp> {
-    match arr.remove(0) {
+fn bulk_string(resp: Resp) -> Result<String, Resp> {
+    match resp {
+        Resp::BulkString(s) => String::from_utf8(s).map_err(|_| Resp::invalid_utf8()),
+        _ => Err(Resp::invalid_arguments()),
+    }
+}
+
+fn bytes(resp: Resp) -> Result<Vec<u8>, Resp> {
+    match resp {
         Resp::BulkString(s) => Ok(s),
-        _ => return Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::invalid_arguments()),
     }
 }
 
-fn create_echo(mut arr: Vec<Resp>) -> Result<Command, Resp> {
-    if arr.len() != 1 {
+fn integer(resp: Resp) -> Result<i64, Resp> {
+    bulk_string(resp)?
+        .parse()
+        .map_err(|_| Resp::not_an_integer())
+}
+
+fn bulk_strings(arr: Vec<Resp>) -> Result<Vec<String>, Resp> {
+    arr.into_iter().map(bulk_string).collect()
+}
+
+fn create_expire(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let key = bulk_string(arr.remove(0))?;
+    let seconds = integer(arr.remove(0))?;
+    Ok(Command::Expire { key, seconds })
+}
+
+fn create_ttl(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    Ok(Command::Ttl(bulk_string(arr.remove(0))?))
+}
+
+fn create_restore(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut arr = arr.into_iter();
+    let key = bulk_string(arr.next().ok_or_else(Resp::wrong_number_of_arguments)?)?;
+    let ttl = integer(arr.next().ok_or_else(Resp::wrong_number_of_arguments)?)?;
+    let payload = bytes(arr.next().ok_or_else(Resp::wrong_number_of_arguments)?)?;
+    if ttl < 0 {
+        return Err(Resp::SimpleError(String::from(
+            "ERR Invalid TTL value, must be >= 0",
+        )));
+    }
+    let (mut replace, mut absttl, mut idletime, mut freq) = (false, false, None, None);
+    while let Some(option) = arr.next() {
+        match bulk_string(option)?.to_uppercase().as_str() {
+            "REPLACE" => replace = true,
+            "ABSTTL" => absttl = true,
+            "IDLETIME" if freq.is_none() => {
+                let seconds = integer(arr.next().ok_or_else(Resp::syntax_error)?)?;
+                let seconds = u64::try_from(seconds).map_err(|_| {
+                    Resp::SimpleError(String::from("ERR Invalid IDLETIME value, must be >= 0"))
+                })?;
+                idletime = Some(seconds);
+            }
+            "FREQ" if idletime.is_none() => {
+                let frequency = integer(arr.next().ok_or_else(Resp::syntax_error)?)?;
+                let frequency = u8::try_from(frequency).map_err(|_| {
+                    Resp::SimpleError(String::from(
+                        "ERR Invalid FREQ value, must be >= 0 and <= 255",
+                    ))
+                })?;
+                freq = Some(frequency);
+            }
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    Ok(Command::Restore {
+        key,
+        ttl,
+        payload,
+        replace,
+        absttl,
+        idletime,
+        freq,
+    })
+}
+
+fn create_migrate(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?.into_iter();
+    let mut next = || args.next().ok_or_else(Resp::wrong_number_of_arguments);
+    let host = next()?;
+    let port: u16 = next()?.parse().map_err(|_| Resp::not_an_integer())?;
+    let key = next()?;
+    let db = next()?.parse().map_err(|_| Resp::not_an_integer())?;
+    let timeout = next()?.parse().map_err(|_| Resp::not_an_integer())?;
+    let (mut copy, mut replace, mut keys) = (false, false, None);
+    while let Some(option) = args.next() {
+        match option.to_uppercase().as_str() {
+            "COPY" => copy = true,
+            "REPLACE" => replace = true,
+            "KEYS" if key.is_empty() => keys = Some(args.by_ref().collect()),
+            "KEYS" => {
+                return Err(Resp::SimpleError(String::from(
+                    "ERR When using MIGRATE KEYS option, the key argument must be set to the empty string",
+                )))
+            }
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    Ok(Command::Migrate {
+        address: format!("{host}:{port}"),
+        keys: keys.unwrap_or_else(|| vec![key]),
+        db,
+        timeout,
+        copy,
+        replace,
+    })
+}
+
+fn create_object(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?;
+    let subcommand = args.remove(0);
+    match (subcommand.to_uppercase().as_str(), args.pop(), args.len()) {
+        ("ENCODING", Some(key), 0) => Ok(Command::ObjectEncoding(key)),
+        ("IDLETIME", Some(key), 0) => Ok(Command::ObjectIdleTime(key)),
+        ("FREQ", Some(key), 0) => Ok(Command::ObjectFreq(key)),
+        ("ENCODING" | "IDLETIME" | "FREQ", _, _) => Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::unkown_subcommand(&subcommand)),
+    }
+}
+
+fn create_publish(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let channel = bulk_string(arr.remove(0))?;
+    let message = bulk_string(arr.remove(0))?;
+    Ok(Command::Publish { channel, message })
+}
+
+fn unsigned<T: std::str::FromStr>(resp: Resp) -> Result<T, Resp> {
+    bulk_string(resp)?
+        .parse()
+        .map_err(|_| Resp::not_an_integer())
+}
+
+fn create_xadd(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?.into_iter();
+    let key = args.next().ok_or_else(Resp::wrong_number_of_arguments)?;
+    let mut nomkstream = false;
+    let mut maxlen = None;
+    let id = loop {
+        let arg = args.next().ok_or_else(Resp::wrong_number_of_arguments)?;
+        match arg.to_uppercase().as_str() {
+            "NOMKSTREAM" => nomkstream = true,
+            "MAXLEN" => {
+                let mut value = args.next().ok_or_else(Resp::syntax_error)?;
+                if value == "=" || value == "~" {
+                    value = args.next().ok_or_else(Resp::syntax_error)?;
+                }
+                maxlen = Some(value.parse().map_err(|_| Resp::not_an_integer())?);
+            }
+            _ => break arg.parse()?,
+        }
+    };
+    let rest: Vec<String> = args.collect();
+    if rest.is_empty() || !rest.len().is_multiple_of(2) {
         return Err(Resp::wrong_number_of_arguments());
     }
-    match arr.remove(0) {
-        Resp::BulkString(s) => Ok(Command::Echo(s)),
-        _ => Err(Resp::wrong_number_of_arguments()),
+    let fields = rest
+        .chunks(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect();
+    Ok(Command::XAdd {
+        key,
+        nomkstream,
+        maxlen,
+        id,
+        fields,
+    })
+}
+
+fn create_pfadd(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let key = bulk_string(arr.remove(0))?;
+    let elements = arr.into_iter().map(bytes).collect::<Result<_, _>>()?;
+    Ok(Command::PfAdd { key, elements })
+}
+
+fn create_pfmerge(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut keys = bulk_strings(arr)?;
+    let destination = keys.remove(0);
+    Ok(Command::PfMerge {
+        destination,
+        sources: keys,
+    })
+}
+
+fn create_setbit(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    Ok(Command::SetBit {
+        key: args[0].clone(),
+        offset: bitops::parse_offset(&args[1])?,
+        value: bitops::parse_bit(&args[2])?,
+    })
+}
+
+fn create_getbit(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    Ok(Command::GetBit {
+        key: args[0].clone(),
+        offset: bitops::parse_offset(&args[1])?,
+    })
+}
+
+fn create_bitcount(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    let range = match args.len() {
+        1 => None,
+        3 | 4 => Some(bit_range(&args[1..])?),
+        _ => return Err(Resp::syntax_error()),
+    };
+    Ok(Command::BitCount {
+        key: args[0].clone(),
+        range,
+    })
+}
+
+fn create_bitpos(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    let range = match args.len() {
+        2 => None,
+        3..=5 => Some(bit_range(&args[2..])?),
+        _ => return Err(Resp::syntax_error()),
+    };
+    Ok(Command::BitPos {
+        key: args[0].clone(),
+        bit: bitops::parse_bit(&args[1])?,
+        range,
+    })
+}
+
+fn bit_range(args: &[String]) -> Result<BitRange, Resp> {
+    let integer = |arg: &String| arg.parse::<i64>().map_err(|_| Resp::not_an_integer());
+    Ok(BitRange {
+        start: integer(&args[0])?,
+        end: args.get(1).map(integer).transpose()?,
+        unit: match args.get(2) {
+            Some(unit) => unit.parse()?,
+            None => Default::default(),
+        },
+    })
+}
+
+fn create_bitop(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?;
+    let op: BitOp = args.remove(0).parse()?;
+    let destination = args.remove(0);
+    if op == BitOp::Not && args.len() != 1 {
+        return Err(Resp::SimpleError(String::from(
+            "ERR BITOP NOT must be called with a single source key.",
+        )));
+    }
+    Ok(Command::BitOp {
+        op,
+        destination,
+        keys: args,
+    })
+}
+
+fn float(arg: &str) -> Result<f64, Resp> {
+    match arg.parse::<f64>() {
+        Ok(value) if !value.is_nan() => Ok(value),
+        _ => Err(Resp::not_a_float()),
+    }
+}
+
+fn key_and_members(arr: Vec<Resp>) -> Result<(String, Vec<String>), Resp> {
+    let mut args = bulk_strings(arr)?;
+    let key = args.remove(0);
+    Ok((key, args))
+}
+
+fn create_geoadd(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    let (mut nx, mut xx, mut ch) = (false, false, false);
+    let mut index = 1;
+    while let Some(option) = args.get(index) {
+        match option.to_uppercase().as_str() {
+            "NX" => nx = true,
+            "XX" => xx = true,
+            "CH" => ch = true,
+            _ => break,
+        }
+        index += 1;
+    }
+    if nx && xx {
+        return Err(Resp::SimpleError(String::from(
+            "ERR XX and NX options at the same time are not compatible",
+        )));
+    }
+    let items = &args[index..];
+    if items.is_empty() || items.len() % 3 != 0 {
+        return Err(Resp::syntax_error());
+    }
+    let items = items
+        .chunks(3)
+        .map(|item| Ok((float(&item[0])?, float(&item[1])?, item[2].clone())))
+        .collect::<Result<_, Resp>>()?;
+    Ok(Command::GeoAdd {
+        key: args[0].clone(),
+        nx,
+        xx,
+        ch,
+        items,
+    })
+}
+
+fn create_geodist(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    let unit = match args.len() {
+        3 => Unit::Meters,
+        4 => args[3].parse()?,
+        _ => return Err(Resp::syntax_error()),
+    };
+    Ok(Command::GeoDist {
+        key: args[0].clone(),
+        first: args[1].clone(),
+        second: args[2].clone(),
+        unit,
+    })
+}
+
+fn create_geosearch(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?.into_iter();
+    let key = args.next().ok_or_else(Resp::syntax_error)?;
+    let mut origin = None;
+    let mut shape = None;
+    let mut unit = Unit::Meters;
+    let mut order = None;
+    let mut count = None;
+    let mut any = false;
+    let (mut withcoord, mut withdist, mut withhash) = (false, false, false);
+    let mut next = || args.next().ok_or_else(Resp::syntax_error);
+    while let Ok(option) = next() {
+        match option.to_uppercase().as_str() {
+            "FROMMEMBER" if origin.is_none() => origin = Some(GeoOrigin::Member(next()?)),
+            "FROMLONLAT" if origin.is_none() => {
+                let longitude = float(&next()?)?;
+                let latitude = float(&next()?)?;
+                geo::validate(longitude, latitude)?;
+                origin = Some(GeoOrigin::LonLat(longitude, latitude));
+            }
+            "BYRADIUS" if shape.is_none() => {
+                let radius = float(&next()?)?;
+                if radius < 0.0 {
+                    return Err(Resp::SimpleError(String::from(
+                        "ERR radius cannot be negative",
+                    )));
+                }
+                unit = next()?.parse()?;
+                shape = Some(Shape::Radius(radius * unit.to_meters()));
+            }
+            "BYBOX" if shape.is_none() => {
+                let width = float(&next()?)?;
+                let height = float(&next()?)?;
+                if width < 0.0 || height < 0.0 {
+                    return Err(Resp::SimpleError(String::from(
+                        "ERR height or width cannot be negative",
+                    )));
+                }
+                unit = next()?.parse()?;
+                shape = Some(Shape::Box {
+                    width: width * unit.to_meters(),
+                    height: height * unit.to_meters(),
+                });
+            }
+            "ASC" => order = Some(Order::Asc),
+            "DESC" => order = Some(Order::Desc),
+            "COUNT" => {
+                let value = next()?.parse::<i64>().map_err(|_| Resp::not_an_integer())?;
+                if value <= 0 {
+                    return Err(Resp::SimpleError(String::from("ERR COUNT must be > 0")));
+                }
+                count = Some(value as usize);
+            }
+            "ANY" => any = true,
+            "WITHCOORD" => withcoord = true,
+            "WITHDIST" => withdist = true,
+            "WITHHASH" => withhash = true,
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    let Some(origin) = origin else {
+        return Err(Resp::SimpleError(String::from(
+            "ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for GEOSEARCH",
+        )));
+    };
+    let Some(shape) = shape else {
+        return Err(Resp::SimpleError(String::from(
+            "ERR exactly one of BYRADIUS and BYBOX can be specified for GEOSEARCH",
+        )));
+    };
+    if any && count.is_none() {
+        return Err(Resp::SimpleError(String::from(
+            "ERR the ANY argument requires COUNT argument",
+        )));
+    }
+    Ok(Command::GeoSearch {
+        key,
+        search: GeoSearch {
+            origin,
+            shape,
+            unit,
+            order,
+            count,
+            any,
+            withcoord,
+            withdist,
+            withhash,
+        },
+    })
+}
+
+fn create_xlen(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    Ok(Command::XLen(bulk_string(arr.remove(0))?))
+}
+
+fn create_xrange(arr: Vec<Resp>, rev: bool) -> Result<Command, Resp> {
+    if arr.len() != 3 && arr.len() != 5 {
+        return Err(Resp::wrong_number_of_arguments());
+    }
+    let args = bulk_strings(arr)?;
+    let (start, end) = if rev {
+        (RangeBound::start(&args[2])?, RangeBound::end(&args[1])?)
+    } else {
+        (RangeBound::start(&args[1])?, RangeBound::end(&args[2])?)
+    };
+    let count = match args.get(3) {
+        Some(option) if option.eq_ignore_ascii_case("COUNT") => {
+            Some(args[4].parse().map_err(|_| Resp::not_an_integer())?)
+        }
+        Some(_) => return Err(Resp::syntax_error()),
+        None => None,
+    };
+    Ok(Command::XRange {
+        key: args[0].clone(),
+        start,
+        end,
+        count,
+        rev,
+    })
+}
+
+fn create_streams(args: Vec<String>) -> Result<Vec<(String, ReadId)>, Resp> {
+    if args.is_empty() || !args.len().is_multiple_of(2) {
+        return Err(Resp::SimpleError(String::from(
+            "ERR Unbalanced 'xread' list of streams: for each stream key an ID or '$' must be specified.",
+        )));
+    }
+    let (keys, ids) = args.split_at(args.len() / 2);
+    keys.iter()
+        .zip(ids)
+        .map(|(key, id)| Ok((key.clone(), id.parse()?)))
+        .collect()
+}
+
+fn create_xread(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?.into_iter();
+    let mut count = None;
+    let mut block = None;
+    loop {
+        let arg = args.next().ok_or_else(Resp::syntax_error)?;
+        match arg.to_uppercase().as_str() {
+            "COUNT" => count = Some(unsigned(next_resp(&mut args)?)?),
+            "BLOCK" => block = Some(unsigned(next_resp(&mut args)?)?),
+            "STREAMS" => break,
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    let streams = create_streams(args.collect())?;
+    if streams.iter().any(|(_, id)| *id == ReadId::Undelivered) {
+        return Err(Resp::SimpleError(String::from(
+            "ERR The > ID can be specified only when calling XREADGROUP using the GROUP <group> <consumer> option.",
+        )));
+    }
+    Ok(Command::XRead {
+        count,
+        block,
+        streams,
+    })
+}
+
+fn next_resp(args: &mut impl Iterator<Item = String>) -> Result<Resp, Resp> {
+    args.next()
+        .map(|s| Resp::BulkString(s.into()))
+        .ok_or_else(Resp::syntax_error)
+}
+
+fn create_xgroup(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let args = bulk_strings(arr)?;
+    let subcommand = match args.first() {
+        Some(subcommand) => subcommand.to_uppercase(),
+        None => return Err(Resp::wrong_number_of_arguments()),
+    };
+    match (subcommand.as_str(), args.len()) {
+        ("CREATE", 4) | ("CREATE", 5) => {
+            let mkstream = match args.get(4) {
+                Some(option) if option.eq_ignore_ascii_case("MKSTREAM") => true,
+                Some(_) => return Err(Resp::syntax_error()),
+                None => false,
+            };
+            Ok(Command::XGroupCreate {
+                key: args[1].clone(),
+                group: args[2].clone(),
+                id: args[3].parse()?,
+                mkstream,
+            })
+        }
+        ("DESTROY", 3) => Ok(Command::XGroupDestroy {
+            key: args[1].clone(),
+            group: args[2].clone(),
+        }),
+        ("CREATE", _) | ("DESTROY", _) => Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::unkown_subcommand(&args[0])),
+    }
+}
+
+fn create_xreadgroup(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?.into_iter();
+    match args.next() {
+        Some(option) if option.eq_ignore_ascii_case("GROUP") => {}
+        _ => return Err(Resp::syntax_error()),
+    }
+    let group = args.next().ok_or_else(Resp::syntax_error)?;
+    let consumer = args.next().ok_or_else(Resp::syntax_error)?;
+    let mut count = None;
+    let mut block = None;
+    let mut noack = false;
+    loop {
+        let arg = args.next().ok_or_else(Resp::syntax_error)?;
+        match arg.to_uppercase().as_str() {
+            "COUNT" => count = Some(unsigned(next_resp(&mut args)?)?),
+            "BLOCK" => block = Some(unsigned(next_resp(&mut args)?)?),
+            "NOACK" => noack = true,
+            "STREAMS" => break,
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    Ok(Command::XReadGroup {
+        group,
+        consumer,
+        count,
+        block,
+        noack,
+        streams: create_streams(args.collect())?,
+    })
+}
+
+fn create_xack(arr: Vec<Resp>) -> Result<Command, Resp> {
+    if arr.len() < 3 {
+        return Err(Resp::wrong_number_of_arguments());
+    }
+    let mut args = bulk_strings(arr)?.into_iter();
+    let key = args.next().unwrap_or_default();
+    let group = args.next().unwrap_or_default();
+    let ids = args.map(|id| id.parse()).collect::<Result<_, _>>()?;
+    Ok(Command::XAck { key, group, ids })
+}
+
+fn create_xpending(arr: Vec<Resp>) -> Result<Command, Resp> {
+    if arr.len() < 2 {
+        return Err(Resp::wrong_number_of_arguments());
+    }
+    let mut args = bulk_strings(arr)?.into_iter();
+    let key = args.next().unwrap_or_default();
+    let group = args.next().unwrap_or_default();
+    let mut rest: Vec<String> = args.collect();
+    if rest.is_empty() {
+        return Ok(Command::XPending {
+            key,
+            group,
+            details: None,
+        });
     }
+    let mut idle = None;
+    if rest[0].eq_ignore_ascii_case("IDLE") && rest.len() > 1 {
+        idle = Some(rest[1].parse().map_err(|_| Resp::not_an_integer())?);
+        rest.drain(..2);
+    }
+    if rest.len() != 3 && rest.len() != 4 {
+        return Err(Resp::syntax_error());
+    }
+    let details = PendingRange {
+        idle,
+        start: RangeBound::start(&rest[0])?,
+        end: RangeBound::end(&rest[1])?,
+        count: rest[2].parse().map_err(|_| Resp::not_an_integer())?,
+        consumer: rest.get(3).cloned(),
+    };
+    Ok(Command::XPending {
+        key,
+        group,
+        details: Some(details),
+    })
+}
+
+fn create_select(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    Ok(Command::Select(integer(arr.remove(0))?))
+}
+
+fn create_move(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let key = bulk_string(arr.remove(0))?;
+    let db = integer(arr.remove(0))?;
+    Ok(Command::Move { key, db })
+}
+
+fn create_swapdb(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    let first = integer(arr.remove(0))?;
+    let second = integer(arr.remove(0))?;
+    Ok(Command::SwapDb(first, second))
 }
+
+fn create_flush(arr: Vec<Resp>, command: Command) -> Result<Command, Resp> {
+    if arr.len() > 1 {
+        return Err(Resp::wrong_number_of_arguments());
+    }
+    for option in arr {
+        match bulk_string(option)?.to_uppercase().as_str() {
+            "SYNC" | "ASYNC" => {}
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    Ok(command)
+}
+
+fn create_info(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    match arr.len() {
+        0 => Ok(Command::Info(None)),
+        1 => Ok(Command::Info(Some(bulk_string(arr.remove(0))?))),
+        _ => Err(Resp::syntax_error()),
+    }
+}
+
+/// `SHUTDOWN SAVE` is rejected up front: there is no persistence, so the
+/// server could never honour it and would refuse to stop.
+fn create_shutdown(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    if arr.len() > 1 {
+        return Err(Resp::syntax_error());
+    }
+    let Some(mode) = arr.pop() else {
+        return Ok(Command::Shutdown(ShutdownMode::Default));
+    };
+    match bulk_string(mode)?.to_uppercase().as_str() {
+        "SAVE" => Err(Resp::SimpleError(String::from(
+            "ERR persistence is not supported, use SHUTDOWN NOSAVE",
+        ))),
+        "NOSAVE" => Ok(Command::Shutdown(ShutdownMode::NoSave)),
+        _ => Err(Resp::syntax_error()),
+    }
+}
+
+fn create_client(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?;
+    let subcommand = args.remove(0).to_uppercase();
+    match (subcommand.as_str(), args.len()) {
+        ("ID", 0) => Ok(Command::ClientId),
+        ("GETREDIR", 0) => Ok(Command::ClientGetRedir),
+        ("TRACKINGINFO", 0) => Ok(Command::ClientTrackingInfo),
+        ("CACHING", 1) => match args[0].to_uppercase().as_str() {
+            "YES" => Ok(Command::ClientCaching(true)),
+            "NO" => Ok(Command::ClientCaching(false)),
+            _ => Err(Resp::syntax_error()),
+        },
+        ("TRACKING", n) if n > 0 => create_client_tracking(args.into_iter()),
+        ("ID" | "GETREDIR" | "TRACKINGINFO" | "CACHING" | "TRACKING", _) => {
+            Err(Resp::wrong_number_of_arguments())
+        }
+        _ => Ok(Command::Client),
+    }
+}
+
+fn create_client_tracking(mut args: impl Iterator<Item = String>) -> Result<Command, Resp> {
+    let enabled = match args.next().unwrap_or_default().to_uppercase().as_str() {
+        "ON" => true,
+        "OFF" => false,
+        _ => return Err(Resp::syntax_error()),
+    };
+    let mut options = TrackingOptions::default();
+    while let Some(option) = args.next() {
+        match option.to_uppercase().as_str() {
+            "REDIRECT" if options.redirect.is_some() => {
+                return Err(Resp::SimpleError(String::from(
+                    "ERR A client can only redirect to a single other client",
+                )))
+            }
+            "REDIRECT" => {
+                let id = args.next().ok_or_else(Resp::syntax_error)?;
+                options.redirect = Some(id.parse().map_err(|_| Resp::not_an_integer())?);
+            }
+            "PREFIX" => options
+                .prefixes
+                .push(args.next().ok_or_else(Resp::syntax_error)?),
+            "BCAST" => options.bcast = true,
+            "OPTIN" => options.optin = true,
+            "OPTOUT" => options.optout = true,
+            "NOLOOP" => options.noloop = true,
+            _ => return Err(Resp::syntax_error()),
+        }
+    }
+    if enabled {
+        Ok(Command::ClientTracking(Some(options)))
+    } else {
+        Ok(Command::ClientTracking(None))
+    }
+}
+
+fn create_hello(arr: Vec<Resp>) -> Result<Command, Resp> {
+    let mut args = bulk_strings(arr)?.into_iter();
+    let Some(protover) = args.next() else {
+        return Ok(Command::Hello(None));
+    };
+    let protover = protover.parse().map_err(|_| {
+        Resp::SimpleError(String::from(
+            "ERR Protocol version is not an integer or out of range",
+        ))
+    })?;
+    while let Some(option) = args.next() {
+        let values = match option.to_uppercase().as_str() {
+            "AUTH" => 2,
+            "SETNAME" => 1,
+            _ => 0,
+        };
+        if values == 0 || args.by_ref().take(values).count() != values {
+            return Err(Resp::SimpleError(format!(
+                "ERR Syntax error in HELLO option '{option}'"
+            )));
+        }
+    }
+    Ok(Command::Hello(Some(protover)))
+}
+
+fn create_config(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    if arr.is_empty() {
+        return Err(Resp::wrong_number_of_arguments());
+    }
+    let subcommand = bulk_string(arr.remove(0))?;
+    match (subcommand.to_uppercase().as_str(), arr.len()) {
+        ("GET", 1) => Ok(Command::ConfigGet(bulk_string(arr.remove(0))?)),
+        ("SET", 2) => {
+            let parameter = bulk_string(arr.remove(0))?;
+            let value = bulk_string(arr.remove(0))?;
+            Ok(Command::ConfigSet { parameter, value })
+        }
+        ("GET", _) | ("SET", _) => Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::unkown_subcommand(&subcommand)),
+    }
+}
+
+fn create_slowlog(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    if arr.is_empty() {
+        return Err(Resp::wrong_number_of_arguments());
+    }
+    let subcommand = bulk_string(arr.remove(0))?;
+    match (subcommand.to_uppercase().as_str(), arr.len()) {
+        ("GET", 0) => Ok(Command::SlowLogGet(None)),
+        ("GET", 1) => {
+            let count = bulk_string(arr.remove(0))?;
+            match count.parse() {
+                Ok(count) => Ok(Command::SlowLogGet(Some(count))),
+                Err(_) => Err(Resp::not_an_integer()),
+            }
+        }
+        ("LEN", 0) => Ok(Command::SlowLogLen),
+        ("RESET", 0) => Ok(Command::SlowLogReset),
+        ("GET", _) | ("LEN", _) | ("RESET", _) => Err(Resp::wrong_number_of_arguments()),
+        _ => Err(Resp::unkown_subcommand(&subcommand)),
+    }
+}
+
+fn create_echo(mut arr: Vec<Resp>) -> Result<Command, Resp> {
+    Ok(Command::Echo(bytes(arr.remove(0))?))
+}
+#[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn parse_ping() -> Result<(), String> {
         let name = String::from("PING");
-        let resp = vec![Resp::BulkString(name)];
+        let resp = vec![Resp::BulkString(name.into())];
         let command = Command::try_from(resp).map_err(|err| err.to_string())?;
         assert_eq!(Command::Ping, command);
         Ok(())
@@ -97,9 +1919,207 @@ mod tests {
     fn parse_echo() -> Result<(), String> {
         let name = String::from("ECHO");
         let arg = String::from("test");
-        let resp = vec![Resp::BulkString(name), Resp::BulkString(arg.clone())];
+        let resp = vec![
+            Resp::BulkString(name.into()),
+            Resp::BulkString(arg.clone().into()),
+        ];
         let command = Command::try_from(resp).map_err(|err| err.to_string())?;
-        assert_eq!(Command::Echo(arg), command);
+        assert_eq!(Command::Echo(arg.into_bytes()), command);
         Ok(())
     }
+
+    #[test]
+    fn parse_binary_keys() -> Result<(), String> {
+        let set = |key: &[u8]| {
+            Command::try_from(vec![
+                Resp::BulkString("SET".into()),
+                Resp::BulkString(key.to_vec()),
+                Resp::BulkString(vec![0xff, 0x00]),
+            ])
+        };
+        for key in [&b"\xfe"[..], b"\xff", b"a\xc3"] {
+            assert_eq!(Err(Resp::invalid_utf8()), set(key));
+        }
+        let command = set("ключ".as_bytes()).map_err(|err| err.to_string())?;
+        assert_eq!(
+            Command::Set {
+                key: String::from("ключ"),
+                value: vec![0xff, 0x00]
+            },
+            command
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_slowlog() -> Result<(), String> {
+        let resp = vec![
+            Resp::BulkString("slowlog".into()),
+            Resp::BulkString("get".into()),
+            Resp::BulkString("5".into()),
+        ];
+        let command = Command::try_from(resp).map_err(|err| err.to_string())?;
+        assert_eq!(Command::SlowLogGet(Some(5)), command);
+        let resp = vec![
+            Resp::BulkString("SLOWLOG".into()),
+            Resp::BulkString("LEN".into()),
+            Resp::BulkString("5".into()),
+        ];
+        assert_eq!(
+            Err(Resp::wrong_number_of_arguments()),
+            Command::try_from(resp)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_client() -> Result<(), String> {
+        let command = |args: &[&str]| {
+            let resp = args.iter().map(|arg| Resp::BulkString((*arg).into()));
+            Command::try_from(resp.collect::<Vec<_>>())
+        };
+        assert_eq!(Ok(Command::ClientId), command(&["CLIENT", "id"]));
+        assert_eq!(
+            Ok(Command::ClientCaching(false)),
+            command(&["CLIENT", "CACHING", "no"])
+        );
+        let want = TrackingOptions {
+            redirect: Some(3),
+            prefixes: vec![String::from("a:"), String::from("b:")],
+            bcast: true,
+            noloop: true,
+            ..TrackingOptions::default()
+        };
+        assert_eq!(
+            Ok(Command::ClientTracking(Some(want))),
+            command(&[
+                "CLIENT", "TRACKING", "on", "redirect", "3", "bcast", "prefix", "a:", "prefix",
+                "b:", "noloop"
+            ])
+        );
+        assert_eq!(
+            Ok(Command::ClientTracking(None)),
+            command(&["CLIENT", "TRACKING", "OFF"])
+        );
+        assert_eq!(
+            Err(Resp::syntax_error()),
+            command(&["CLIENT", "TRACKING", "ON", "PREFIX"])
+        );
+        assert_eq!(Ok(Command::Client), command(&["CLIENT", "SETNAME", "name"]));
+        assert_eq!(
+            Ok(Command::Hello(Some(3))),
+            command(&["HELLO", "3", "SETNAME", "a"])
+        );
+        assert!(command(&["HELLO", "3", "AUTH", "user"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_move() -> Result<(), String> {
+        let resp = vec![
+            Resp::BulkString("MOVE".into()),
+            Resp::BulkString("key".into()),
+            Resp::BulkString("1".into()),
+        ];
+        let command = Command::try_from(resp).map_err(|err| err.to_string())?;
+        let want = Command::Move {
+            key: String::from("key"),
+            db: 1,
+        };
+        assert_eq!(want, command);
+        let resp = vec![
+            Resp::BulkString("SELECT".into()),
+            Resp::BulkString("one".into()),
+        ];
+        assert_eq!(Err(Resp::not_an_integer()), Command::try_from(resp));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_xreadgroup() -> Result<(), String> {
+        let resp = [
+            "XREADGROUP",
+            "GROUP",
+            "g",
+            "c",
+            "COUNT",
+            "2",
+            "STREAMS",
+            "a",
+            "b",
+            ">",
+            "0",
+        ]
+        .iter()
+        .map(|arg| Resp::BulkString((*arg).into()))
+        .collect::<Vec<_>>();
+        let command = Command::try_from(resp).map_err(|err| err.to_string())?;
+        let want = Command::XReadGroup {
+            group: String::from("g"),
+            consumer: String::from("c"),
+            count: Some(2),
+            block: None,
+            noack: false,
+            streams: vec![
+                (String::from("a"), ReadId::Undelivered),
+                (String::from("b"), ReadId::Id(StreamId::MIN)),
+            ],
+        };
+        assert_eq!(want, command);
+        let resp = ["XREAD", "STREAMS", "a", "b", "0"]
+            .iter()
+            .map(|arg| Resp::BulkString((*arg).into()))
+            .collect::<Vec<_>>();
+        assert!(Command::try_from(resp).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn command_table_arity() {
+        let resp = vec![Resp::BulkString("get".into())];
+        assert_eq!(Err(Resp::wrong_arity("get")), Command::try_from(resp));
+        let resp = vec![Resp::BulkString("DEL".into())];
+        assert_eq!(Err(Resp::wrong_arity("del")), Command::try_from(resp));
+        let resp = vec![Resp::BulkString("nope".into())];
+        assert_eq!(Err(Resp::unkown_command("nope")), Command::try_from(resp));
+        assert_eq!(
+            Err(Resp::wrong_number_of_arguments()),
+            Command::try_from(Vec::new())
+        );
+    }
+
+    #[test]
+    fn command_keys() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let del = lookup("del").unwrap();
+        assert_eq!(args(&["a", "b"]), del.keys(&args(&["DEL", "a", "b"])));
+        let xread = lookup("XREAD").unwrap();
+        assert_eq!(
+            args(&["s1", "s2"]),
+            xread.keys(&args(&[
+                "XREAD", "COUNT", "1", "STREAMS", "s1", "s2", "0", "0"
+            ]))
+        );
+        assert!(lookup("ping").unwrap().keys(&args(&["PING"])).is_empty());
+        let migrate = lookup("migrate").unwrap();
+        assert_eq!(
+            args(&["key"]),
+            migrate.keys(&args(&["MIGRATE", "host", "6379", "key", "0", "1000"]))
+        );
+        assert_eq!(
+            args(&["a", "b"]),
+            migrate.keys(&args(&[
+                "MIGRATE", "host", "6379", "", "0", "1000", "REPLACE", "KEYS", "a", "b"
+            ]))
+        );
+        for spec in COMMAND_TABLE {
+            let movable = matches!(spec.keys, KeySpec::Movable(_));
+            assert_eq!(
+                movable,
+                spec.flags.contains(&"movablekeys"),
+                "{}",
+                spec.name
+            );
+        }
+    }
 }
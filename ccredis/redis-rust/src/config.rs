@@ -0,0 +1,168 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::notify::KeyspaceEvents;
+
+pub struct Config {
+    pub databases: usize,
+    pub slowlog_log_slower_than: i64,
+    pub slowlog_max_len: usize,
+    pub notify_keyspace_events: KeyspaceEvents,
+    pub maxmemory: usize,
+    pub maxmemory_policy: MaxMemoryPolicy,
+    pub cluster_enabled: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            databases: 16,
+            slowlog_log_slower_than: 10000,
+            slowlog_max_len: 128,
+            notify_keyspace_events: KeyspaceEvents::default(),
+            maxmemory: 0,
+            maxmemory_policy: MaxMemoryPolicy::NoEviction,
+            cluster_enabled: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaxMemoryPolicy {
+    NoEviction,
+    AllKeysRandom,
+    VolatileRandom,
+    VolatileTtl,
+}
+
+impl FromStr for MaxMemoryPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "noeviction" => Ok(MaxMemoryPolicy::NoEviction),
+            "allkeys-random" => Ok(MaxMemoryPolicy::AllKeysRandom),
+            "volatile-random" => Ok(MaxMemoryPolicy::VolatileRandom),
+            "volatile-ttl" => Ok(MaxMemoryPolicy::VolatileTtl),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Display for MaxMemoryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MaxMemoryPolicy::NoEviction => "noeviction",
+            MaxMemoryPolicy::AllKeysRandom => "allkeys-random",
+            MaxMemoryPolicy::VolatileRandom => "volatile-random",
+            MaxMemoryPolicy::VolatileTtl => "volatile-ttl",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Config {
+    const PARAMETERS: [&'static str; 7] = [
+        "databases",
+        "slowlog-log-slower-than",
+        "slowlog-max-len",
+        "notify-keyspace-events",
+        "maxmemory",
+        "maxmemory-policy",
+        "cluster-enabled",
+    ];
+
+    pub fn get(&self, pattern: &str) -> Vec<(String, String)> {
+        Self::PARAMETERS
+            .iter()
+            .filter(|name| glob_match(&pattern.to_lowercase(), name))
+            .filter_map(|name| self.value(name).map(|value| (name.to_string(), value)))
+            .collect()
+    }
+
+    pub fn set(&mut self, parameter: &str, value: &str) -> Result<(), String> {
+        match parameter.to_lowercase().as_str() {
+            "slowlog-log-slower-than" => {
+                self.slowlog_log_slower_than = parse_value(parameter, value)?
+            }
+            "slowlog-max-len" => self.slowlog_max_len = parse_value(parameter, value)?,
+            "notify-keyspace-events" => {
+                self.notify_keyspace_events = parse_value(parameter, value)?
+            }
+            "maxmemory" => self.maxmemory = parse_value(parameter, value)?,
+            "maxmemory-policy" => self.maxmemory_policy = parse_value(parameter, value)?,
+            "databases" | "cluster-enabled" => {
+                return Err(format!(
+                    "ERR CONFIG SET failed (possibly related to argument '{parameter}') - can't set immutable config"
+                ))
+            }
+            _ => {
+                return Err(format!(
+                    "ERR Unknown option or number of arguments for CONFIG SET - '{parameter}'"
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&self, parameter: &str) -> Option<String> {
+        match parameter {
+            "databases" => Some(self.databases.to_string()),
+            "slowlog-log-slower-than" => Some(self.slowlog_log_slower_than.to_string()),
+            "slowlog-max-len" => Some(self.slowlog_max_len.to_string()),
+            "notify-keyspace-events" => Some(self.notify_keyspace_events.to_string()),
+            "maxmemory" => Some(self.maxmemory.to_string()),
+            "maxmemory-policy" => Some(self.maxmemory_policy.to_string()),
+            "cluster-enabled" if self.cluster_enabled => Some(String::from("yes")),
+            "cluster-enabled" => Some(String::from("no")),
+            _ => None,
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(parameter: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("ERR Invalid argument '{value}' for CONFIG SET '{parameter}'"))
+}
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_chars(&pattern, &text)
+}
+
+fn glob_match_chars(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') => (0..=text.len()).any(|i| glob_match_chars(&pattern[1..], &text[i..])),
+        Some('?') => !text.is_empty() && glob_match_chars(&pattern[1..], &text[1..]),
+        Some(c) => text.first() == Some(c) && glob_match_chars(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", "slowlog-max-len"));
+        assert!(glob_match("slowlog-*", "slowlog-max-len"));
+        assert!(glob_match("slowlog-max-le?", "slowlog-max-len"));
+        assert!(!glob_match("slowlog-", "slowlog-max-len"));
+    }
+
+    #[test]
+    fn set_and_get() -> Result<(), String> {
+        let mut config = Config::default();
+        config.set("slowlog-max-len", "5")?;
+        assert_eq!(
+            vec![(String::from("slowlog-max-len"), String::from("5"))],
+            config.get("slowlog-max-len")
+        );
+        assert!(config.set("slowlog-max-len", "abc").is_err());
+        assert!(config.set("unknown", "1").is_err());
+        assert!(config.set("databases", "4").is_err());
+        Ok(())
+    }
+}
@@ -1,26 +1,180 @@
 use std::{
+    cell::Cell,
     collections::HashMap,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
+const LFU_INIT_VAL: u8 = 5;
+const LFU_LOG_FACTOR: f64 = 10.0;
+const LFU_DECAY_TIME: Duration = Duration::from_secs(60);
+
 pub struct Dictionary<V> {
     inner: HashMap<String, Entry<V>>,
+    modified: Vec<String>,
+}
+impl<V> Default for Dictionary<V> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 impl<V> Dictionary<V> {
     pub fn new() -> Self {
         Self {
             inner: HashMap::new(),
+            modified: Vec::new(),
         }
     }
     pub fn get(&self, key: &str) -> Option<&V> {
         self.inner
             .get(key)
-            .map(|value| match value.expires_at {
-                Some(t) if t > SystemTime::now() => Some(&value.value),
-                Some(_) => None,
-                None => Some(&value.value),
+            .filter(|value| !value.is_expired())
+            .map(|value| {
+                value.touch();
+                &value.value
             })
-            .flatten()
+    }
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let entry = self
+            .inner
+            .get_mut(key)
+            .filter(|value| !value.is_expired())?;
+        entry.touch();
+        self.modified.push(key.to_string());
+        Some(&mut entry.value)
+    }
+    pub fn entry(&self, key: &str) -> Option<&Entry<V>> {
+        self.inner.get(key).filter(|value| !value.is_expired())
+    }
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entry(key).is_some()
+    }
+    pub fn len(&self) -> usize {
+        self.inner.values().filter(|e| !e.is_expired()).count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn expires(&self) -> usize {
+        self.inner
+            .values()
+            .filter(|e| e.expires_at.is_some() && !e.is_expired())
+            .count()
+    }
+    pub fn avg_ttl(&self) -> Duration {
+        let now = SystemTime::now();
+        let ttls: Vec<Duration> = self
+            .inner
+            .values()
+            .filter_map(|e| e.expires_at)
+            .filter_map(|t| t.duration_since(now).ok())
+            .collect();
+        if ttls.is_empty() {
+            return Duration::ZERO;
+        }
+        ttls.iter().sum::<Duration>() / ttls.len() as u32
+    }
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+    pub fn take(&mut self, key: &str) -> Option<Entry<V>> {
+        let entry = self.inner.remove(key)?;
+        self.modified.push(key.to_string());
+        Some(entry).filter(|e| !e.is_expired())
+    }
+    pub fn insert_entry(&mut self, key: String, entry: Entry<V>) {
+        self.modified.push(key.clone());
+        self.inner.insert(key, entry);
+    }
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        self.take(key).map(|e| e.value)
+    }
+    pub fn set_expiry(&mut self, key: &str, expires_at: SystemTime) -> bool {
+        match self.inner.get_mut(key).filter(|e| !e.is_expired()) {
+            Some(entry) => {
+                entry.expires_at = Some(expires_at);
+                self.modified.push(key.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn ttl(&self, key: &str) -> Option<Option<Duration>> {
+        self.inner.get(key).filter(|e| !e.is_expired()).map(|e| {
+            e.expires_at
+                .map(|t| t.duration_since(SystemTime::now()).unwrap_or_default())
+        })
+    }
+    pub fn remove_if_expired(&mut self, key: &str) -> bool {
+        if self.inner.get(key).is_some_and(Entry::is_expired) {
+            self.inner.remove(key);
+            self.modified.push(key.to_string());
+            return true;
+        }
+        false
+    }
+    pub fn remove_expired(&mut self) -> Vec<String> {
+        let expired: Vec<String> = self
+            .inner
+            .iter()
+            .filter(|(_, e)| e.is_expired())
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired.iter() {
+            self.inner.remove(key);
+        }
+        self.modified.extend(expired.iter().cloned());
+        expired
+    }
+    pub fn take_modified(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.modified)
+    }
+    pub fn random_key(&self, volatile: bool) -> Option<String> {
+        let keys: Vec<&String> = self
+            .inner
+            .iter()
+            .filter(|(_, e)| !volatile || e.expires_at.is_some())
+            .map(|(key, _)| key)
+            .collect();
+        if keys.is_empty() {
+            return None;
+        }
+        let seed = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos() as usize;
+        Some(keys[seed % keys.len()].clone())
+    }
+    pub fn soonest_expiring_key(&self) -> Option<String> {
+        self.inner
+            .iter()
+            .filter_map(|(key, e)| e.expires_at.map(|t| (key, t)))
+            .min_by_key(|(_, t)| *t)
+            .map(|(key, _)| key.clone())
+    }
+}
+
+pub trait MemoryUsage {
+    fn memory_usage(&self) -> usize;
+}
+
+impl MemoryUsage for String {
+    fn memory_usage(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl MemoryUsage for Vec<u8> {
+    fn memory_usage(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<V: MemoryUsage> Dictionary<V> {
+    pub fn used_memory(&self) -> usize {
+        self.inner
+            .iter()
+            .map(|(key, e)| key.capacity() + e.value.memory_usage())
+            .sum()
     }
     pub fn set(
         &mut self,
@@ -36,10 +190,7 @@ impl<V> Dictionary<V> {
             None => true,
         };
         if can_set {
-            let mut expires_at = expire_rule
-                .as_ref()
-                .map(|r| r.calculate_expire_time())
-                .flatten();
+            let mut expires_at = expire_rule.as_ref().and_then(|r| r.calculate_expire_time());
             let old = self.inner.remove(&key);
             if let Some(ref old) = old {
                 if let Some(ExpireRule::KEEPTTL) = expire_rule {
@@ -47,6 +198,7 @@ impl<V> Dictionary<V> {
                 }
             }
             let entry = Entry::new(value, expires_at);
+            self.modified.push(key.clone());
             self.inner.insert(key, entry);
             if get {
                 return old.map(|e| e.value);
@@ -55,14 +207,62 @@ impl<V> Dictionary<V> {
         None
     }
 }
-struct Entry<V> {
+pub struct Entry<V> {
     value: V,
     expires_at: Option<SystemTime>,
+    last_access: Cell<Instant>,
+    frequency: Cell<u8>,
 }
 
 impl<V> Entry<V> {
-    fn new(value: V, expires_at: Option<SystemTime>) -> Self {
-        Self { value, expires_at }
+    pub fn new(value: V, expires_at: Option<SystemTime>) -> Self {
+        Self {
+            value,
+            expires_at,
+            last_access: Cell::new(Instant::now()),
+            frequency: Cell::new(LFU_INIT_VAL),
+        }
+    }
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+    fn is_expired(&self) -> bool {
+        matches!(self.expires_at, Some(t) if t <= SystemTime::now())
+    }
+    pub fn idle_time(&self) -> Duration {
+        self.last_access.get().elapsed()
+    }
+    pub fn set_idle_time(&self, idle: Duration) {
+        let now = Instant::now();
+        self.last_access.set(now.checked_sub(idle).unwrap_or(now));
+    }
+    pub fn frequency(&self) -> u8 {
+        let periods = self.idle_time().as_secs() / LFU_DECAY_TIME.as_secs();
+        self.frequency
+            .get()
+            .saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+    pub fn set_frequency(&self, frequency: u8) {
+        self.frequency.set(frequency);
+    }
+    fn touch(&self) {
+        let mut frequency = self.frequency();
+        if frequency < u8::MAX {
+            let base = frequency.saturating_sub(LFU_INIT_VAL) as f64;
+            let probability = 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos();
+            if (nanos % 1_000_000) as f64 / 1_000_000.0 < probability {
+                frequency += 1;
+            }
+        }
+        self.frequency.set(frequency);
+        self.last_access.set(Instant::now());
     }
 }
 
@@ -89,3 +289,23 @@ impl ExpireRule {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_key_does_not_touch() {
+        let mut dictionary = Dictionary::new();
+        dictionary.insert_entry(String::from("key"), Entry::new(1, None));
+        let entry = dictionary.entry("key").unwrap();
+        entry.set_idle_time(Duration::from_secs(100));
+        entry.set_frequency(0);
+        assert!(dictionary.contains_key("key"));
+        let entry = dictionary.entry("key").unwrap();
+        assert!(entry.idle_time() >= Duration::from_secs(100));
+        assert_eq!(0, entry.frequency());
+        dictionary.get("key");
+        assert!(dictionary.entry("key").unwrap().idle_time() < Duration::from_secs(100));
+    }
+}
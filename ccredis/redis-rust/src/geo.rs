@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+use crate::resp::Resp;
+
+const STEP: u32 = 26;
+const LAT_MIN: f64 = -85.051_128_78;
+const LAT_MAX: f64 = 85.051_128_78;
+const LONG_MIN: f64 = -180.0;
+const LONG_MAX: f64 = 180.0;
+const EARTH_RADIUS: f64 = 6_372_797.560_856;
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Unit {
+    Meters,
+    Kilometers,
+    Feet,
+    Miles,
+}
+
+impl Unit {
+    pub fn to_meters(self) -> f64 {
+        match self {
+            Unit::Meters => 1.0,
+            Unit::Kilometers => 1000.0,
+            Unit::Feet => 0.3048,
+            Unit::Miles => 1609.34,
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = Resp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "m" => Ok(Unit::Meters),
+            "km" => Ok(Unit::Kilometers),
+            "ft" => Ok(Unit::Feet),
+            "mi" => Ok(Unit::Miles),
+            _ => Err(Resp::SimpleError(String::from(
+                "ERR unsupported unit provided. please use M, KM, FT, MI",
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Shape {
+    Radius(f64),
+    Box { width: f64, height: f64 },
+}
+
+impl Shape {
+    pub fn contains(&self, center: (f64, f64), point: (f64, f64)) -> Option<f64> {
+        match *self {
+            Shape::Radius(radius) => {
+                Some(distance(center, point)).filter(|distance| *distance <= radius)
+            }
+            Shape::Box { width, height } => {
+                let lat_distance = EARTH_RADIUS * (point.1 - center.1).to_radians().abs();
+                if lat_distance > height / 2.0 {
+                    return None;
+                }
+                let long_distance = distance((point.0, point.1), (center.0, point.1));
+                if long_distance > width / 2.0 {
+                    return None;
+                }
+                Some(distance(center, point))
+            }
+        }
+    }
+}
+
+pub fn validate(longitude: f64, latitude: f64) -> Result<(), Resp> {
+    if !(LONG_MIN..=LONG_MAX).contains(&longitude) || !(LAT_MIN..=LAT_MAX).contains(&latitude) {
+        return Err(Resp::SimpleError(format!(
+            "ERR invalid longitude,latitude pair {longitude:.6},{latitude:.6}"
+        )));
+    }
+    Ok(())
+}
+
+pub fn encode(longitude: f64, latitude: f64) -> u64 {
+    let scale = (1u64 << STEP) as f64;
+    let lat = ((latitude - LAT_MIN) / (LAT_MAX - LAT_MIN) * scale) as u64;
+    let long = ((longitude - LONG_MIN) / (LONG_MAX - LONG_MIN) * scale) as u64;
+    interleave(lat, long)
+}
+
+pub fn decode(hash: u64) -> (f64, f64) {
+    let (lat, long) = deinterleave(hash);
+    let scale = (1u64 << STEP) as f64;
+    let center = |cell: u64, min: f64, max: f64| {
+        let low = min + cell as f64 / scale * (max - min);
+        let high = min + (cell + 1) as f64 / scale * (max - min);
+        ((low + high) / 2.0).clamp(min, max)
+    };
+    (
+        center(long, LONG_MIN, LONG_MAX),
+        center(lat, LAT_MIN, LAT_MAX),
+    )
+}
+
+pub fn geohash_string(hash: u64) -> String {
+    let (longitude, latitude) = decode(hash);
+    let scale = (1u64 << STEP) as f64;
+    let lat = ((latitude + 90.0) / 180.0 * scale) as u64;
+    let long = ((longitude + 180.0) / 360.0 * scale) as u64;
+    let bits = interleave(lat, long);
+    (0..11)
+        .map(|i| {
+            let index = if i == 10 {
+                0
+            } else {
+                (bits >> (52 - (i + 1) * 5)) & 0x1f
+            };
+            ALPHABET[index as usize] as char
+        })
+        .collect()
+}
+
+pub fn distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (long1, lat1) = (from.0.to_radians(), from.1.to_radians());
+    let (long2, lat2) = (to.0.to_radians(), to.1.to_radians());
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let v = ((long2 - long1) / 2.0).sin();
+    2.0 * EARTH_RADIUS * (u * u + lat1.cos() * lat2.cos() * v * v).sqrt().asin()
+}
+
+pub fn format_distance(meters: f64, unit: Unit) -> String {
+    format!("{:.4}", meters / unit.to_meters())
+}
+
+pub fn format_coordinate(value: f64) -> String {
+    let digits = if value == 0.0 {
+        1
+    } else {
+        value.abs().log10().floor() as i32 + 1
+    };
+    let precision = (17 - digits).max(0) as usize;
+    let formatted = format!("{value:.precision$}");
+    if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+fn spread(value: u64) -> u64 {
+    let mut x = value & 0xffff_ffff;
+    x = (x | (x << 16)) & 0x0000_ffff_0000_ffff;
+    x = (x | (x << 8)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x << 2)) & 0x3333_3333_3333_3333;
+    (x | (x << 1)) & 0x5555_5555_5555_5555
+}
+
+fn squash(value: u64) -> u64 {
+    let mut x = value & 0x5555_5555_5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333_3333_3333;
+    x = (x | (x >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    x = (x | (x >> 4)) & 0x00ff_00ff_00ff_00ff;
+    x = (x | (x >> 8)) & 0x0000_ffff_0000_ffff;
+    (x | (x >> 16)) & 0xffff_ffff
+}
+
+fn interleave(lat: u64, long: u64) -> u64 {
+    spread(lat) | (spread(long) << 1)
+}
+
+fn deinterleave(hash: u64) -> (u64, u64) {
+    (squash(hash), squash(hash >> 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALERMO: (f64, f64) = (13.361389, 38.115556);
+    const CATANIA: (f64, f64) = (15.087269, 37.502669);
+
+    #[test]
+    fn encode_and_decode() {
+        let hash = encode(PALERMO.0, PALERMO.1);
+        assert_eq!(3479099956230698, hash);
+        let (longitude, latitude) = decode(hash);
+        assert!((longitude - PALERMO.0).abs() < 1e-5);
+        assert!((latitude - PALERMO.1).abs() < 1e-5);
+        assert_eq!("sqc8b49rny0", geohash_string(hash));
+        assert_eq!("sqdtr74hyu0", geohash_string(encode(CATANIA.0, CATANIA.1)));
+    }
+
+    #[test]
+    fn distances() {
+        let palermo = decode(encode(PALERMO.0, PALERMO.1));
+        let catania = decode(encode(CATANIA.0, CATANIA.1));
+        assert_eq!(
+            "166274.1516",
+            format_distance(distance(palermo, catania), Unit::Meters)
+        );
+        assert_eq!(
+            "166.2742",
+            format_distance(distance(palermo, catania), Unit::Kilometers)
+        );
+        let shape = Shape::Box {
+            width: 400_000.0,
+            height: 400_000.0,
+        };
+        assert!(shape.contains((15.0, 37.0), palermo).is_some());
+        assert!(Shape::Radius(100_000.0)
+            .contains((15.0, 37.0), palermo)
+            .is_none());
+    }
+
+    #[test]
+    fn coordinates() {
+        assert_eq!("13.361389338970184", format_coordinate(13.361389338970184));
+        assert_eq!("0", format_coordinate(0.0));
+        assert!(validate(0.0, 86.0).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use crate::resp::Resp;
+
+const P: u32 = 14;
+const Q: u32 = 64 - P;
+const REGISTERS: usize = 1 << P;
+const BITS: usize = 6;
+const HEADER_SIZE: usize = 16;
+const DENSE_SIZE: usize = HEADER_SIZE + (REGISTERS * BITS).div_ceil(8);
+const SPARSE_MAX_BYTES: usize = 3000;
+const SPARSE_MAX_VALUE: u8 = 32;
+const MAGIC: &[u8; 4] = b"HYLL";
+const DENSE: u8 = 0;
+const SPARSE: u8 = 1;
+const ALPHA_INF: f64 = 0.721_347_520_444_481_7;
+
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+    card: [u8; 8],
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; REGISTERS],
+            card: [0; 8],
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Resp> {
+        if bytes.len() < HEADER_SIZE || &bytes[..4] != MAGIC {
+            return Err(invalid());
+        }
+        let registers = match bytes[4] {
+            DENSE if bytes.len() == DENSE_SIZE => decode_dense(&bytes[HEADER_SIZE..]),
+            SPARSE => decode_sparse(&bytes[HEADER_SIZE..])?,
+            _ => return Err(invalid()),
+        };
+        let mut card = [0; 8];
+        card.copy_from_slice(&bytes[8..16]);
+        Ok(Self { registers, card })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sparse = encode_sparse(&self.registers);
+        let (encoding, payload) = match sparse {
+            Some(sparse) if HEADER_SIZE + sparse.len() <= SPARSE_MAX_BYTES => (SPARSE, sparse),
+            _ => (DENSE, encode_dense(&self.registers)),
+        };
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[encoding, 0, 0, 0]);
+        bytes.extend_from_slice(&self.card);
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    pub fn add(&mut self, element: &[u8]) -> bool {
+        let hash = murmurhash64a(element, 0xadc8_3b19);
+        let index = (hash & (REGISTERS as u64 - 1)) as usize;
+        let count = ((hash >> P) | (1 << Q)).trailing_zeros() as u8 + 1;
+        if self.registers[index] >= count {
+            return false;
+        }
+        self.registers[index] = count;
+        self.invalidate_cache();
+        true
+    }
+
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        let mut changed = false;
+        for (register, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if *other > *register {
+                *register = *other;
+                changed = true;
+            }
+        }
+        if changed {
+            self.invalidate_cache();
+        }
+    }
+
+    pub fn is_cached(&self) -> bool {
+        self.card[7] & 0x80 == 0
+    }
+
+    fn invalidate_cache(&mut self) {
+        self.card[7] |= 0x80;
+    }
+
+    pub fn count(&mut self) -> u64 {
+        if self.is_cached() {
+            return u64::from_le_bytes(self.card);
+        }
+        let mut histogram = [0u32; 64];
+        for register in self.registers.iter() {
+            histogram[*register as usize] += 1;
+        }
+        let m = REGISTERS as f64;
+        let mut z = m * tau((m - histogram[Q as usize + 1] as f64) / m);
+        for j in (1..=Q as usize).rev() {
+            z += histogram[j] as f64;
+            z *= 0.5;
+        }
+        z += m * sigma(histogram[0] as f64 / m);
+        let card = (ALPHA_INF * m * m / z).round() as u64;
+        self.card = card.to_le_bytes();
+        card
+    }
+}
+
+fn invalid() -> Resp {
+    Resp::SimpleError(String::from(
+        "WRONGTYPE Key is not a valid HyperLogLog string value.",
+    ))
+}
+
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+    let mut y = 1.0;
+    let mut z = x;
+    loop {
+        x *= x;
+        let previous = z;
+        z += x * y;
+        y += y;
+        if previous == z {
+            return z;
+        }
+    }
+}
+
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+    let mut y = 1.0;
+    let mut z = 1.0 - x;
+    loop {
+        x = x.sqrt();
+        let previous = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+        if previous == z {
+            return z / 3.0;
+        }
+    }
+}
+
+fn decode_dense(bytes: &[u8]) -> Vec<u8> {
+    (0..REGISTERS)
+        .map(|register| {
+            let byte = register * BITS / 8;
+            let shift = register * BITS % 8;
+            let low = bytes[byte] as u16;
+            let high = bytes.get(byte + 1).copied().unwrap_or(0) as u16;
+            (((high << 8 | low) >> shift) & 63) as u8
+        })
+        .collect()
+}
+
+fn encode_dense(registers: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0; DENSE_SIZE - HEADER_SIZE];
+    for (register, value) in registers.iter().enumerate() {
+        let byte = register * BITS / 8;
+        let shift = register * BITS % 8;
+        let value = (*value as u16) << shift;
+        bytes[byte] |= value as u8;
+        if let Some(next) = bytes.get_mut(byte + 1) {
+            *next |= (value >> 8) as u8;
+        }
+    }
+    bytes
+}
+
+fn decode_sparse(bytes: &[u8]) -> Result<Vec<u8>, Resp> {
+    let mut registers = Vec::with_capacity(REGISTERS);
+    let mut bytes = bytes.iter();
+    while let Some(opcode) = bytes.next() {
+        let (value, len) = match opcode >> 6 {
+            0 => (0, (opcode & 0x3f) as usize + 1),
+            1 => {
+                let low = *bytes.next().ok_or_else(invalid)? as usize;
+                (0, (((opcode & 0x3f) as usize) << 8 | low) + 1)
+            }
+            _ => (((opcode >> 2) & 0x1f) + 1, (opcode & 0x3) as usize + 1),
+        };
+        if registers.len() + len > REGISTERS {
+            return Err(invalid());
+        }
+        registers.extend(std::iter::repeat_n(value, len));
+    }
+    if registers.len() != REGISTERS {
+        return Err(invalid());
+    }
+    Ok(registers)
+}
+
+fn encode_sparse(registers: &[u8]) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut index = 0;
+    while index < registers.len() {
+        let value = registers[index];
+        if value > SPARSE_MAX_VALUE {
+            return None;
+        }
+        let run = registers[index..]
+            .iter()
+            .take_while(|register| **register == value)
+            .count();
+        index += run;
+        if value == 0 {
+            let mut run = run;
+            while run > 0 {
+                let len = run.min(16384);
+                if len > 64 {
+                    bytes.push(0x40 | ((len - 1) >> 8) as u8);
+                    bytes.push(((len - 1) & 0xff) as u8);
+                } else {
+                    bytes.push((len - 1) as u8);
+                }
+                run -= len;
+            }
+        } else {
+            let mut run = run;
+            while run > 0 {
+                let len = run.min(4);
+                bytes.push(0x80 | (value - 1) << 2 | (len - 1) as u8);
+                run -= len;
+            }
+        }
+    }
+    Some(bytes)
+}
+
+fn murmurhash64a(key: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+    let mut h = seed ^ (key.len() as u64).wrapping_mul(M);
+    let mut chunks = key.chunks_exact(8);
+    for chunk in chunks.by_ref() {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap_or_default());
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * i);
+        }
+        h = h.wrapping_mul(M);
+    }
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_sparse_encoding() {
+        let bytes = HyperLogLog::new().to_bytes();
+        assert_eq!(
+            b"HYLL\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x7f\xff",
+            &bytes[..]
+        );
+        assert_eq!(0, HyperLogLog::from_bytes(&bytes).unwrap().count());
+    }
+
+    #[test]
+    fn estimate_cardinality() {
+        let mut hll = HyperLogLog::new();
+        assert!(hll.add(b"a"));
+        assert!(!hll.add(b"a"));
+        assert_eq!(1, hll.count());
+        for i in 0..20000 {
+            hll.add(format!("element:{i}").as_bytes());
+        }
+        let count = hll.count() as f64;
+        assert!((count - 20001.0).abs() / 20001.0 < 0.02, "{count}");
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..100 {
+            hll.add(i.to_string().as_bytes());
+        }
+        let sparse = hll.to_bytes();
+        assert_eq!(SPARSE, sparse[4]);
+        let mut dense = MAGIC.to_vec();
+        dense.extend_from_slice(&[DENSE, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80]);
+        dense.extend_from_slice(&encode_dense(&hll.registers));
+        let mut from_dense = HyperLogLog::from_bytes(&dense).unwrap();
+        let mut from_sparse = HyperLogLog::from_bytes(&sparse).unwrap();
+        assert_eq!(hll.registers, from_dense.registers);
+        assert_eq!(hll.registers, from_sparse.registers);
+        assert_eq!(from_dense.count(), from_sparse.count());
+        assert!(HyperLogLog::from_bytes(b"HYLL").is_err());
+        assert!(HyperLogLog::from_bytes(b"not a hyperloglog").is_err());
+    }
+}
@@ -1,5 +1,20 @@
+pub mod bitops;
+pub mod client;
+pub mod cluster;
 pub mod command;
+pub mod config;
+pub mod dictionary;
+pub mod geo;
+pub mod hyperloglog;
+pub mod notify;
+pub mod pubsub;
+pub mod rdb;
 pub mod resp;
 pub mod server;
-pub mod dictionary;
-pub mod worker;
\ No newline at end of file
+pub mod session;
+pub mod slowlog;
+pub mod sorted_set;
+pub mod stream;
+pub mod tracking;
+pub mod value;
+pub mod worker;
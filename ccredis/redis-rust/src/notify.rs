@@ -0,0 +1,168 @@
+use std::{fmt::Display, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventClass {
+    Generic,
+    String,
+    List,
+    Set,
+    Hash,
+    SortedSet,
+    Expired,
+    Evicted,
+    Stream,
+    KeyMiss,
+    Module,
+    New,
+}
+
+impl EventClass {
+    const ALL: [(char, EventClass); 12] = [
+        ('g', EventClass::Generic),
+        ('$', EventClass::String),
+        ('l', EventClass::List),
+        ('s', EventClass::Set),
+        ('h', EventClass::Hash),
+        ('z', EventClass::SortedSet),
+        ('x', EventClass::Expired),
+        ('e', EventClass::Evicted),
+        ('t', EventClass::Stream),
+        ('m', EventClass::KeyMiss),
+        ('d', EventClass::Module),
+        ('n', EventClass::New),
+    ];
+
+    fn bit(self) -> u16 {
+        1 << (self as u16 + 2)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyspaceEvents(u16);
+
+impl KeyspaceEvents {
+    const KEYSPACE: u16 = 1;
+    const KEYEVENT: u16 = 2;
+    const ALIAS: &'static str = "g$lshzxetd";
+
+    pub fn keyspace(&self) -> bool {
+        self.0 & Self::KEYSPACE != 0
+    }
+
+    pub fn keyevent(&self) -> bool {
+        self.0 & Self::KEYEVENT != 0
+    }
+
+    pub fn is_enabled(&self, class: EventClass) -> bool {
+        (self.keyspace() || self.keyevent()) && self.0 & class.bit() != 0
+    }
+}
+
+impl FromStr for KeyspaceEvents {
+    type Err = char;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut flags = 0;
+        for c in s.chars() {
+            match c {
+                'K' => flags |= Self::KEYSPACE,
+                'E' => flags |= Self::KEYEVENT,
+                'A' => flags |= Self::ALIAS.parse::<KeyspaceEvents>()?.0,
+                c => match EventClass::ALL.iter().find(|(flag, _)| *flag == c) {
+                    Some((_, class)) => flags |= class.bit(),
+                    None => return Err(c),
+                },
+            }
+        }
+        Ok(KeyspaceEvents(flags))
+    }
+}
+
+impl Display for KeyspaceEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut flags = String::new();
+        let alias = Self::ALIAS.parse::<KeyspaceEvents>().unwrap_or_default();
+        if self.0 & alias.0 == alias.0 {
+            flags.push('A');
+        }
+        for (flag, class) in EventClass::ALL {
+            if self.0 & class.bit() != 0 && (!flags.starts_with('A') || alias.0 & class.bit() == 0)
+            {
+                flags.push(flag);
+            }
+        }
+        if self.keyspace() {
+            flags.push('K');
+        }
+        if self.keyevent() {
+            flags.push('E');
+        }
+        write!(f, "{flags}")
+    }
+}
+
+pub struct Notification {
+    pub class: EventClass,
+    pub event: &'static str,
+    pub key: String,
+    pub db: usize,
+}
+
+impl Notification {
+    pub fn new(class: EventClass, event: &'static str, key: String, db: usize) -> Self {
+        Self {
+            class,
+            event,
+            key,
+            db,
+        }
+    }
+
+    pub fn messages(&self, events: KeyspaceEvents) -> Vec<(String, String)> {
+        let mut messages = Vec::new();
+        if !events.is_enabled(self.class) {
+            return messages;
+        }
+        if events.keyspace() {
+            let channel = format!("__keyspace@{}__:{}", self.db, self.key);
+            messages.push((channel, self.event.to_string()));
+        }
+        if events.keyevent() {
+            let channel = format!("__keyevent@{}__:{}", self.db, self.event);
+            messages.push((channel, self.key.clone()));
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_flags() {
+        let events: KeyspaceEvents = "Ex".parse().unwrap();
+        assert!(events.keyevent());
+        assert!(!events.keyspace());
+        assert!(events.is_enabled(EventClass::Expired));
+        assert!(!events.is_enabled(EventClass::Generic));
+        assert_eq!("xE", events.to_string());
+        assert_eq!("AKE", "KEA".parse::<KeyspaceEvents>().unwrap().to_string());
+        assert_eq!(Err('q'), "Kq".parse::<KeyspaceEvents>());
+    }
+
+    #[test]
+    fn notification_channels() {
+        let events: KeyspaceEvents = "KE$".parse().unwrap();
+        let notification = Notification::new(EventClass::String, "set", String::from("key"), 3);
+        assert_eq!(
+            vec![
+                (String::from("__keyspace@3__:key"), String::from("set")),
+                (String::from("__keyevent@3__:set"), String::from("key")),
+            ],
+            notification.messages(events)
+        );
+        let events: KeyspaceEvents = "Kg".parse().unwrap();
+        assert!(notification.messages(events).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+};
+
+use crate::{config::glob_match, resp::Resp};
+
+#[derive(Default)]
+pub struct PubSub {
+    channels: HashMap<String, HashSet<SocketAddr>>,
+    patterns: HashMap<String, HashSet<SocketAddr>>,
+}
+
+impl PubSub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe(&mut self, client: SocketAddr, channels: Vec<String>) -> Vec<Resp> {
+        let mut replies = Vec::new();
+        for channel in channels {
+            self.channels
+                .entry(channel.clone())
+                .or_default()
+                .insert(client);
+            replies.push(self.confirmation("subscribe", Some(channel), client));
+        }
+        replies
+    }
+
+    pub fn psubscribe(&mut self, client: SocketAddr, patterns: Vec<String>) -> Vec<Resp> {
+        let mut replies = Vec::new();
+        for pattern in patterns {
+            self.patterns
+                .entry(pattern.clone())
+                .or_default()
+                .insert(client);
+            replies.push(self.confirmation("psubscribe", Some(pattern), client));
+        }
+        replies
+    }
+
+    pub fn unsubscribe(&mut self, client: SocketAddr, channels: Vec<String>) -> Vec<Resp> {
+        let channels = if channels.is_empty() {
+            subscriptions(&self.channels, client)
+        } else {
+            channels
+        };
+        if channels.is_empty() {
+            return vec![self.confirmation("unsubscribe", None, client)];
+        }
+        let mut replies = Vec::new();
+        for channel in channels {
+            remove(&mut self.channels, &channel, client);
+            replies.push(self.confirmation("unsubscribe", Some(channel), client));
+        }
+        replies
+    }
+
+    pub fn punsubscribe(&mut self, client: SocketAddr, patterns: Vec<String>) -> Vec<Resp> {
+        let patterns = if patterns.is_empty() {
+            subscriptions(&self.patterns, client)
+        } else {
+            patterns
+        };
+        if patterns.is_empty() {
+            return vec![self.confirmation("punsubscribe", None, client)];
+        }
+        let mut replies = Vec::new();
+        for pattern in patterns {
+            remove(&mut self.patterns, &pattern, client);
+            replies.push(self.confirmation("punsubscribe", Some(pattern), client));
+        }
+        replies
+    }
+
+    pub fn publish(
+        &self,
+        channel: &str,
+        message: &str,
+        resp3: &HashSet<SocketAddr>,
+    ) -> Vec<(SocketAddr, Resp)> {
+        let frame = |client: &SocketAddr, items| {
+            if resp3.contains(client) {
+                Resp::Push(items)
+            } else {
+                Resp::Array(items)
+            }
+        };
+        let mut messages = Vec::new();
+        if let Some(clients) = self.channels.get(channel) {
+            for client in clients {
+                let message = frame(
+                    client,
+                    vec![
+                        Resp::BulkString("message".into()),
+                        Resp::BulkString(channel.into()),
+                        Resp::BulkString(message.into()),
+                    ],
+                );
+                messages.push((*client, message));
+            }
+        }
+        for (pattern, clients) in self.patterns.iter() {
+            if !glob_match(pattern, channel) {
+                continue;
+            }
+            for client in clients {
+                let message = frame(
+                    client,
+                    vec![
+                        Resp::BulkString("pmessage".into()),
+                        Resp::BulkString(pattern.clone().into()),
+                        Resp::BulkString(channel.into()),
+                        Resp::BulkString(message.into()),
+                    ],
+                );
+                messages.push((*client, message));
+            }
+        }
+        messages
+    }
+
+    pub fn is_subscribed(&self, client: SocketAddr, channel: &str) -> bool {
+        self.channels
+            .get(channel)
+            .is_some_and(|clients| clients.contains(&client))
+    }
+
+    pub fn disconnect(&mut self, client: &SocketAddr) {
+        for channel in subscriptions(&self.channels, *client) {
+            remove(&mut self.channels, &channel, *client);
+        }
+        for pattern in subscriptions(&self.patterns, *client) {
+            remove(&mut self.patterns, &pattern, *client);
+        }
+    }
+
+    fn count(&self, client: SocketAddr) -> usize {
+        subscriptions(&self.channels, client).len() + subscriptions(&self.patterns, client).len()
+    }
+
+    fn confirmation(&self, kind: &str, name: Option<String>, client: SocketAddr) -> Resp {
+        Resp::Array(vec![
+            Resp::BulkString(kind.into()),
+            name.map(|s| Resp::BulkString(s.into()))
+                .unwrap_or(Resp::Null),
+            Resp::Integer(self.count(client) as i64),
+        ])
+    }
+}
+
+fn subscriptions(map: &HashMap<String, HashSet<SocketAddr>>, client: SocketAddr) -> Vec<String> {
+    map.iter()
+        .filter(|(_, clients)| clients.contains(&client))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+fn remove(map: &mut HashMap<String, HashSet<SocketAddr>>, name: &str, client: SocketAddr) {
+    if let Some(clients) = map.get_mut(name) {
+        clients.remove(&client);
+        if clients.is_empty() {
+            map.remove(name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn publish_to_channels_and_patterns() {
+        let first = SocketAddr::from(([127, 0, 0, 1], 1));
+        let second = SocketAddr::from(([127, 0, 0, 1], 2));
+        let mut pubsub = PubSub::new();
+        pubsub.subscribe(first, vec![String::from("news")]);
+        let replies = pubsub.psubscribe(second, vec![String::from("n*")]);
+        assert_eq!(
+            vec![Resp::Array(vec![
+                Resp::BulkString("psubscribe".into()),
+                Resp::BulkString("n*".into()),
+                Resp::Integer(1),
+            ])],
+            replies
+        );
+
+        let messages = pubsub.publish("news", "hello", &HashSet::new());
+        assert_eq!(2, messages.len());
+        assert!(messages.iter().any(|(client, _)| *client == first));
+        assert!(messages.iter().any(|(client, _)| *client == second));
+
+        let messages = pubsub.publish("news", "hello", &HashSet::from([first]));
+        let message = |client| {
+            messages
+                .iter()
+                .find(|(address, _)| *address == client)
+                .map(|(_, message)| message.clone())
+        };
+        let items = vec![
+            Resp::BulkString("message".into()),
+            Resp::BulkString("news".into()),
+            Resp::BulkString("hello".into()),
+        ];
+        assert_eq!(Some(Resp::Push(items)), message(first));
+        assert!(matches!(message(second), Some(Resp::Array(items)) if items.len() == 4));
+
+        pubsub.unsubscribe(first, Vec::new());
+        pubsub.disconnect(&second);
+        assert!(pubsub.publish("news", "hello", &HashSet::new()).is_empty());
+    }
+}
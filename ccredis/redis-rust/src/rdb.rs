@@ -0,0 +1,644 @@
+use std::collections::VecDeque;
+
+use crate::{resp::Resp, sorted_set::SortedSet, stream::Stream, value::Value};
+
+pub const RDB_VERSION: u16 = 11;
+
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_ZSET: u8 = 3;
+const TYPE_ZSET_2: u8 = 5;
+const TYPE_STREAM_LISTPACKS: u8 = 15;
+const TYPE_ZSET_LISTPACK: u8 = 17;
+const TYPE_LIST_QUICKLIST_2: u8 = 18;
+const TYPE_STREAM_LISTPACKS_2: u8 = 19;
+const TYPE_STREAM_LISTPACKS_3: u8 = 21;
+
+const ENC_INT8: u8 = 0;
+const ENC_INT16: u8 = 1;
+const ENC_INT32: u8 = 2;
+const ENC_LZF: u8 = 3;
+
+const QUICKLIST_NODE_PLAIN: u64 = 1;
+const QUICKLIST_NODE_PACKED: u64 = 2;
+
+const CRC64_TABLE: [u64; 256] = crc64_table();
+
+pub fn dump(value: &Value) -> Vec<u8> {
+    let mut writer = Writer::new();
+    match value {
+        Value::String(bytes) => {
+            writer.byte(TYPE_STRING);
+            writer.string(bytes);
+        }
+        Value::List(list) => {
+            writer.byte(TYPE_LIST_QUICKLIST_2);
+            writer.length(1);
+            writer.length(QUICKLIST_NODE_PACKED);
+            let mut listpack = Listpack::new();
+            for element in list {
+                listpack.string(element);
+            }
+            writer.string(&listpack.into_bytes());
+        }
+        Value::SortedSet(set) => {
+            writer.byte(TYPE_ZSET_2);
+            writer.length(set.len() as u64);
+            for (member, score) in set.iter() {
+                writer.string(member.as_bytes());
+                writer.raw(&score.to_le_bytes());
+            }
+        }
+        Value::Stream(stream) => {
+            writer.byte(TYPE_STREAM_LISTPACKS_3);
+            stream.save(&mut writer);
+        }
+    }
+    writer.raw(&RDB_VERSION.to_le_bytes());
+    let crc = crc64(0, &writer.bytes);
+    writer.raw(&crc.to_le_bytes());
+    writer.bytes
+}
+
+pub fn restore(payload: &[u8]) -> Result<Value, Resp> {
+    let wrong_payload = || {
+        Resp::SimpleError(String::from(
+            "ERR DUMP payload version or checksum are wrong",
+        ))
+    };
+    if payload.len() < 10 {
+        return Err(wrong_payload());
+    }
+    let (body, footer) = payload.split_at(payload.len() - 10);
+    let version = u16::from_le_bytes([footer[0], footer[1]]);
+    let mut crc = [0; 8];
+    crc.copy_from_slice(&footer[2..]);
+    if version > RDB_VERSION || crc64(0, &payload[..payload.len() - 8]) != u64::from_le_bytes(crc) {
+        return Err(wrong_payload());
+    }
+    let mut reader = Reader::new(body);
+    let kind = reader.byte()?;
+    let value = match kind {
+        TYPE_STRING => Value::String(reader.string()?),
+        TYPE_LIST => {
+            let mut list = VecDeque::new();
+            for _ in 0..reader.length()? {
+                list.push_back(reader.string()?);
+            }
+            Value::List(list)
+        }
+        TYPE_LIST_QUICKLIST_2 => {
+            let mut list = VecDeque::new();
+            for _ in 0..reader.length()? {
+                match reader.length()? {
+                    QUICKLIST_NODE_PLAIN => list.push_back(reader.string()?),
+                    QUICKLIST_NODE_PACKED => {
+                        let entries = parse_listpack(&reader.string()?)?;
+                        list.extend(entries.into_iter().map(ListpackEntry::into_bytes));
+                    }
+                    _ => return Err(bad_format()),
+                }
+            }
+            Value::List(list)
+        }
+        TYPE_ZSET | TYPE_ZSET_2 => {
+            let mut set = SortedSet::new();
+            for _ in 0..reader.length()? {
+                let member = String::from_utf8_lossy(&reader.string()?).into_owned();
+                let score = if kind == TYPE_ZSET {
+                    reader.ascii_double()?
+                } else {
+                    f64::from_le_bytes(reader.array()?)
+                };
+                set.insert(member, score);
+            }
+            Value::SortedSet(set)
+        }
+        TYPE_ZSET_LISTPACK => {
+            let mut set = SortedSet::new();
+            let mut entries = parse_listpack(&reader.string()?)?.into_iter();
+            while let Some(member) = entries.next() {
+                let member = String::from_utf8_lossy(&member.into_bytes()).into_owned();
+                let score = entries.next().ok_or_else(bad_format)?.float()?;
+                set.insert(member, score);
+            }
+            Value::SortedSet(set)
+        }
+        TYPE_STREAM_LISTPACKS => Value::Stream(Stream::load(&mut reader, 1)?),
+        TYPE_STREAM_LISTPACKS_2 => Value::Stream(Stream::load(&mut reader, 2)?),
+        TYPE_STREAM_LISTPACKS_3 => Value::Stream(Stream::load(&mut reader, 3)?),
+        _ => return Err(bad_format()),
+    };
+    let empty = match &value {
+        Value::List(list) => list.is_empty(),
+        Value::SortedSet(set) => set.is_empty(),
+        _ => false,
+    };
+    if empty || !reader.is_empty() {
+        return Err(bad_format());
+    }
+    Ok(value)
+}
+
+pub fn bad_format() -> Resp {
+    Resp::SimpleError(String::from("ERR Bad data format"))
+}
+
+pub fn crc64(crc: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(crc, |crc, byte| {
+        CRC64_TABLE[((crc ^ *byte as u64) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+const fn crc64_table() -> [u64; 256] {
+    const POLY: u64 = 0x95ac_9329_ac4b_c9b5;
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[derive(Default)]
+pub struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn byte(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    pub fn raw(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn length(&mut self, length: u64) {
+        if length < 1 << 6 {
+            self.byte(length as u8);
+        } else if length < 1 << 14 {
+            self.raw(&[0x40 | (length >> 8) as u8, length as u8]);
+        } else if length <= u32::MAX as u64 {
+            self.byte(0x80);
+            self.raw(&(length as u32).to_be_bytes());
+        } else {
+            self.byte(0x81);
+            self.raw(&length.to_be_bytes());
+        }
+    }
+
+    pub fn string(&mut self, bytes: &[u8]) {
+        let integer = std::str::from_utf8(bytes)
+            .ok()
+            .filter(|s| s.len() <= 11)
+            .and_then(|s| s.parse::<i64>().ok().filter(|i| i.to_string() == s));
+        match integer {
+            Some(i) if i8::try_from(i).is_ok() => self.raw(&[0xc0 | ENC_INT8, i as u8]),
+            Some(i) if i16::try_from(i).is_ok() => {
+                self.byte(0xc0 | ENC_INT16);
+                self.raw(&(i as i16).to_le_bytes());
+            }
+            Some(i) if i32::try_from(i).is_ok() => {
+                self.byte(0xc0 | ENC_INT32);
+                self.raw(&(i as i32).to_le_bytes());
+            }
+            _ => {
+                self.length(bytes.len() as u64);
+                self.raw(bytes);
+            }
+        }
+    }
+
+    pub fn millis(&mut self, millis: u64) {
+        self.raw(&millis.to_le_bytes());
+    }
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn byte(&mut self) -> Result<u8, Resp> {
+        Ok(self.raw(1)?[0])
+    }
+
+    pub fn raw(&mut self, len: usize) -> Result<&'a [u8], Resp> {
+        if self.bytes.len() < len {
+            return Err(bad_format());
+        }
+        let (raw, remaining) = self.bytes.split_at(len);
+        self.bytes = remaining;
+        Ok(raw)
+    }
+
+    pub fn array<const N: usize>(&mut self) -> Result<[u8; N], Resp> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.raw(N)?);
+        Ok(array)
+    }
+
+    fn length_or_encoding(&mut self) -> Result<(u64, bool), Resp> {
+        let first = self.byte()?;
+        match first >> 6 {
+            0 => Ok(((first & 0x3f) as u64, false)),
+            1 => Ok(((((first & 0x3f) as u64) << 8) | self.byte()? as u64, false)),
+            2 if first == 0x80 => Ok((u32::from_be_bytes(self.array()?) as u64, false)),
+            2 if first == 0x81 => Ok((u64::from_be_bytes(self.array()?), false)),
+            3 => Ok(((first & 0x3f) as u64, true)),
+            _ => Err(bad_format()),
+        }
+    }
+
+    pub fn length(&mut self) -> Result<u64, Resp> {
+        match self.length_or_encoding()? {
+            (length, false) => Ok(length),
+            (_, true) => Err(bad_format()),
+        }
+    }
+
+    pub fn string(&mut self) -> Result<Vec<u8>, Resp> {
+        let (length, encoded) = self.length_or_encoding()?;
+        if !encoded {
+            let length = usize::try_from(length).map_err(|_| bad_format())?;
+            return Ok(self.raw(length)?.to_vec());
+        }
+        let integer = match length as u8 {
+            ENC_INT8 => self.byte()? as i8 as i64,
+            ENC_INT16 => i16::from_le_bytes(self.array()?) as i64,
+            ENC_INT32 => i32::from_le_bytes(self.array()?) as i64,
+            ENC_LZF => {
+                let compressed = usize::try_from(self.length()?).map_err(|_| bad_format())?;
+                let length = usize::try_from(self.length()?).map_err(|_| bad_format())?;
+                return lzf_decompress(self.raw(compressed)?, length);
+            }
+            _ => return Err(bad_format()),
+        };
+        Ok(integer.to_string().into_bytes())
+    }
+
+    pub fn millis(&mut self) -> Result<u64, Resp> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn ascii_double(&mut self) -> Result<f64, Resp> {
+        match self.byte()? {
+            253 => Ok(f64::NAN),
+            254 => Ok(f64::INFINITY),
+            255 => Ok(f64::NEG_INFINITY),
+            len => std::str::from_utf8(self.raw(len as usize)?)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(bad_format),
+        }
+    }
+}
+
+fn lzf_decompress(input: &[u8], length: usize) -> Result<Vec<u8>, Resp> {
+    let mut output: Vec<u8> = Vec::with_capacity(length.min(input.len() * 64));
+    let mut input = input.iter().copied();
+    while let Some(control) = input.next() {
+        if control < 32 {
+            for _ in 0..=control {
+                output.push(input.next().ok_or_else(bad_format)?);
+            }
+        } else {
+            let mut len = (control >> 5) as usize;
+            if len == 7 {
+                len += input.next().ok_or_else(bad_format)? as usize;
+            }
+            let offset =
+                ((control as usize & 0x1f) << 8) + input.next().ok_or_else(bad_format)? as usize;
+            let start = output
+                .len()
+                .checked_sub(offset + 1)
+                .ok_or_else(bad_format)?;
+            for i in 0..len + 2 {
+                output.push(output[start + i]);
+            }
+        }
+        if output.len() > length {
+            return Err(bad_format());
+        }
+    }
+    if output.len() != length {
+        return Err(bad_format());
+    }
+    Ok(output)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListpackEntry {
+    Integer(i64),
+    String(Vec<u8>),
+}
+
+impl ListpackEntry {
+    pub fn integer(&self) -> Result<i64, Resp> {
+        match self {
+            ListpackEntry::Integer(i) => Ok(*i),
+            ListpackEntry::String(s) => std::str::from_utf8(s)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(bad_format),
+        }
+    }
+
+    fn float(&self) -> Result<f64, Resp> {
+        match self {
+            ListpackEntry::Integer(i) => Ok(*i as f64),
+            ListpackEntry::String(s) => std::str::from_utf8(s)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(bad_format),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            ListpackEntry::Integer(i) => i.to_string().into_bytes(),
+            ListpackEntry::String(s) => s,
+        }
+    }
+}
+
+pub struct Listpack {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Default for Listpack {
+    fn default() -> Self {
+        Self {
+            bytes: vec![0; 6],
+            len: 0,
+        }
+    }
+}
+
+impl Listpack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn integer(&mut self, i: i64) {
+        let start = self.bytes.len();
+        match i {
+            0..=127 => self.bytes.push(i as u8),
+            -4096..=4095 => {
+                let unsigned = (i as u64) & 0x1fff;
+                self.bytes.push(0xc0 | (unsigned >> 8) as u8);
+                self.bytes.push(unsigned as u8);
+            }
+            _ if i16::try_from(i).is_ok() => {
+                self.bytes.push(0xf1);
+                self.bytes.extend_from_slice(&(i as i16).to_le_bytes());
+            }
+            -8_388_608..=8_388_607 => {
+                self.bytes.push(0xf2);
+                self.bytes.extend_from_slice(&(i as i32).to_le_bytes()[..3]);
+            }
+            _ if i32::try_from(i).is_ok() => {
+                self.bytes.push(0xf3);
+                self.bytes.extend_from_slice(&(i as i32).to_le_bytes());
+            }
+            _ => {
+                self.bytes.push(0xf4);
+                self.bytes.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+        self.push_backlen(start);
+    }
+
+    pub fn string(&mut self, s: &[u8]) {
+        let start = self.bytes.len();
+        if s.len() < 64 {
+            self.bytes.push(0x80 | s.len() as u8);
+        } else if s.len() < 4096 {
+            self.bytes.push(0xe0 | (s.len() >> 8) as u8);
+            self.bytes.push(s.len() as u8);
+        } else {
+            self.bytes.push(0xf0);
+            self.bytes
+                .extend_from_slice(&(s.len() as u32).to_le_bytes());
+        }
+        self.bytes.extend_from_slice(s);
+        self.push_backlen(start);
+    }
+
+    fn push_backlen(&mut self, start: usize) {
+        let len = self.bytes.len() - start;
+        let backlen = encode_backlen(len);
+        self.bytes.extend_from_slice(&backlen);
+        self.len += 1;
+    }
+
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.bytes.push(0xff);
+        let total = self.bytes.len() as u32;
+        self.bytes[..4].copy_from_slice(&total.to_le_bytes());
+        let len = u16::try_from(self.len).unwrap_or(u16::MAX);
+        self.bytes[4..6].copy_from_slice(&len.to_le_bytes());
+        self.bytes
+    }
+}
+
+fn encode_backlen(len: usize) -> Vec<u8> {
+    let groups = match len {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2_097_150 => 3,
+        2_097_151..=268_435_454 => 4,
+        _ => 5,
+    };
+    (0..groups)
+        .rev()
+        .enumerate()
+        .map(|(i, shift)| {
+            let byte = ((len >> (7 * shift)) & 127) as u8;
+            if i == 0 {
+                byte
+            } else {
+                byte | 128
+            }
+        })
+        .collect()
+}
+
+pub fn parse_listpack(bytes: &[u8]) -> Result<Vec<ListpackEntry>, Resp> {
+    if bytes.len() < 7 || bytes[bytes.len() - 1] != 0xff {
+        return Err(bad_format());
+    }
+    let total = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    if total != bytes.len() {
+        return Err(bad_format());
+    }
+    let mut reader = Reader::new(&bytes[6..bytes.len() - 1]);
+    let mut entries = Vec::new();
+    while !reader.is_empty() {
+        let start = reader.bytes.len();
+        let encoding = reader.byte()?;
+        let entry = match encoding {
+            0x00..=0x7f => ListpackEntry::Integer(encoding as i64),
+            0x80..=0xbf => ListpackEntry::String(reader.raw((encoding & 0x3f) as usize)?.to_vec()),
+            0xc0..=0xdf => {
+                let unsigned = (((encoding & 0x1f) as u64) << 8) | reader.byte()? as u64;
+                ListpackEntry::Integer(((unsigned << 51) as i64) >> 51)
+            }
+            0xe0..=0xef => {
+                let len = (((encoding & 0x0f) as usize) << 8) | reader.byte()? as usize;
+                ListpackEntry::String(reader.raw(len)?.to_vec())
+            }
+            0xf0 => {
+                let len = u32::from_le_bytes(reader.array()?) as usize;
+                ListpackEntry::String(reader.raw(len)?.to_vec())
+            }
+            0xf1 => ListpackEntry::Integer(i16::from_le_bytes(reader.array()?) as i64),
+            0xf2 => {
+                let [a, b, c] = reader.array()?;
+                ListpackEntry::Integer((i32::from_le_bytes([0, a, b, c]) >> 8) as i64)
+            }
+            0xf3 => ListpackEntry::Integer(i32::from_le_bytes(reader.array()?) as i64),
+            0xf4 => ListpackEntry::Integer(i64::from_le_bytes(reader.array()?)),
+            _ => return Err(bad_format()),
+        };
+        let len = start - reader.bytes.len();
+        reader.raw(encode_backlen(len).len())?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksum() {
+        assert_eq!(0xe9c6_d914_c4b8_d9ca, crc64(0, b"123456789"));
+        let payload = b"\x00\xc0\n\t\x00\xbem\x06\x89Z(\x00\n";
+        assert_eq!(
+            u64::from_le_bytes(payload[5..].try_into().unwrap()),
+            crc64(0, &payload[..5])
+        );
+    }
+
+    #[test]
+    fn dump_and_restore_strings() {
+        let dump = super::dump(&Value::String(b"10".to_vec()));
+        assert_eq!(b"\x00\xc0\n\x0b\x00", &dump[..5]);
+        for value in [&b"10"[..], b"-70000", b"hello", b"007", &[0xff; 100]] {
+            let dump = super::dump(&Value::String(value.to_vec()));
+            match restore(&dump) {
+                Ok(Value::String(restored)) => assert_eq!(value, restored),
+                _ => panic!("failed to restore {value:?}"),
+            }
+        }
+        let mut corrupted = super::dump(&Value::String(b"hello".to_vec()));
+        corrupted[2] = b'j';
+        assert!(restore(&corrupted).is_err());
+        assert!(restore(b"short").is_err());
+    }
+
+    #[test]
+    fn dump_and_restore_lists() {
+        let list = VecDeque::from([b"c".to_vec(), b"12".to_vec(), vec![0xff; 100]]);
+        match restore(&super::dump(&Value::List(list.clone()))) {
+            Ok(Value::List(restored)) => assert_eq!(list, restored),
+            _ => panic!("failed to restore a list"),
+        }
+
+        let mut payload = vec![TYPE_LIST, 2, 1, b'a', 0xc0 | ENC_INT8, 7];
+        payload.extend_from_slice(&RDB_VERSION.to_le_bytes());
+        let crc = crc64(0, &payload);
+        payload.extend_from_slice(&crc.to_le_bytes());
+        match restore(&payload) {
+            Ok(Value::List(restored)) => {
+                assert_eq!(VecDeque::from([b"a".to_vec(), b"7".to_vec()]), restored)
+            }
+            _ => panic!("failed to restore a plain list"),
+        }
+    }
+
+    #[test]
+    fn restore_redis_payloads() {
+        let mut payload = b"\x00\xc3\x05\x40\x78\x00a\xe0\x6e\x00\x0b\x00".to_vec();
+        let crc = crc64(0, &payload);
+        payload.extend_from_slice(&crc.to_le_bytes());
+        match restore(&payload) {
+            Ok(Value::String(restored)) => assert_eq!(vec![b'a'; 120], restored),
+            _ => panic!("failed to restore an LZF compressed string"),
+        }
+
+        let mut listpack = Listpack::new();
+        listpack.string(b"a");
+        listpack.integer(1);
+        listpack.string(b"b");
+        listpack.string(b"2.5");
+        let mut payload = vec![TYPE_ZSET_LISTPACK];
+        let mut writer = Writer::new();
+        writer.string(&listpack.into_bytes());
+        payload.extend_from_slice(&writer.bytes);
+        payload.extend_from_slice(&RDB_VERSION.to_le_bytes());
+        let crc = crc64(0, &payload);
+        payload.extend_from_slice(&crc.to_le_bytes());
+        match restore(&payload) {
+            Ok(Value::SortedSet(set)) => {
+                assert_eq!(Some(1.0), set.score("a"));
+                assert_eq!(Some(2.5), set.score("b"));
+            }
+            _ => panic!("failed to restore a listpack sorted set"),
+        }
+    }
+
+    #[test]
+    fn listpack_round_trip() {
+        let integers = [
+            0, 127, 128, -1, -4096, 4095, 4096, -32768, 8_000_000, -8_388_608,
+        ];
+        let integers = integers
+            .into_iter()
+            .chain([i32::MIN as i64, i64::MAX, i64::MIN]);
+        let mut listpack = Listpack::new();
+        let mut want = Vec::new();
+        for i in integers {
+            listpack.integer(i);
+            want.push(ListpackEntry::Integer(i));
+        }
+        for len in [0, 63, 64, 4095, 4096, 20000] {
+            listpack.string(&vec![b'x'; len]);
+            want.push(ListpackEntry::String(vec![b'x'; len]));
+        }
+        let bytes = listpack.into_bytes();
+        assert_eq!(
+            want.len(),
+            u16::from_le_bytes([bytes[4], bytes[5]]) as usize
+        );
+        assert_eq!(want, parse_listpack(&bytes).unwrap());
+        assert!(parse_listpack(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -1,13 +1,15 @@
 use std::fmt::Display;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Resp {
     SimpleString(String),
     SimpleError(String),
     Integer(i64),
-    BulkString(String),
+    BulkString(Vec<u8>),
     Array(Vec<Resp>),
     Null,
+    Map(Vec<(Resp, Resp)>),
+    Push(Vec<Resp>),
 }
 
 impl Resp {
@@ -18,9 +20,63 @@ impl Resp {
         Resp::SimpleError(format!("Unkown command '{command}'"))
     }
 
+    pub fn unkown_subcommand(subcommand: &str) -> Resp {
+        Resp::SimpleError(format!("ERR unknown subcommand '{subcommand}'"))
+    }
+
+    pub fn not_an_integer() -> Resp {
+        Resp::SimpleError(String::from("ERR value is not an integer or out of range"))
+    }
+
+    pub fn invalid_utf8() -> Resp {
+        Resp::SimpleError(String::from(
+            "ERR keys and string arguments must be valid UTF-8",
+        ))
+    }
+
+    pub fn not_a_float() -> Resp {
+        Resp::SimpleError(String::from("ERR value is not a valid float"))
+    }
+
+    pub fn db_index_out_of_range() -> Resp {
+        Resp::SimpleError(String::from("ERR DB index is out of range"))
+    }
+
+    pub fn wrong_type() -> Resp {
+        Resp::SimpleError(String::from(
+            "WRONGTYPE Operation against a key holding the wrong kind of value",
+        ))
+    }
+
+    pub fn invalid_stream_id() -> Resp {
+        Resp::SimpleError(String::from(
+            "ERR Invalid stream ID specified as stream command argument",
+        ))
+    }
+
+    pub fn stream_id_too_small() -> Resp {
+        Resp::SimpleError(String::from(
+            "ERR The ID specified in XADD is equal or smaller than the target stream top item",
+        ))
+    }
+
+    pub fn no_group(group: &str) -> Resp {
+        Resp::SimpleError(format!("NOGROUP No such key or consumer group '{group}'"))
+    }
+
+    pub fn syntax_error() -> Resp {
+        Resp::SimpleError(String::from("ERR syntax error"))
+    }
+
     pub fn wrong_number_of_arguments() -> Resp {
         Resp::SimpleError(String::from("ERR wrong number of arguments for command"))
     }
+    pub fn wrong_arity(command: &str) -> Resp {
+        Resp::SimpleError(format!(
+            "ERR wrong number of arguments for '{command}' command"
+        ))
+    }
+
     pub fn invalid_arguments() -> Resp {
         Resp::SimpleError(String::from("ERR wrong number of arguments for command"))
     }
@@ -28,23 +84,90 @@ impl Resp {
     pub fn parse(bytes: &[u8]) -> Result<Vec<Resp>, Resp> {
         let mut remaining = bytes;
         let mut resps = Vec::new();
-        loop {
-            match parse_resp(remaining) {
-                (Some(resp), r) => {
+        while !remaining.is_empty() {
+            match parse_value(remaining, 0) {
+                Ok((resp, r)) => {
                     remaining = r;
                     resps.push(resp);
                 }
-                (None, r) => {
-                    if r.is_empty() {
-                        return Ok(resps);
-                    }
+                Err(ParseError::Invalid(message)) => {
+                    return Err(Resp::SimpleError(format!("ERR Protocol error: {message}")));
+                }
+                Err(ParseError::Incomplete) if remaining.iter().all(u8::is_ascii_whitespace) => {
+                    break;
+                }
+                Err(ParseError::Incomplete) => {
                     return Err(Resp::unkown_command(&String::from_utf8_lossy(bytes)));
                 }
             }
         }
+        Ok(resps)
+    }
+
+    pub fn parse_one(bytes: &[u8]) -> Result<Option<(Resp, &[u8])>, Resp> {
+        match parse_value(bytes, 0) {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(ParseError::Incomplete) => Ok(None),
+            Err(ParseError::Invalid(message)) => {
+                Err(Resp::SimpleError(format!("ERR Protocol error: {message}")))
+            }
+        }
+    }
+}
+
+pub fn split_args(line: &[u8]) -> Option<Vec<Vec<u8>>> {
+    let mut args = Vec::new();
+    let mut bytes = line.iter().copied().peekable();
+    loop {
+        while bytes.next_if(|b| b.is_ascii_whitespace()).is_some() {}
+        if bytes.peek().is_none() {
+            return Some(args);
+        }
+        let mut arg = Vec::new();
+        let mut quote = None;
+        loop {
+            let byte = bytes.next();
+            match (quote, byte) {
+                (Some(_), None) => return None,
+                (None, None) => break,
+                (None, Some(b)) if b.is_ascii_whitespace() => break,
+                (None, Some(b @ (b'"' | b'\''))) => quote = Some(b),
+                (None, Some(b)) => arg.push(b),
+                (Some(q), Some(b)) if b == q => {
+                    if bytes.peek().is_some_and(|b| !b.is_ascii_whitespace()) {
+                        return None;
+                    }
+                    quote = None;
+                }
+                (Some(b'"'), Some(b'\\')) => arg.push(unescape(&mut bytes)?),
+                (Some(b'\''), Some(b'\\')) if bytes.peek() == Some(&b'\'') => {
+                    arg.push(b'\'');
+                    bytes.next();
+                }
+                (Some(_), Some(b)) => arg.push(b),
+            }
+        }
+        args.push(arg);
     }
 }
 
+fn unescape(bytes: &mut impl Iterator<Item = u8>) -> Option<u8> {
+    let byte = match bytes.next()? {
+        b'n' => b'\n',
+        b'r' => b'\r',
+        b't' => b'\t',
+        b'b' => 0x08,
+        b'a' => 0x07,
+        b'x' => {
+            let high = (bytes.next()? as char).to_digit(16)?;
+            let low = (bytes.next()? as char).to_digit(16)?;
+            (high * 16 + low) as u8
+        }
+        b => b,
+    };
+    Some(byte)
+}
+
 impl Display for Resp {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", String::from(self))
@@ -75,7 +198,7 @@ impl From<&Resp> for String {
                 string += "$";
                 string += b.len().to_string().as_str();
                 string += clrf;
-                string += b.as_str();
+                string += String::from_utf8_lossy(b).as_ref();
                 string += clrf;
             }
             Resp::Array(a) => {
@@ -91,6 +214,23 @@ impl From<&Resp> for String {
                 string += "-1";
                 string += clrf;
             }
+            Resp::Map(pairs) => {
+                string += "%";
+                string += pairs.len().to_string().as_str();
+                string += clrf;
+                for (key, value) in pairs {
+                    string += String::from(key).as_str();
+                    string += String::from(value).as_str();
+                }
+            }
+            Resp::Push(a) => {
+                string += ">";
+                string += a.len().to_string().as_str();
+                string += clrf;
+                for i in a {
+                    string += String::from(i).as_str();
+                }
+            }
         }
         string
     }
@@ -113,14 +253,14 @@ impl From<Resp> for Vec<u8> {
             }
             Resp::Integer(i) => {
                 bytes.push(b':');
-                bytes.extend_from_slice(&i.to_string().as_bytes());
+                bytes.extend_from_slice(i.to_string().as_bytes());
                 bytes.extend_from_slice(clrf);
             }
             Resp::BulkString(b) => {
                 bytes.push(b'$');
                 bytes.extend_from_slice(b.len().to_string().as_bytes());
                 bytes.extend_from_slice(clrf);
-                bytes.extend_from_slice(b.as_bytes());
+                bytes.extend_from_slice(&b);
                 bytes.extend_from_slice(clrf);
             }
             Resp::Array(resps) => {
@@ -133,140 +273,203 @@ impl From<Resp> for Vec<u8> {
                 }
             }
             Resp::Null => bytes.extend_from_slice(b"*-1\r\n"),
+            Resp::Map(pairs) => {
+                bytes.push(b'%');
+                bytes.extend_from_slice(pairs.len().to_string().as_bytes());
+                bytes.extend_from_slice(clrf);
+                for (key, value) in pairs {
+                    bytes.extend(Vec::from(key));
+                    bytes.extend(Vec::from(value));
+                }
+            }
+            Resp::Push(resps) => {
+                bytes.push(b'>');
+                bytes.extend_from_slice(resps.len().to_string().as_bytes());
+                bytes.extend_from_slice(clrf);
+                for resp in resps {
+                    bytes.extend(Vec::from(resp));
+                }
+            }
         }
         bytes
     }
 }
 
-fn parse_resp(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    if value.is_empty() {
-        return (None, value);
-    }
+const MAX_NESTING: usize = 128;
+const MAX_AGGREGATE_LEN: i64 = 1024 * 1024;
+const MAX_BULK_LEN: i64 = 512 * 1024 * 1024;
+pub const MAX_INLINE_LEN: usize = 64 * 1024;
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    Incomplete,
+    Invalid(&'static str),
+}
+
+type ParseResult<'a> = Result<(Resp, &'a [u8]), ParseError>;
+
+fn parse_value(value: &[u8], depth: usize) -> ParseResult<'_> {
+    let Some(kind) = value.first() else {
+        return Err(ParseError::Incomplete);
+    };
     let body = &value[1..];
-    match value[0] {
+    match kind {
         b'+' => parse_simple_string(body),
         b'-' => parse_simple_error(body),
         b':' => parse_integer(body),
         b'$' => parse_bulk_string(body),
-        b'*' => parse_array(body),
-        _ => (None, value),
+        b'*' => parse_array(body, depth),
+        b'%' => parse_map(body, depth),
+        b'>' => match parse_array(body, depth)? {
+            (Resp::Array(array), r) => Ok((Resp::Push(array), r)),
+            _ => Err(ParseError::Invalid("invalid multibulk length")),
+        },
+        _ if depth == 0 => parse_inline(value),
+        _ => Err(ParseError::Invalid("unexpected type byte")),
     }
 }
 
-fn parse_simple_string(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    let pos = value.iter().position(|b| *b == b'\r');
-    if pos.is_none() {
-        return (None, value);
+fn parse_inline(mut value: &[u8]) -> ParseResult<'_> {
+    loop {
+        let window = &value[..value.len().min(MAX_INLINE_LEN)];
+        let Some(pos) = window.iter().position(|b| *b == b'\n') else {
+            if value.len() >= MAX_INLINE_LEN {
+                return Err(ParseError::Invalid("too big inline request"));
+            }
+            return Err(ParseError::Incomplete);
+        };
+        let line = &value[..pos];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let args = split_args(line).ok_or(ParseError::Invalid("unbalanced quotes in request"))?;
+        value = &value[pos + 1..];
+        if !args.is_empty() {
+            let args = args.into_iter().map(Resp::BulkString).collect();
+            return Ok((Resp::Array(args), value));
+        }
+        if value.first().is_some_and(|b| b"+-:$*%>".contains(b)) {
+            return parse_value(value, 0);
+        }
     }
-    let pos = pos.unwrap();
-    let (data, remaining) = value.split_at(pos);
-    if remaining.len() < 2 || b"\r\n" != &remaining[..2] {
-        return (None, value);
+}
+
+fn parse_line(value: &[u8]) -> Result<(&[u8], &[u8]), ParseError> {
+    let Some(pos) = value.iter().position(|b| *b == b'\r') else {
+        return Err(ParseError::Incomplete);
+    };
+    match value.get(pos + 1) {
+        Some(b'\n') => Ok((&value[..pos], &value[pos + 2..])),
+        Some(_) => Err(ParseError::Invalid("expected CRLF")),
+        None => Err(ParseError::Incomplete),
     }
+}
+
+fn parse_simple_string(value: &[u8]) -> ParseResult<'_> {
+    let (data, remaining) = parse_line(value)?;
     let text = String::from_utf8_lossy(data).to_string();
-    (Some(Resp::SimpleString(text)), &remaining[2..])
+    Ok((Resp::SimpleString(text), remaining))
 }
 
-fn parse_simple_error(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    match parse_simple_string(value) {
-        (Some(Resp::SimpleString(s)), r) => (Some(Resp::SimpleError(s)), r),
-        _ => (None, value),
-    }
+fn parse_simple_error(value: &[u8]) -> ParseResult<'_> {
+    let (data, remaining) = parse_line(value)?;
+    let text = String::from_utf8_lossy(data).to_string();
+    Ok((Resp::SimpleError(text), remaining))
 }
 
-fn parse_integer(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    match parse_length(value) {
-        (Some(i), r) => (Some(Resp::Integer(i)), r),
-        _ => (None, value),
-    }
+fn parse_integer(value: &[u8]) -> ParseResult<'_> {
+    let (integer, remaining) = parse_length(value).map_err(|err| match err {
+        ParseError::Invalid(_) => ParseError::Invalid("invalid integer"),
+        err => err,
+    })?;
+    Ok((Resp::Integer(integer), remaining))
 }
 
-fn parse_array(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    let (length, remaining) = parse_length(value);
-    if length.is_none() {
-        return (None, value);
-    }
-    if length.unwrap() == -1 {
-        match parse_null(value) {
-            (Some(null), r) => return (Some(null), r),
-            _ => return (None, value),
-        }
+fn parse_aggregate_len(value: &[u8], depth: usize) -> Result<(Option<usize>, &[u8]), ParseError> {
+    const INVALID: ParseError = ParseError::Invalid("invalid multibulk length");
+    if depth >= MAX_NESTING {
+        return Err(ParseError::Invalid("too many nested aggregates"));
+    }
+    let (length, remaining) = parse_length(value).map_err(|err| match err {
+        ParseError::Invalid(_) => INVALID,
+        err => err,
+    })?;
+    match length {
+        -1 => Ok((None, remaining)),
+        0..=MAX_AGGREGATE_LEN => Ok((Some(length as usize), remaining)),
+        _ => Err(INVALID),
     }
-    let length = length.unwrap() as usize;
-    let mut array = Vec::with_capacity(length);
-    let mut contents = remaining;
-    for _ in 0..length {
-        match parse_resp(contents) {
-            (Some(resp), r) => {
-                array.push(resp);
-                contents = r;
-            }
-            (None, r) => {
-                if r.is_empty() && array.len() == length {
-                    return (Some(Resp::Array(array)), r);
-                }
-                return (None, r);
-            }
-        }
-    }
-    (Some(Resp::Array(array)), contents)
 }
 
-fn parse_bulk_string(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    let (length, remaining) = parse_length(value);
-    if length.is_none() {
-        return (None, value);
-    }
-    if length.unwrap() == -1 {
-        match parse_null(value) {
-            (Some(null), r) => return (Some(null), r),
-            _ => return (None, value),
-        }
-    }
-    let length = length.unwrap() as usize;
-    let (data, remaining) = remaining.split_at(length);
-    let text = String::from_utf8_lossy(data).to_string();
-    if text.len() != length || remaining.len() < 2 || b"\r\n" != &remaining[..2] {
-        return (None, value);
+fn parse_array(value: &[u8], depth: usize) -> ParseResult<'_> {
+    let (length, mut contents) = parse_aggregate_len(value, depth)?;
+    let Some(length) = length else {
+        return Ok((Resp::Null, contents));
+    };
+    let mut array = Vec::with_capacity(length.min(contents.len()));
+    for _ in 0..length {
+        let (resp, r) = parse_value(contents, depth + 1)?;
+        array.push(resp);
+        contents = r;
     }
-    (Some(Resp::BulkString(text)), &remaining[2..])
+    Ok((Resp::Array(array), contents))
 }
 
-fn parse_length(value: &[u8]) -> (Option<i64>, &[u8]) {
-    let pos = value.iter().position(|b| *b == b'\r');
-    if pos.is_none() {
-        return (None, value);
-    }
-    let pos = pos.unwrap();
-    let (binary, remaining) = value.split_at(pos);
-    let binary_str = match std::str::from_utf8(binary) {
-        Ok(s) => s,
-        Err(_) => return (None, value),
-    };
-    let length = match binary_str.parse() {
-        Ok(l) => l,
-        Err(_) => return (None, value),
+fn parse_map(value: &[u8], depth: usize) -> ParseResult<'_> {
+    let (Some(length), mut contents) = parse_aggregate_len(value, depth)? else {
+        return Err(ParseError::Invalid("invalid multibulk length"));
     };
-    if value.len() <= pos + 1 || value[pos + 1] != b'\n' {
-        return (None, value);
+    let mut pairs = Vec::with_capacity(length.min(contents.len()));
+    for _ in 0..length {
+        let (key, r) = parse_value(contents, depth + 1)?;
+        let (value, r) = parse_value(r, depth + 1)?;
+        pairs.push((key, value));
+        contents = r;
     }
-    (Some(length), &remaining[2..])
+    Ok((Resp::Map(pairs), contents))
 }
 
-fn parse_null(value: &[u8]) -> (Option<Resp>, &[u8]) {
-    if value.len() < 4 {
-        return (None, value);
+fn parse_bulk_string(value: &[u8]) -> ParseResult<'_> {
+    const INVALID: ParseError = ParseError::Invalid("invalid bulk length");
+    let (length, remaining) = parse_length(value).map_err(|err| match err {
+        ParseError::Invalid(_) => INVALID,
+        err => err,
+    })?;
+    let length = match length {
+        -1 => return Ok((Resp::Null, remaining)),
+        0..=MAX_BULK_LEN => length as usize,
+        _ => return Err(INVALID),
+    };
+    if remaining.len() < length + 2 {
+        return Err(ParseError::Incomplete);
     }
-    let null = &value[..4];
-    match null {
-        b"-1\r\n" => (Some(Resp::Null), &value[4..]),
-        _ => (None, &value[4..]),
+    let (data, remaining) = remaining.split_at(length);
+    match remaining.strip_prefix(b"\r\n") {
+        Some(remaining) => Ok((Resp::BulkString(data.to_vec()), remaining)),
+        None => Err(ParseError::Invalid("expected CRLF after bulk string")),
     }
 }
 
+fn parse_length(value: &[u8]) -> Result<(i64, &[u8]), ParseError> {
+    let (line, remaining) = parse_line(value)?;
+    let length = std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse().ok())
+        .ok_or(ParseError::Invalid("invalid length"))?;
+    Ok((length, remaining))
+}
+
+#[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
+    fn parse_resp(value: &[u8]) -> (Option<Resp>, &[u8]) {
+        match parse_value(value, 0) {
+            Ok((resp, remaining)) => (Some(resp), remaining),
+            Err(_) => (None, value),
+        }
+    }
+
     #[test]
     fn parse_null() -> Result<(), &'static str> {
         let input = "$-1\r\n";
@@ -287,7 +490,7 @@ mod tests {
                 assert!(r.is_empty());
                 assert_eq!(arr.len(), 1);
                 match &arr[0] {
-                    Resp::BulkString(s) => assert_eq!(s, "ping"),
+                    Resp::BulkString(s) => assert_eq!(s, b"ping"),
                     _ => return Err("Array should contain a simple string"),
                 }
                 Ok(())
@@ -308,11 +511,11 @@ mod tests {
                 assert!(r.is_empty());
                 assert_eq!(arr.len(), 2);
                 match &arr[0] {
-                    Resp::BulkString(s) => assert_eq!(s, "echo"),
+                    Resp::BulkString(s) => assert_eq!(s, b"echo"),
                     _ => return Err("Array should contain a simple string"),
                 }
                 match &arr[1] {
-                    Resp::BulkString(s) => assert_eq!(s, "hello world"),
+                    Resp::BulkString(s) => assert_eq!(s, b"hello world"),
                     _ => return Err("Array should contain a simple string"),
                 }
                 Ok(())
@@ -329,11 +532,11 @@ mod tests {
                 assert!(r.is_empty());
                 assert_eq!(arr.len(), 2);
                 match &arr[0] {
-                    Resp::BulkString(s) => assert_eq!(s, "get"),
+                    Resp::BulkString(s) => assert_eq!(s, b"get"),
                     _ => return Err("Array should contain a bulk string"),
                 }
                 match &arr[1] {
-                    Resp::BulkString(s) => assert_eq!(s, "key"),
+                    Resp::BulkString(s) => assert_eq!(s, b"key"),
                     _ => return Err("Array should contain a bulk string"),
                 }
                 Ok(())
@@ -349,15 +552,15 @@ mod tests {
             (Some(Resp::Array(arr)), r) => {
                 assert_eq!(arr.len(), 3);
                 match &arr[0] {
-                    Resp::BulkString(s) => assert_eq!(s, "CONFIG"),
+                    Resp::BulkString(s) => assert_eq!(s, b"CONFIG"),
                     _ => return Err("Expected bulk string"),
                 }
                 match &arr[1] {
-                    Resp::BulkString(s) => assert_eq!(s, "GET"),
+                    Resp::BulkString(s) => assert_eq!(s, b"GET"),
                     _ => return Err("Expected bulk string"),
                 }
                 match &arr[2] {
-                    Resp::BulkString(s) => assert_eq!(s, "save"),
+                    Resp::BulkString(s) => assert_eq!(s, b"save"),
                     _ => return Err("Expected bulk string"),
                 }
                 assert!(!r.is_empty());
@@ -366,15 +569,15 @@ mod tests {
                         assert_eq!(arr.len(), 3);
                         assert!(r.is_empty());
                         match &arr[0] {
-                            Resp::BulkString(s) => assert_eq!(s, "CONFIG"),
+                            Resp::BulkString(s) => assert_eq!(s, b"CONFIG"),
                             _ => return Err("Expected bulk string"),
                         }
                         match &arr[1] {
-                            Resp::BulkString(s) => assert_eq!(s, "GET"),
+                            Resp::BulkString(s) => assert_eq!(s, b"GET"),
                             _ => return Err("Expected bulk string"),
                         }
                         match &arr[2] {
-                            Resp::BulkString(s) => assert_eq!(s, "appendonly"),
+                            Resp::BulkString(s) => assert_eq!(s, b"appendonly"),
                             _ => return Err("Expected bulk string"),
                         }
                     }
@@ -415,7 +618,7 @@ mod tests {
         let input = "$0\r\n\r\n";
         match parse_resp(input.as_bytes()) {
             (Some(Resp::BulkString(s)), _) => {
-                assert_eq!(s, "");
+                assert!(s.is_empty());
                 Ok(())
             }
             _ => Err("Should be of type bulk string"),
@@ -433,4 +636,184 @@ mod tests {
             _ => Err("Should be of type simple string"),
         }
     }
+
+    #[test]
+    fn parse_inline() {
+        let input = b"PING\r\n\r\nset key \"hello world\"\nGET key\r\n*1\r\n$4\r\nping\r\n";
+        let command = |args: &[&str]| {
+            Resp::Array(
+                args.iter()
+                    .map(|arg| Resp::BulkString((*arg).into()))
+                    .collect(),
+            )
+        };
+        assert_eq!(
+            Ok(vec![
+                command(&["PING"]),
+                command(&["set", "key", "hello world"]),
+                command(&["GET", "key"]),
+                command(&["ping"]),
+            ]),
+            Resp::parse(input)
+        );
+        assert_eq!(
+            Err(Resp::SimpleError(String::from(
+                "ERR Protocol error: unbalanced quotes in request"
+            ))),
+            Resp::parse(b"echo \"unbalanced\r\n")
+        );
+        assert_eq!(
+            (None, &b"PING"[..]),
+            parse_resp(b"PING"),
+            "an unterminated inline command is incomplete"
+        );
+    }
+
+    #[test]
+    fn split_inline_args() {
+        let args = |line: &str| split_args(line.as_bytes());
+        assert_eq!(
+            Some(vec![b"set".to_vec(), b"key".to_vec()]),
+            args("  set   key ")
+        );
+        assert_eq!(
+            Some(vec![
+                b"echo".to_vec(),
+                b"hello world\n".to_vec(),
+                b"it's".to_vec()
+            ]),
+            args(r#"echo "hello world\n" 'it\'s'"#)
+        );
+        assert_eq!(Some(vec![vec![0x41, 0xff]]), args(r#""\x41\xff""#));
+        assert_eq!(Some(vec![]), args(""));
+        assert_eq!(None, args(r#"echo "unbalanced"#));
+        assert_eq!(None, args(r#"echo "closing"quote"#));
+    }
+
+    #[test]
+    fn resp3_types() {
+        let push = Resp::Push(vec![
+            Resp::BulkString("invalidate".into()),
+            Resp::Array(vec![Resp::BulkString("key".into())]),
+        ]);
+        let map = Resp::Map(vec![
+            (Resp::BulkString("proto".into()), Resp::Integer(3)),
+            (Resp::BulkString("modules".into()), Resp::Array(vec![])),
+        ]);
+        let input = b">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nkey\r\n%2\r\n$5\r\nproto\r\n:3\r\n$7\r\nmodules\r\n*0\r\n";
+        assert_eq!(Ok(vec![push.clone(), map.clone()]), Resp::parse(input));
+        let mut serialized = Vec::from(push);
+        serialized.extend(Vec::from(map));
+        assert_eq!(input.to_vec(), serialized);
+        assert_eq!(Ok(None), Resp::parse_one(b"%1\r\n$5\r\nproto\r\n"));
+    }
+
+    #[test]
+    fn reject_malformed_input() {
+        let error =
+            |message: &str| Err(Resp::SimpleError(format!("ERR Protocol error: {message}")));
+        assert_eq!(error("invalid bulk length"), Resp::parse(b"$-2\r\n"));
+        assert_eq!(error("invalid bulk length"), Resp::parse(b"$abc\r\n"));
+        assert_eq!(error("invalid multibulk length"), Resp::parse(b"*-5\r\n"));
+        assert_eq!(
+            error("invalid multibulk length"),
+            Resp::parse(b"*9223372036854775807\r\n")
+        );
+        assert_eq!(
+            error("expected CRLF after bulk string"),
+            Resp::parse(b"$1\r\nab\r\n")
+        );
+        assert_eq!(error("unexpected type byte"), Resp::parse(b"*1\r\nGET\r\n"));
+        let nested = "*1\r\n".repeat(100_000);
+        assert_eq!(
+            error("too many nested aggregates"),
+            Resp::parse(nested.as_bytes())
+        );
+        let unterminated = vec![b'a'; MAX_INLINE_LEN];
+        assert_eq!(error("too big inline request"), Resp::parse(&unterminated));
+        assert_eq!(Ok(None), Resp::parse_one(&unterminated[1..]));
+        let blank_lines = "\r\n".repeat(100_000);
+        assert_eq!(Ok(vec![]), Resp::parse(blank_lines.as_bytes()));
+        assert_eq!(Ok(None), Resp::parse_one(b"*3\r\n$3\r\nSET\r\n"));
+    }
+
+    fn resp_strategy() -> impl Strategy<Value = Resp> {
+        let leaf = prop_oneof![
+            "[^\r\n]*".prop_map(Resp::SimpleString),
+            "[^\r\n]*".prop_map(Resp::SimpleError),
+            any::<i64>().prop_map(Resp::Integer),
+            prop::collection::vec(any::<u8>(), 0..64).prop_map(Resp::BulkString),
+            Just(Resp::Null),
+        ];
+        leaf.prop_recursive(4, 64, 8, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Resp::Array),
+                prop::collection::vec(inner.clone(), 0..8).prop_map(Resp::Push),
+                prop::collection::vec((inner.clone(), inner), 0..8).prop_map(Resp::Map),
+            ]
+        })
+    }
+
+    const TOKENS: &[&str] = &[
+        "*",
+        "$",
+        "%",
+        ">",
+        "+",
+        "-",
+        ":",
+        "-1",
+        "-2",
+        "0",
+        "1",
+        "3",
+        "64",
+        "9223372036854775808",
+        "\r\n",
+        "\r",
+        "\n",
+        "a",
+        "\"",
+        "'",
+        " ",
+        "\\x",
+    ];
+
+    proptest! {
+        #[test]
+        fn serialized_values_parse_back(resps in prop::collection::vec(resp_strategy(), 1..4)) {
+            let bytes: Vec<u8> = resps.iter().cloned().flat_map(Vec::from).collect();
+            prop_assert_eq!(Ok(resps), Resp::parse(&bytes));
+        }
+
+        #[test]
+        fn truncated_values_are_incomplete(resp in resp_strategy(), cut in any::<prop::sample::Index>()) {
+            let bytes = Vec::from(resp.clone());
+            prop_assert_eq!(Ok(None), Resp::parse_one(&bytes[..cut.index(bytes.len())]));
+            prop_assert_eq!(Ok(Some((resp, &b""[..]))), Resp::parse_one(&bytes));
+        }
+
+        #[test]
+        fn arbitrary_bytes_do_not_panic(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+            let _ = Resp::parse(&bytes);
+            let _ = Resp::parse_one(&bytes);
+        }
+
+        #[test]
+        fn oversized_inline_requests_are_rejected(line in "[a-z ]{1,64}") {
+            let request = line.repeat(MAX_INLINE_LEN / line.len() + 1);
+            prop_assert_eq!(
+                Err(Resp::SimpleError(String::from("ERR Protocol error: too big inline request"))),
+                Resp::parse_one(request.as_bytes())
+            );
+        }
+
+        #[test]
+        fn parsed_frames_round_trip(tokens in prop::collection::vec(prop::sample::select(TOKENS), 0..32)) {
+            if let Ok(resps) = Resp::parse(tokens.concat().as_bytes()) {
+                let bytes: Vec<u8> = resps.iter().cloned().flat_map(Vec::from).collect();
+                prop_assert_eq!(Ok(resps), Resp::parse(&bytes));
+            }
+        }
+    }
 }
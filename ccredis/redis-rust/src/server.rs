@@ -1,18 +1,18 @@
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap},
     io::{self, BufReader, Read, Write},
-    net::{SocketAddr, TcpListener, TcpStream},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     sync::mpsc::{self, Receiver, Sender},
     thread::{self, JoinHandle},
+    time::Duration,
 };
-use std::io::BufRead;
 
-use crate::{command::Command, resp::Resp, worker::Worker};
+use crate::{cluster, resp::Resp, session::Session, worker::Worker};
 
 pub struct ServerThread {
     server: Option<Server>,
     sender: Option<Sender<()>>,
-    join_handle: Option<JoinHandle<()>>,
+    join_handle: Option<JoinHandle<io::Result<()>>>,
 }
 
 impl ServerThread {
@@ -37,69 +37,183 @@ impl Drop for ServerThread {
     fn drop(&mut self) {
         drop(self.sender.take());
         if let Some(join_handle) = self.join_handle.take() {
-            join_handle.join().unwrap();
+            if let Err(err) = join_handle.join().unwrap() {
+                println!("{err}");
+            }
             println!("stopped server");
         }
     }
 }
 
+struct Connection {
+    stream: BufReader<TcpStream>,
+    buffer: Vec<u8>,
+    session: Session,
+}
+
 pub struct Server {
     listener: TcpListener,
-    connections: HashMap<SocketAddr, BufReader<TcpStream>>,
+    bus_listener: Option<TcpListener>,
+    connections: HashMap<SocketAddr, Connection>,
+    links: HashMap<SocketAddr, TcpStream>,
     worker: Worker,
 }
 
 impl Server {
-    pub fn new(address: &str, worker: Worker) -> io::Result<Self> {
+    pub fn new(address: &str, mut worker: Worker) -> io::Result<Self> {
         let listener = TcpListener::bind(address)?;
         listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        worker.set_address(address);
+        let bus_listener = if worker.is_cluster_enabled() {
+            let bus_address = cluster::bus_address(address).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "port too high to derive the cluster bus port",
+                )
+            })?;
+            let bus_listener = TcpListener::bind(bus_address)?;
+            bus_listener.set_nonblocking(true)?;
+            Some(bus_listener)
+        } else {
+            None
+        };
         Ok(Server {
             listener,
+            bus_listener,
             connections: HashMap::new(),
+            links: HashMap::new(),
             worker,
         })
     }
-    pub fn start(&mut self, receiver: Receiver<()>) {
+    /// Serves clients until a shutdown is requested, then closes every
+    /// connection. Returns the first error hit while shutting down.
+    pub fn start(&mut self, receiver: Receiver<()>) -> io::Result<()> {
         loop {
-            if let Err(mpsc::TryRecvError::Disconnected) = receiver.try_recv() {
+            if !matches!(receiver.try_recv(), Err(mpsc::TryRecvError::Empty)) {
+                println!("Received shutdown signal, scheduling shutdown...");
                 break;
             }
-            let result = try_accept(&self.listener);
-            if let Some((stream, address)) = result {
+            if self.worker.is_shutting_down() {
+                break;
+            }
+            let listeners = std::iter::once((&self.listener, false))
+                .chain(self.bus_listener.iter().map(|listener| (listener, true)));
+            for (listener, bus) in listeners {
+                let Some((stream, address)) = try_accept(listener) else {
+                    continue;
+                };
                 println!("new connection: {address}");
                 stream.set_nonblocking(true).unwrap();
-                self.connections.insert(address, BufReader::new(stream));
+                stream.set_nodelay(true).unwrap();
+                let mut session = Session::new(address);
+                session.bus = bus;
+                let connection = Connection {
+                    stream: BufReader::new(stream),
+                    buffer: Vec::new(),
+                    session,
+                };
+                self.worker.connect(&connection.session);
+                self.connections.insert(address, connection);
             }
             let mut disconnected = Vec::new();
-            for (address, stream) in self.connections.iter_mut() {
-                match try_read(stream) {
-                    Ok(bytes) => match parse(&bytes) {
-                        Ok(commands) => {
-                            for command in commands {
-                                println!("Received command {command:?}");
-                                let response = self.worker.handle_command(command);
-                                println!("Sending response {response}");
-                                let serialized = Vec::from(response);
-                                if let Err(err) = stream.get_mut().write_all(&serialized) {
-                                    println!("{err}");
-                                }
-                            }
+            let mut pushes: Vec<(SocketAddr, Resp)> = Vec::new();
+            for (address, connection) in self.connections.iter_mut() {
+                let Connection {
+                    stream,
+                    buffer,
+                    session,
+                } = connection;
+                let (own, others) = pushes.drain(..).partition(|(target, _)| target == address);
+                pushes = others;
+                for (_, push) in own {
+                    if let Err(err) = stream.get_mut().write_all(&Vec::from(push)) {
+                        println!("{err}");
+                    }
+                }
+                if let Err(err) = try_read(stream, buffer) {
+                    disconnected.push(*address);
+                    println!("{err}");
+                    continue;
+                }
+                while !self.worker.is_blocked(address) && !self.worker.is_shutting_down() {
+                    let request = match Resp::parse_one(buffer) {
+                        Ok(Some((request, remaining))) => {
+                            let consumed = buffer.len() - remaining.len();
+                            buffer.drain(..consumed);
+                            request
                         }
+                        Ok(None) => break,
                         Err(err) => {
                             disconnected.push(*address);
                             println!("{err}");
+                            let _ = stream.get_mut().write_all(&Vec::from(err));
+                            break;
                         }
-                    },
-                    Err(err) => {
-                        disconnected.push(*address);
+                    };
+                    println!("Received request {request}");
+                    if let Some(response) = self.worker.handle_request(session, request) {
+                        println!("Sending response {response}");
+                        let serialized = Vec::from(response);
+                        if let Err(err) = stream.get_mut().write_all(&serialized) {
+                            println!("{err}");
+                        }
+                    }
+                    for (target, push) in self.worker.take_pushes() {
+                        if target != *address {
+                            pushes.push((target, push));
+                        } else if let Err(err) = stream.get_mut().write_all(&Vec::from(push)) {
+                            println!("{err}");
+                        }
+                    }
+                }
+            }
+            self.worker.cron();
+            self.worker.serve_blocked();
+            pushes.extend(self.worker.take_pushes());
+            for (address, message) in self.worker.take_bus_messages() {
+                send_bus_message(&mut self.links, address, message);
+            }
+            for (address, push) in pushes {
+                if let Some(connection) = self.connections.get_mut(&address) {
+                    let stream = connection.stream.get_mut();
+                    if let Err(err) = stream.write_all(&Vec::from(push)) {
                         println!("{err}");
                     }
                 }
             }
             for address in disconnected {
                 self.connections.remove(&address);
+                self.worker.disconnect(&address);
+            }
+        }
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        println!("Closing {} client connections", self.connections.len());
+        let mut failure = None;
+        for (address, connection) in self.connections.drain() {
+            let stream = connection.stream.into_inner();
+            let result = stream
+                .set_nonblocking(false)
+                .and_then(|_| stream.shutdown(Shutdown::Write));
+            match result {
+                Err(err) if err.kind() != io::ErrorKind::NotConnected => {
+                    println!("{address}: {err}");
+                    failure.get_or_insert(err);
+                }
+                _ => {}
             }
+            self.worker.disconnect(&address);
         }
+        self.links.clear();
+        if let Some(err) = failure {
+            println!("Errors trying to SHUTDOWN. Check logs.");
+            return Err(err);
+        }
+        println!("Redis is now ready to exit, bye bye...");
+        Ok(())
     }
 }
 
@@ -114,6 +228,47 @@ fn try_accept(listener: &TcpListener) -> Option<(TcpStream, SocketAddr)> {
         .ok()
 }
 
+fn send_bus_message(
+    links: &mut HashMap<SocketAddr, TcpStream>,
+    address: SocketAddr,
+    message: Resp,
+) {
+    const CONNECT_TIMEOUT: Duration = Duration::from_millis(100);
+    let link = match links.entry(address) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+                .and_then(|stream| stream.set_nonblocking(true).map(|_| stream));
+            match stream {
+                Ok(stream) => entry.insert(stream),
+                Err(err) => {
+                    println!("cluster bus {address}: {err}");
+                    return;
+                }
+            }
+        }
+    };
+    let mut buf = [0; 1024];
+    let result = loop {
+        match link.read(&mut buf) {
+            Ok(0) => {
+                break Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "Connection closed",
+                ))
+            }
+            Ok(_) => continue,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => break Ok(()),
+            Err(err) => break Err(err),
+        }
+    }
+    .and_then(|_| link.write_all(&Vec::from(message)));
+    if let Err(err) = result {
+        println!("cluster bus {address}: {err}");
+        links.remove(&address);
+    }
+}
+
 pub struct RespStream {
     inner: BufReader<TcpStream>,
     buffer: Vec<u8>,
@@ -121,9 +276,12 @@ pub struct RespStream {
 
 impl RespStream {
     pub fn new(stream: TcpStream) -> Self {
-        Self { inner: BufReader::new(stream), buffer: Vec::new() }
+        Self {
+            inner: BufReader::new(stream),
+            buffer: Vec::new(),
+        }
     }
-    pub fn next(&mut self) -> io::Result<Result<Vec<Resp>, Resp>> {
+    pub fn next_resps(&mut self) -> io::Result<Result<Vec<Resp>, Resp>> {
         let n = self.inner.read(&mut self.buffer)?;
         if n == 0 {
             return Err(io::Error::new(
@@ -133,29 +291,17 @@ impl RespStream {
         }
         match Resp::parse(&self.buffer) {
             Ok(r) => Ok(Ok(r)),
-            Err(r) => Ok(Err(r))
+            Err(r) => Ok(Err(r)),
         }
     }
 }
 
-//TODO check if data is incomplete buffer incomplete data and discard corrupted data
-fn parse(data: &[u8]) -> Result<Vec<Command>, Resp> {
-    let resps = Resp::parse(data)?;
-    let mut commands = Vec::new();
-    for resp in resps {
-        let command = Command::try_from(resp)?;
-        commands.push(command);
-    }
-    Ok(commands)
-}
-
-fn try_read(buf_reader: &mut BufReader<TcpStream>) -> io::Result<Vec<u8>> {
+fn try_read(buf_reader: &mut BufReader<TcpStream>, buffer: &mut Vec<u8>) -> io::Result<()> {
     const CHUNK_SIZE: usize = 1028;
-    let mut buffer = Vec::with_capacity(CHUNK_SIZE);
     let mut buf = [0; CHUNK_SIZE];
     loop {
         match buf_reader.read(&mut buf) {
-            Ok(size) if size == 0 => {
+            Ok(0) => {
                 return Err(io::Error::new(
                     io::ErrorKind::ConnectionAborted,
                     "Connection closed",
@@ -171,23 +317,49 @@ fn try_read(buf_reader: &mut BufReader<TcpStream>) -> io::Result<Vec<u8>> {
             Err(err) => return Err(err),
         }
     }
-    Ok(buffer)
+    Ok(())
 }
 
+#[cfg(test)]
 mod tests {
-    use std::error::Error;
+    use std::{error::Error, time::Duration};
 
-    use redis::{Commands, RedisError};
+    use redis::Commands;
 
-    use crate::dictionary::Dictionary;
+    use crate::{
+        client::{Client, ClientError},
+        config::Config,
+    };
 
     use super::*;
 
+    type TestCommand = Box<dyn FnOnce(&mut redis::Connection) -> redis::RedisResult<redis::Value>>;
+
     struct TestCase {
-        command: Box<dyn FnOnce(&mut redis::Connection) -> redis::RedisResult<redis::Value>>,
+        command: TestCommand,
         want: redis::Value,
     }
 
+    fn start_server(port: u16) -> Result<(ServerThread, redis::Client), Box<dyn Error>> {
+        let address = format!("127.0.0.1:{port}");
+        let mut server = ServerThread::new(Server::new(&address, Worker::new(Config::default()))?);
+        server.start();
+        let client = redis::Client::open(format!("redis://{address}"))?;
+        Ok((server, client))
+    }
+
+    fn start_cluster_node(port: u16) -> Result<(ServerThread, redis::Client), Box<dyn Error>> {
+        let address = format!("127.0.0.1:{port}");
+        let config = Config {
+            cluster_enabled: true,
+            ..Config::default()
+        };
+        let mut server = ServerThread::new(Server::new(&address, Worker::new(config))?);
+        server.start();
+        let client = redis::Client::open(format!("redis://{address}"))?;
+        Ok((server, client))
+    }
+
     #[test]
     fn set_value() -> Result<(), Box<dyn Error>> {
         let tests = HashMap::from([
@@ -220,7 +392,7 @@ mod tests {
             println!("Test: {name}");
             let address = "127.0.0.1:6379";
             let mut server =
-                ServerThread::new(Server::new(address, Worker::new(Dictionary::new()))?);
+                ServerThread::new(Server::new(address, Worker::new(Config::default()))?);
             server.start();
             let address = "redis://127.0.0.1:6379";
             let client = redis::Client::open(address)?;
@@ -233,4 +405,1040 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn counters_and_lists() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6377)?;
+        let mut connection = client.get_connection()?;
+        assert_eq!(
+            1,
+            redis::cmd("INCR")
+                .arg("counter")
+                .query::<i64>(&mut connection)?
+        );
+        assert_eq!(
+            2,
+            redis::cmd("INCR")
+                .arg("counter")
+                .query::<i64>(&mut connection)?
+        );
+        connection.set::<_, _, ()>("counter", i64::MAX)?;
+        let err = redis::cmd("INCR")
+            .arg("counter")
+            .query::<i64>(&mut connection)
+            .unwrap_err();
+        assert!(err.to_string().contains("would overflow"));
+        connection.set::<_, _, ()>("text", "")?;
+        let err = redis::cmd("INCR")
+            .arg("text")
+            .query::<i64>(&mut connection)
+            .unwrap_err();
+        assert!(err.to_string().contains("not an integer"));
+
+        assert_eq!(2, connection.lpush::<_, _, i64>("list", &["a", "b"])?);
+        assert_eq!(3, connection.lpush::<_, _, i64>("list", "c")?);
+        let encoding: String = redis::cmd("OBJECT")
+            .arg("ENCODING")
+            .arg("list")
+            .query(&mut connection)?;
+        assert_eq!("listpack", encoding);
+        let err = connection.lpush::<_, _, i64>("text", "a").unwrap_err();
+        assert!(err.to_string().contains("WRONGTYPE"));
+        let err = redis::cmd("INCR")
+            .arg("list")
+            .query::<i64>(&mut connection)
+            .unwrap_err();
+        assert!(err.to_string().contains("WRONGTYPE"));
+        Ok(())
+    }
+
+    #[test]
+    fn slowlog() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6380)?;
+        let mut connection = client.get_connection()?;
+        redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("slowlog-log-slower-than")
+            .arg(0)
+            .query::<()>(&mut connection)?;
+        connection.set::<_, _, ()>("key", "value")?;
+        let len: i64 = redis::cmd("SLOWLOG").arg("LEN").query(&mut connection)?;
+        assert_eq!(2, len);
+
+        let entries: Vec<redis::Value> = redis::cmd("SLOWLOG")
+            .arg("GET")
+            .arg(2)
+            .query(&mut connection)?;
+        assert_eq!(2, entries.len());
+        match &entries[1] {
+            redis::Value::Bulk(entry) => {
+                assert_eq!(redis::Value::Int(1), entry[0]);
+                let args = vec![
+                    redis::Value::Data(b"SET".into()),
+                    redis::Value::Data(b"key".into()),
+                    redis::Value::Data(b"value".into()),
+                ];
+                assert_eq!(redis::Value::Bulk(args), entry[3]);
+            }
+            other => panic!("expected slowlog entry, got {other:?}"),
+        }
+
+        redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("slowlog-log-slower-than")
+            .arg(-1)
+            .query::<()>(&mut connection)?;
+        redis::cmd("SLOWLOG")
+            .arg("RESET")
+            .query::<()>(&mut connection)?;
+        connection.set::<_, _, ()>("key", "value")?;
+        let len: i64 = redis::cmd("SLOWLOG").arg("LEN").query(&mut connection)?;
+        assert_eq!(0, len);
+        Ok(())
+    }
+
+    #[test]
+    fn monitor() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6381)?;
+        let mut monitor = client.get_connection()?;
+        monitor.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let reply: String = redis::cmd("MONITOR").query(&mut monitor)?;
+        assert_eq!("OK", reply);
+
+        let mut connection = client.get_connection()?;
+        connection.set::<_, _, ()>("key", "value")?;
+        loop {
+            match monitor.recv_response()? {
+                redis::Value::Status(line) if line.contains(r#""SET""#) => {
+                    assert!(line.ends_with(r#""SET" "key" "value""#), "{line}");
+                    assert!(line.contains("[0 127.0.0.1:"), "{line}");
+                    return Ok(());
+                }
+                redis::Value::Status(_) => continue,
+                other => panic!("expected status line, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn databases() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6382)?;
+        let mut connection = client.get_connection()?;
+        redis::cmd("SELECT").arg(1).query::<()>(&mut connection)?;
+        connection.set::<_, _, ()>("key", "value")?;
+        redis::cmd("SELECT").arg(0).query::<()>(&mut connection)?;
+        assert_eq!(None, connection.get::<_, Option<String>>("key")?);
+
+        let info: String = redis::cmd("INFO").arg("keyspace").query(&mut connection)?;
+        assert_eq!("# Keyspace\r\ndb1:keys=1,expires=0,avg_ttl=0\r\n", info);
+
+        redis::cmd("SWAPDB")
+            .arg(0)
+            .arg(1)
+            .query::<()>(&mut connection)?;
+        assert_eq!(Some(String::from("value")), connection.get("key")?);
+
+        let moved: i64 = redis::cmd("MOVE")
+            .arg("key")
+            .arg(2)
+            .query(&mut connection)?;
+        assert_eq!(1, moved);
+        let size: i64 = redis::cmd("DBSIZE").query(&mut connection)?;
+        assert_eq!(0, size);
+
+        let result: redis::RedisResult<()> = redis::cmd("SELECT").arg(16).query(&mut connection);
+        assert!(result.is_err());
+
+        redis::cmd("FLUSHALL").query::<()>(&mut connection)?;
+        redis::cmd("SELECT").arg(2).query::<()>(&mut connection)?;
+        let size: i64 = redis::cmd("DBSIZE").query(&mut connection)?;
+        assert_eq!(0, size);
+        Ok(())
+    }
+
+    fn next_message(pubsub: &mut redis::PubSub) -> Result<(String, String), Box<dyn Error>> {
+        let message = pubsub.get_message()?;
+        Ok((
+            message.get_channel_name().to_string(),
+            message.get_payload()?,
+        ))
+    }
+
+    #[test]
+    fn keyspace_notifications() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6383)?;
+        let mut connection = client.get_connection()?;
+        redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("notify-keyspace-events")
+            .arg("KEA")
+            .query::<()>(&mut connection)?;
+        let mut subscriber = client.get_connection()?;
+        let mut pubsub = subscriber.as_pubsub();
+        pubsub.set_read_timeout(Some(Duration::from_secs(5)))?;
+        pubsub.psubscribe("__key*__:*")?;
+
+        connection.set::<_, _, ()>("key", "value")?;
+        assert_eq!(
+            (String::from("__keyspace@0__:key"), String::from("set")),
+            next_message(&mut pubsub)?
+        );
+        assert_eq!(
+            (String::from("__keyevent@0__:set"), String::from("key")),
+            next_message(&mut pubsub)?
+        );
+
+        let err = connection.expire::<_, ()>("key", i64::MAX).unwrap_err();
+        assert!(err.to_string().contains("invalid expire time"));
+        connection.expire::<_, ()>("key", 1)?;
+        assert_eq!(
+            (String::from("__keyspace@0__:key"), String::from("expire")),
+            next_message(&mut pubsub)?
+        );
+        assert_eq!(
+            (String::from("__keyevent@0__:expire"), String::from("key")),
+            next_message(&mut pubsub)?
+        );
+        assert_eq!(
+            (String::from("__keyspace@0__:key"), String::from("expired")),
+            next_message(&mut pubsub)?
+        );
+        assert_eq!(
+            (String::from("__keyevent@0__:expired"), String::from("key")),
+            next_message(&mut pubsub)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn eviction_notifications() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6384)?;
+        let mut connection = client.get_connection()?;
+        for (parameter, value) in [
+            ("notify-keyspace-events", "Ee"),
+            ("maxmemory", "1"),
+            ("maxmemory-policy", "allkeys-random"),
+        ] {
+            redis::cmd("CONFIG")
+                .arg("SET")
+                .arg(parameter)
+                .arg(value)
+                .query::<()>(&mut connection)?;
+        }
+        let mut subscriber = client.get_connection()?;
+        let mut pubsub = subscriber.as_pubsub();
+        pubsub.set_read_timeout(Some(Duration::from_secs(5)))?;
+        pubsub.subscribe("__keyevent@0__:evicted")?;
+
+        connection.set::<_, _, ()>("first", "value")?;
+        connection.set::<_, _, ()>("second", "value")?;
+        assert_eq!(
+            (
+                String::from("__keyevent@0__:evicted"),
+                String::from("first")
+            ),
+            next_message(&mut pubsub)?
+        );
+        assert_eq!(None, connection.get::<_, Option<String>>("first")?);
+
+        redis::cmd("CONFIG")
+            .arg("SET")
+            .arg("maxmemory-policy")
+            .arg("noeviction")
+            .query::<()>(&mut connection)?;
+        let result: redis::RedisResult<()> = connection.set("third", "value");
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn streams() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6385)?;
+        let mut connection = client.get_connection()?;
+        let id: String = redis::cmd("XADD")
+            .arg("stream")
+            .arg("1-1")
+            .arg("field")
+            .arg("value")
+            .query(&mut connection)?;
+        assert_eq!("1-1", id);
+        let result: redis::RedisResult<String> = redis::cmd("XADD")
+            .arg("stream")
+            .arg("1-1")
+            .arg("field")
+            .arg("value")
+            .query(&mut connection);
+        assert!(result.is_err());
+
+        let range: redis::Value = redis::cmd("XRANGE")
+            .arg("stream")
+            .arg("-")
+            .arg("+")
+            .query(&mut connection)?;
+        let want = redis::Value::Bulk(vec![redis::Value::Bulk(vec![
+            redis::Value::Data(b"1-1".into()),
+            redis::Value::Bulk(vec![
+                redis::Value::Data(b"field".into()),
+                redis::Value::Data(b"value".into()),
+            ]),
+        ])]);
+        assert_eq!(want, range);
+
+        redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg("stream")
+            .arg("group")
+            .arg("0")
+            .query::<()>(&mut connection)?;
+        let read: redis::Value = redis::cmd("XREADGROUP")
+            .arg("GROUP")
+            .arg("group")
+            .arg("consumer")
+            .arg("STREAMS")
+            .arg("stream")
+            .arg(">")
+            .query(&mut connection)?;
+        assert_ne!(redis::Value::Nil, read);
+        let pending: (i64, String, String, Vec<Vec<String>>) = redis::cmd("XPENDING")
+            .arg("stream")
+            .arg("group")
+            .query(&mut connection)?;
+        assert_eq!(1, pending.0);
+        assert_eq!(
+            vec![vec![String::from("consumer"), String::from("1")]],
+            pending.3
+        );
+        let acked: i64 = redis::cmd("XACK")
+            .arg("stream")
+            .arg("group")
+            .arg("1-1")
+            .query(&mut connection)?;
+        assert_eq!(1, acked);
+        Ok(())
+    }
+
+    #[test]
+    fn blocking_xread() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6386)?;
+        let mut connection = client.get_connection()?;
+        let timed_out: redis::Value = redis::cmd("XREAD")
+            .arg("BLOCK")
+            .arg(50)
+            .arg("STREAMS")
+            .arg("stream")
+            .arg("$")
+            .query(&mut connection)?;
+        assert_eq!(redis::Value::Nil, timed_out);
+
+        let mut reader = client.get_connection()?;
+        let handle = thread::spawn(move || -> redis::RedisResult<redis::Value> {
+            redis::cmd("XREAD")
+                .arg("BLOCK")
+                .arg(5000)
+                .arg("STREAMS")
+                .arg("stream")
+                .arg("$")
+                .query(&mut reader)
+        });
+        thread::sleep(Duration::from_millis(200));
+        redis::cmd("XADD")
+            .arg("stream")
+            .arg("*")
+            .arg("field")
+            .arg("value")
+            .query::<String>(&mut connection)?;
+        match handle.join().unwrap()? {
+            redis::Value::Bulk(streams) => assert_eq!(1, streams.len()),
+            other => panic!("expected streams, got {other:?}"),
+        }
+
+        let mut stream = TcpStream::connect("127.0.0.1:6386")?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        stream.write_all(
+            b"XREAD BLOCK 100 STREAMS stream $\r\nXREAD BLOCK 100 STREAMS stream $\r\nPING\r\n",
+        )?;
+        let expected = b"*-1\r\n*-1\r\n+PONG\r\n";
+        let mut replies = vec![0; expected.len()];
+        stream.read_exact(&mut replies)?;
+        assert_eq!(expected.as_slice(), replies);
+        Ok(())
+    }
+
+    #[test]
+    fn blocked_clients_fifo() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6378)?;
+        let mut connection = client.get_connection()?;
+        redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg("stream")
+            .arg("group")
+            .arg("$")
+            .arg("MKSTREAM")
+            .query::<()>(&mut connection)?;
+        let mut handles = Vec::new();
+        for consumer in ["first", "second", "third"] {
+            let mut reader = client.get_connection()?;
+            handles.push(thread::spawn(move || -> redis::RedisResult<String> {
+                let read: redis::Value = redis::cmd("XREADGROUP")
+                    .arg("GROUP")
+                    .arg("group")
+                    .arg(consumer)
+                    .arg("BLOCK")
+                    .arg(5000)
+                    .arg("STREAMS")
+                    .arg("stream")
+                    .arg(">")
+                    .query(&mut reader)?;
+                assert_ne!(redis::Value::Nil, read);
+                Ok(consumer.to_string())
+            }));
+            thread::sleep(Duration::from_millis(100));
+        }
+        for (handle, expected) in handles.into_iter().zip(["first", "second", "third"]) {
+            redis::cmd("XADD")
+                .arg("stream")
+                .arg("*")
+                .arg("field")
+                .arg("value")
+                .query::<String>(&mut connection)?;
+            thread::sleep(Duration::from_millis(100));
+            assert!(handle.is_finished(), "{expected} was not served");
+            assert_eq!(expected, handle.join().unwrap()?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn command_introspection() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6387)?;
+        let mut connection = client.get_connection()?;
+        let count: i64 = redis::cmd("COMMAND").arg("COUNT").query(&mut connection)?;
+        assert!(count > 0);
+
+        let info: redis::Value = redis::cmd("COMMAND")
+            .arg("INFO")
+            .arg("get")
+            .arg("unknown")
+            .query(&mut connection)?;
+        match info {
+            redis::Value::Bulk(info) => {
+                assert_eq!(redis::Value::Nil, info[1]);
+                match &info[0] {
+                    redis::Value::Bulk(get) => {
+                        assert_eq!(redis::Value::Data(b"get".to_vec()), get[0]);
+                        assert_eq!(redis::Value::Int(2), get[1]);
+                    }
+                    other => panic!("expected command info, got {other:?}"),
+                }
+            }
+            other => panic!("expected command info, got {other:?}"),
+        }
+
+        let keys: Vec<String> = redis::cmd("COMMAND")
+            .arg("GETKEYS")
+            .arg("DEL")
+            .arg("a")
+            .arg("b")
+            .query(&mut connection)?;
+        assert_eq!(vec![String::from("a"), String::from("b")], keys);
+
+        let err = redis::cmd("GET")
+            .query::<String>(&mut connection)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("wrong number of arguments for 'get'"));
+        Ok(())
+    }
+
+    #[test]
+    fn cluster_redirections() -> Result<(), Box<dyn Error>> {
+        let (_first_server, first_client) = start_cluster_node(6388)?;
+        let (_second_server, second_client) = start_cluster_node(6389)?;
+        let mut first = first_client.get_connection()?;
+        let mut second = second_client.get_connection()?;
+        redis::cmd("CLUSTER")
+            .arg("ADDSLOTSRANGE")
+            .arg(0)
+            .arg(8191)
+            .query::<()>(&mut first)?;
+        redis::cmd("CLUSTER")
+            .arg("ADDSLOTSRANGE")
+            .arg(8192)
+            .arg(16383)
+            .query::<()>(&mut second)?;
+        redis::cmd("CLUSTER")
+            .arg("MEET")
+            .arg("127.0.0.1")
+            .arg(6389)
+            .query::<()>(&mut first)?;
+        for _ in 0..50 {
+            let first_slots: Vec<redis::Value> =
+                redis::cmd("CLUSTER").arg("SLOTS").query(&mut first)?;
+            let second_slots: Vec<redis::Value> =
+                redis::cmd("CLUSTER").arg("SLOTS").query(&mut second)?;
+            if first_slots.len() == 2 && second_slots.len() == 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        let nodes: String = redis::cmd("CLUSTER").arg("NODES").query(&mut first)?;
+        assert_eq!(2, nodes.lines().count());
+        let first_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut first)?;
+        let second_id: String = redis::cmd("CLUSTER").arg("MYID").query(&mut second)?;
+
+        first.set::<_, _, ()>("bar", "value")?;
+        let err = first.set::<_, _, ()>("foo", "value").unwrap_err();
+        assert_eq!(Some("MOVED"), err.code());
+        assert_eq!(Some(("127.0.0.1:6389", 12182)), err.redirect_node());
+        let err = second.get::<_, String>("bar").unwrap_err();
+        assert_eq!(Some(("127.0.0.1:6388", 5061)), err.redirect_node());
+        let err = first.del::<_, i64>(&["bar", "foo"]).unwrap_err();
+        assert_eq!(Some("CROSSSLOT"), err.code());
+
+        redis::cmd("CLUSTER")
+            .arg("SETSLOT")
+            .arg(12182)
+            .arg("MIGRATING")
+            .arg(&first_id)
+            .query::<()>(&mut second)?;
+        redis::cmd("CLUSTER")
+            .arg("SETSLOT")
+            .arg(12182)
+            .arg("IMPORTING")
+            .arg(&second_id)
+            .query::<()>(&mut first)?;
+        let err = second.get::<_, String>("foo").unwrap_err();
+        assert_eq!(Some("ASK"), err.code());
+        assert_eq!(Some(("127.0.0.1:6388", 12182)), err.redirect_node());
+        redis::cmd("ASKING").query::<()>(&mut first)?;
+        first.set::<_, _, ()>("foo", "value")?;
+        let err = first.get::<_, String>("foo").unwrap_err();
+        assert_eq!(Some("MOVED"), err.code());
+
+        for connection in [&mut first, &mut second] {
+            redis::cmd("CLUSTER")
+                .arg("SETSLOT")
+                .arg(12182)
+                .arg("NODE")
+                .arg(&first_id)
+                .query::<()>(connection)?;
+        }
+        assert_eq!(String::from("value"), first.get::<_, String>("foo")?);
+        let err = second.get::<_, String>("foo").unwrap_err();
+        assert_eq!(Some(("127.0.0.1:6388", 12182)), err.redirect_node());
+
+        let err = redis::cmd("CLUSTER")
+            .arg("GOSSIP")
+            .arg("forged")
+            .arg("127.0.0.1:7000")
+            .arg("0-16383")
+            .query::<()>(&mut first)
+            .unwrap_err();
+        assert!(err.to_string().contains("only accepted on the cluster bus"));
+        assert_eq!(String::from("value"), first.get::<_, String>("foo")?);
+        Ok(())
+    }
+
+    #[test]
+    fn hyperloglog_and_bitmaps() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6390)?;
+        let mut connection = client.get_connection()?;
+        let added: i64 = redis::cmd("PFADD")
+            .arg("visitors")
+            .arg(&["alice", "bob", "carol"])
+            .query(&mut connection)?;
+        assert_eq!(1, added);
+        let added: i64 = redis::cmd("PFADD")
+            .arg("visitors")
+            .arg("alice")
+            .query(&mut connection)?;
+        assert_eq!(0, added);
+        redis::cmd("PFADD")
+            .arg("others")
+            .arg(&["dave", "alice"])
+            .query::<()>(&mut connection)?;
+        let count: i64 = redis::cmd("PFCOUNT")
+            .arg("visitors")
+            .query(&mut connection)?;
+        assert_eq!(3, count);
+        let count: i64 = redis::cmd("PFCOUNT")
+            .arg(&["visitors", "others"])
+            .query(&mut connection)?;
+        assert_eq!(4, count);
+        redis::cmd("PFMERGE")
+            .arg(&["merged", "visitors", "others"])
+            .query::<()>(&mut connection)?;
+        let dump: Vec<u8> = connection.get("merged")?;
+        assert_eq!(b"HYLL\x01", &dump[..5]);
+        connection.set::<_, _, ()>("copy", dump)?;
+        let count: i64 = redis::cmd("PFCOUNT").arg("copy").query(&mut connection)?;
+        assert_eq!(4, count);
+        connection.set::<_, _, ()>("plain", "value")?;
+        let err = redis::cmd("PFCOUNT")
+            .arg("plain")
+            .query::<i64>(&mut connection)
+            .unwrap_err();
+        assert_eq!(Some("WRONGTYPE"), err.code());
+
+        let old: i64 = redis::cmd("SETBIT")
+            .arg("bits")
+            .arg(7)
+            .arg(1)
+            .query(&mut connection)?;
+        assert_eq!(0, old);
+        redis::cmd("SETBIT")
+            .arg("bits")
+            .arg(9)
+            .arg(1)
+            .query::<()>(&mut connection)?;
+        let bytes: Vec<u8> = connection.get("bits")?;
+        assert_eq!(vec![0x01, 0x40], bytes);
+        let bit: i64 = redis::cmd("GETBIT")
+            .arg("bits")
+            .arg(9)
+            .query(&mut connection)?;
+        assert_eq!(1, bit);
+        let count: i64 = redis::cmd("BITCOUNT").arg("bits").query(&mut connection)?;
+        assert_eq!(2, count);
+        let position: i64 = redis::cmd("BITPOS")
+            .arg("bits")
+            .arg(1)
+            .query(&mut connection)?;
+        assert_eq!(7, position);
+        connection.set::<_, _, ()>("mask", vec![0xffu8])?;
+        let len: i64 = redis::cmd("BITOP")
+            .arg(&["AND", "result", "bits", "mask"])
+            .query(&mut connection)?;
+        assert_eq!(2, len);
+        let bytes: Vec<u8> = connection.get("result")?;
+        assert_eq!(vec![0x01, 0x00], bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn geo() -> Result<(), Box<dyn Error>> {
+        let (_server, client) = start_server(6391)?;
+        let mut connection = client.get_connection()?;
+        let added: i64 = redis::cmd("GEOADD")
+            .arg("Sicily")
+            .arg(&["13.361389", "38.115556", "Palermo"])
+            .arg(&["15.087269", "37.502669", "Catania"])
+            .query(&mut connection)?;
+        assert_eq!(2, added);
+        let distance: String = redis::cmd("GEODIST")
+            .arg(&["Sicily", "Palermo", "Catania", "km"])
+            .query(&mut connection)?;
+        assert_eq!("166.2742", distance);
+        let hashes: Vec<String> = redis::cmd("GEOHASH")
+            .arg(&["Sicily", "Palermo", "Catania"])
+            .query(&mut connection)?;
+        assert_eq!(vec!["sqc8b49rny0", "sqdtr74hyu0"], hashes);
+        let positions: Vec<Option<(f64, f64)>> = redis::cmd("GEOPOS")
+            .arg(&["Sicily", "Palermo", "Unknown"])
+            .query(&mut connection)?;
+        let (longitude, latitude) = positions[0].unwrap();
+        assert!((longitude - 13.361389).abs() < 1e-5 && (latitude - 38.115556).abs() < 1e-5);
+        assert_eq!(None, positions[1]);
+
+        let members: Vec<String> = redis::cmd("GEOSEARCH")
+            .arg(&[
+                "Sicily",
+                "FROMLONLAT",
+                "15",
+                "37",
+                "BYRADIUS",
+                "200",
+                "km",
+                "ASC",
+            ])
+            .query(&mut connection)?;
+        assert_eq!(vec!["Catania", "Palermo"], members);
+        let results: Vec<Vec<String>> = redis::cmd("GEOSEARCH")
+            .arg(&[
+                "Sicily",
+                "FROMMEMBER",
+                "Palermo",
+                "BYBOX",
+                "400",
+                "400",
+                "km",
+            ])
+            .arg(&["DESC", "COUNT", "1", "WITHDIST"])
+            .query(&mut connection)?;
+        assert_eq!(
+            vec![vec![String::from("Catania"), String::from("166.2742")]],
+            results
+        );
+        let err = redis::cmd("GEOADD")
+            .arg(&["Sicily", "0", "90", "Pole"])
+            .query::<i64>(&mut connection)
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid longitude,latitude pair"));
+        Ok(())
+    }
+
+    #[test]
+    fn client_library() -> Result<(), Box<dyn Error>> {
+        let _server = start_server(6392)?;
+        let mut client = Client::connect("127.0.0.1:6392")?;
+        assert_eq!("PONG", client.ping()?);
+        assert_eq!(b"\x00binary".to_vec(), client.echo(b"\x00binary")?);
+        client.set("key", b"value")?;
+        assert_eq!(Some(b"value".to_vec()), client.get("key")?);
+        assert_eq!(None, client.get("missing")?);
+        assert!(client.expire("key", 100)?);
+        assert_eq!(100, client.ttl("key")?);
+        assert_eq!(1, client.del(&["key", "missing"])?);
+        let err = client.query::<(), _>(&["GET"]).unwrap_err();
+        assert!(matches!(err, ClientError::Server(_)));
+
+        let replies = client
+            .pipeline()
+            .command(&["SET", "counter", "1"])
+            .command(&["GET", "counter"])
+            .command(&["DBSIZE"])
+            .execute()?;
+        assert_eq!(
+            vec![Resp::ok(), Resp::BulkString("1".into()), Resp::Integer(1)],
+            replies
+        );
+        client.select(1)?;
+        assert_eq!(0, client.dbsize()?);
+        Ok(())
+    }
+
+    #[test]
+    fn dump_and_restore() -> Result<(), Box<dyn Error>> {
+        let _server = start_server(6395)?;
+        let mut client = Client::connect("127.0.0.1:6395")?;
+        client.set("number", b"12345")?;
+        client.set("text", b"hello")?;
+        assert_eq!(
+            None,
+            client.query::<Option<Vec<u8>>, _>(&["DUMP", "missing"])?
+        );
+        let payload: Vec<u8> = client.query(&["DUMP", "number"])?;
+        assert_eq!(b"\x00\xc1\x39\x30".to_vec(), payload[..4].to_vec());
+
+        let busy = client.query::<(), _>(&[b"RESTORE".as_slice(), b"text", b"0", &payload]);
+        assert_eq!(
+            "BUSYKEY Target key name already exists.",
+            busy.unwrap_err().to_string()
+        );
+        let restore = [b"RESTORE".as_slice(), b"text", b"0", &payload, b"REPLACE"];
+        client.query::<(), _>(&restore)?;
+        assert_eq!(Some(b"12345".to_vec()), client.get("text")?);
+
+        client.query::<(), _>(&[b"RESTORE".as_slice(), b"ttl", b"50000", &payload])?;
+        let ttl = client.ttl("ttl")?;
+        assert!((49..=50).contains(&ttl));
+        let restore = [
+            b"RESTORE".as_slice(),
+            b"expired",
+            b"1000",
+            &payload,
+            b"ABSTTL",
+        ];
+        client.query::<(), _>(&restore)?;
+        assert_eq!(None, client.get("expired")?);
+
+        let mut corrupted = payload.clone();
+        corrupted[1] ^= 1;
+        let err = client.query::<(), _>(&[b"RESTORE".as_slice(), b"bad", b"0", &corrupted]);
+        assert_eq!(
+            "ERR DUMP payload version or checksum are wrong",
+            err.unwrap_err().to_string()
+        );
+
+        let restore = [
+            b"RESTORE".as_slice(),
+            b"idle",
+            b"0",
+            &payload,
+            b"IDLETIME",
+            b"1000",
+        ];
+        client.query::<(), _>(&restore)?;
+        assert_eq!(
+            1000,
+            client.query::<i64, _>(&["OBJECT", "IDLETIME", "idle"])?
+        );
+        let restore = [
+            b"RESTORE".as_slice(),
+            b"freq",
+            b"0",
+            &payload,
+            b"FREQ",
+            b"100",
+        ];
+        client.query::<(), _>(&restore)?;
+        assert_eq!(100, client.query::<i64, _>(&["OBJECT", "FREQ", "freq"])?);
+
+        let geoadd = ["GEOADD", "zset", "13.361389", "38.115556", "Palermo"];
+        client.query::<(), _>(&geoadd)?;
+        client.query::<(), _>(&["GEOADD", "zset", "15.087269", "37.502669", "Catania"])?;
+        let payload: Vec<u8> = client.query(&["DUMP", "zset"])?;
+        client.query::<(), _>(&[b"RESTORE".as_slice(), b"zcopy", b"0", &payload])?;
+        assert_eq!(
+            "166.2742",
+            client.query::<String, _>(&["GEODIST", "zcopy", "Palermo", "Catania", "km"])?
+        );
+
+        let encoding = |client: &mut Client, key: &str| -> Result<String, ClientError> {
+            client.query(&["OBJECT", "ENCODING", key])
+        };
+        assert_eq!("int", encoding(&mut client, "number")?);
+        client.set("short", b"hello")?;
+        assert_eq!("embstr", encoding(&mut client, "short")?);
+        client.set("long", &[b'x'; 45])?;
+        assert_eq!("raw", encoding(&mut client, "long")?);
+        assert_eq!("listpack", encoding(&mut client, "zset")?);
+        assert_eq!(
+            Resp::Null,
+            client.request(&["OBJECT", "ENCODING", "missing"])?
+        );
+        assert_eq!(
+            0,
+            client.query::<i64, _>(&["OBJECT", "IDLETIME", "number"])?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn migrate() -> Result<(), Box<dyn Error>> {
+        let _source = start_server(6396)?;
+        let _target = start_server(6397)?;
+        let mut source = Client::connect("127.0.0.1:6396")?;
+        let mut target = Client::connect("127.0.0.1:6397")?;
+        source.set("a", b"1")?;
+        source.set("b", b"2")?;
+        source.set("c", b"3")?;
+        source.expire("a", 100)?;
+
+        let reply = source.request(&["MIGRATE", "127.0.0.1", "6397", "a", "0", "1000"])?;
+        assert_eq!(Resp::ok(), reply);
+        assert_eq!(None, source.get("a")?);
+        assert_eq!(Some(b"1".to_vec()), target.get("a")?);
+        assert!(target.ttl("a")? > 0);
+
+        let migrate = [
+            "MIGRATE",
+            "127.0.0.1",
+            "6397",
+            "",
+            "1",
+            "1000",
+            "COPY",
+            "KEYS",
+            "b",
+            "c",
+        ];
+        assert_eq!(Resp::ok(), source.request(&migrate)?);
+        assert_eq!(Some(b"2".to_vec()), source.get("b")?);
+        target.select(1)?;
+        assert_eq!(2, target.dbsize()?);
+
+        let reply = source.request(&["MIGRATE", "127.0.0.1", "6397", "b", "1", "1000"])?;
+        assert_eq!(
+            Resp::SimpleError(String::from(
+                "ERR Target instance replied with error: BUSYKEY Target key name already exists."
+            )),
+            reply
+        );
+        assert_eq!(Some(b"2".to_vec()), source.get("b")?);
+        let migrate = ["MIGRATE", "127.0.0.1", "6397", "b", "1", "1000", "REPLACE"];
+        assert_eq!(Resp::ok(), source.request(&migrate)?);
+        assert_eq!(None, source.get("b")?);
+
+        let reply = source.request(&["MIGRATE", "127.0.0.1", "6397", "missing", "0", "1000"])?;
+        assert_eq!(Resp::SimpleString(String::from("NOKEY")), reply);
+        let reply = source.request(&["MIGRATE", "127.0.0.1", "1", "c", "0", "100"])?;
+        assert!(matches!(reply, Resp::SimpleError(e) if e.starts_with("IOERR")));
+        Ok(())
+    }
+
+    #[test]
+    fn client_tracking() -> Result<(), Box<dyn Error>> {
+        let _server = start_server(6398)?;
+        let address = "127.0.0.1:6398".parse()?;
+        let connect = || Client::connect_timeout(&address, Duration::from_secs(5));
+        let invalidate = |keys: &[&str]| {
+            let keys = keys.iter().map(|key| Resp::BulkString((*key).into()));
+            Resp::Push(vec![
+                Resp::BulkString("invalidate".into()),
+                Resp::Array(keys.collect()),
+            ])
+        };
+        let mut tracked = connect()?;
+        let mut writer = connect()?;
+        let Resp::Map(hello) = tracked.request(&["HELLO", "3"])? else {
+            panic!("HELLO 3 should reply with a map");
+        };
+        assert!(hello.contains(&(Resp::BulkString("proto".into()), Resp::Integer(3))));
+        tracked.query::<(), _>(&["CLIENT", "TRACKING", "ON"])?;
+        tracked.get("key")?;
+        writer.set("key", b"value")?;
+        assert_eq!(invalidate(&["key"]), tracked.read_push()?);
+        writer.set("key", b"again")?;
+        assert_eq!(Some(b"again".to_vec()), tracked.get("key")?);
+
+        tracked.query::<(), _>(&["CLIENT", "TRACKING", "ON", "NOLOOP"])?;
+        tracked.set("key", b"mine")?;
+        assert_eq!("PONG", tracked.ping()?);
+        tracked.get("key")?;
+        writer.set("key", b"theirs")?;
+        assert_eq!("PONG", tracked.ping()?);
+        assert_eq!(vec![invalidate(&["key"])], tracked.take_pushes());
+        writer.query::<(), _>(&["FLUSHALL"])?;
+        let flushed = Resp::Push(vec![Resp::BulkString("invalidate".into()), Resp::Null]);
+        assert_eq!(flushed, tracked.read_push()?);
+
+        tracked.query::<(), _>(&["CLIENT", "TRACKING", "OFF"])?;
+        let bcast = ["CLIENT", "TRACKING", "ON", "BCAST", "PREFIX", "user:"];
+        tracked.query::<(), _>(&bcast)?;
+        writer.set("user:1", b"a")?;
+        writer.set("post:1", b"b")?;
+        writer.del(&["user:2", "user:1"])?;
+        assert_eq!(invalidate(&["user:1"]), tracked.read_push()?);
+        assert_eq!(invalidate(&["user:1"]), tracked.read_push()?);
+        let Resp::Map(info) = tracked.request(&["CLIENT", "TRACKINGINFO"])? else {
+            panic!("TRACKINGINFO should reply with a map in RESP3");
+        };
+        let flags = vec![
+            Resp::BulkString("on".into()),
+            Resp::BulkString("bcast".into()),
+        ];
+        assert_eq!(
+            (Resp::BulkString("flags".into()), Resp::Array(flags)),
+            info[0]
+        );
+
+        let mut optin = connect()?;
+        optin.query::<Resp, _>(&["HELLO", "3"])?;
+        let err = optin.query::<(), _>(&["CLIENT", "CACHING", "YES"]);
+        assert!(err.is_err());
+        optin.query::<(), _>(&["CLIENT", "TRACKING", "ON", "OPTIN"])?;
+        optin.get("a")?;
+        optin.query::<(), _>(&["CLIENT", "CACHING", "YES"])?;
+        optin.get("b")?;
+        writer.set("a", b"1")?;
+        writer.set("b", b"2")?;
+        assert_eq!(invalidate(&["b"]), optin.read_push()?);
+
+        let mut subscriber = connect()?;
+        let id: i64 = subscriber.query(&["CLIENT", "ID"])?;
+        subscriber.query::<Resp, _>(&["SUBSCRIBE", "__redis__:invalidate"])?;
+        let mut redirected = connect()?;
+        let missing = redirected.query::<(), _>(&["CLIENT", "TRACKING", "ON", "REDIRECT", "0"]);
+        assert_eq!(
+            "ERR The client ID you want redirect to does not exist",
+            missing.unwrap_err().to_string()
+        );
+        let redirect = ["CLIENT", "TRACKING", "ON", "REDIRECT", &id.to_string()];
+        redirected.query::<(), _>(&redirect)?;
+        assert_eq!(id, redirected.query::<i64, _>(&["CLIENT", "GETREDIR"])?);
+        redirected.get("c")?;
+        writer.set("c", b"3")?;
+        let message = Resp::Array(vec![
+            Resp::BulkString("message".into()),
+            Resp::BulkString("__redis__:invalidate".into()),
+            Resp::Array(vec![Resp::BulkString("c".into())]),
+        ]);
+        assert_eq!(message, subscriber.read_reply()?);
+        Ok(())
+    }
+
+    #[test]
+    fn inline_commands() -> Result<(), Box<dyn Error>> {
+        let _server = start_server(6393)?;
+        let mut stream = TcpStream::connect("127.0.0.1:6393")?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let read = |stream: &mut TcpStream, want: &[u8]| -> io::Result<Vec<u8>> {
+            let mut reply = Vec::new();
+            let mut buf = [0; 128];
+            while reply.len() < want.len() {
+                let size = stream.read(&mut buf)?;
+                if size == 0 {
+                    break;
+                }
+                reply.extend_from_slice(&buf[..size]);
+            }
+            Ok(reply)
+        };
+        stream.write_all(b"PING\r\n")?;
+        assert_eq!(b"+PONG\r\n".to_vec(), read(&mut stream, b"+PONG\r\n")?);
+        stream.write_all(b"set greeting \"hello world\"\nget greeting\r\n")?;
+        let want = b"+OK\r\n$11\r\nhello world\r\n";
+        assert_eq!(want.to_vec(), read(&mut stream, want)?);
+        stream.write_all(b"echo 'unbalanced\r\n")?;
+        let want = b"-ERR Protocol error: unbalanced quotes in request\r\n";
+        assert_eq!(want.to_vec(), read(&mut stream, want)?);
+        Ok(())
+    }
+
+    #[test]
+    fn split_frames() -> Result<(), Box<dyn Error>> {
+        let _server = start_server(6399)?;
+        let mut stream = TcpStream::connect("127.0.0.1:6399")?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        stream.write_all(b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$5\r\nhel")?;
+        thread::sleep(Duration::from_millis(50));
+        stream.write_all(b"lo\r\n*2\r\n$3\r\nGET\r\n$1\r\nk\r\n")?;
+        let want = b"+OK\r\n$5\r\nhello\r\n";
+        let mut reply = Vec::new();
+        let mut buf = [0; 128];
+        while reply.len() < want.len() {
+            let size = stream.read(&mut buf)?;
+            if size == 0 {
+                break;
+            }
+            reply.extend_from_slice(&buf[..size]);
+        }
+        assert_eq!(want.to_vec(), reply);
+
+        let mut client = Client::connect("127.0.0.1:6399")?;
+        let value = "v".repeat(100);
+        let mut pipeline = client.pipeline();
+        for i in 0..64 {
+            let key = format!("key:{i}");
+            pipeline.command(&["SET", &key, &value]);
+            pipeline.command(&["GET", &key]);
+        }
+        let replies = pipeline.execute()?;
+        assert_eq!(128, replies.len());
+        assert!(replies
+            .chunks(2)
+            .all(|pair| pair == [Resp::ok(), Resp::BulkString(value.clone().into())]));
+        Ok(())
+    }
+
+    #[test]
+    fn shutdown() -> Result<(), Box<dyn Error>> {
+        let (server, client) = start_server(6394)?;
+        let mut connection = client.get_connection()?;
+        let mut other = client.get_connection()?;
+        let err = redis::cmd("SHUTDOWN")
+            .arg("SAVE")
+            .query::<()>(&mut connection)
+            .unwrap_err();
+        assert!(err.to_string().contains("persistence is not supported"));
+        let err = redis::cmd("SHUTDOWN")
+            .arg("LATER")
+            .query::<()>(&mut connection)
+            .unwrap_err();
+        assert!(err.to_string().contains("syntax error"));
+        connection.set::<_, _, ()>("key", "value")?;
+
+        let mut stream = TcpStream::connect("127.0.0.1:6394")?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        stream.write_all(b"PING\r\nSHUTDOWN NOSAVE\r\nPING\r\n")?;
+        let mut replies = Vec::new();
+        stream.read_to_end(&mut replies)?;
+        assert_eq!(b"+PONG\r\n".as_slice(), replies);
+        assert!(redis::cmd("PING").query::<String>(&mut other).is_err());
+        drop(server);
+        assert!(TcpStream::connect("127.0.0.1:6394").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,30 @@
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub struct Session {
+    pub id: u64,
+    pub address: SocketAddr,
+    pub db: usize,
+    pub asking: bool,
+    pub protocol: u8,
+    pub caching: Option<bool>,
+    pub bus: bool,
+}
+
+impl Session {
+    pub fn new(address: SocketAddr) -> Self {
+        Self {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            address,
+            db: 0,
+            asking: false,
+            protocol: 2,
+            caching: None,
+            bus: false,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::{
+    collections::VecDeque,
+    net::SocketAddr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::resp::Resp;
+
+pub struct SlowLog {
+    entries: VecDeque<SlowLogEntry>,
+    next_id: i64,
+}
+
+pub struct SlowLogEntry {
+    id: i64,
+    timestamp: SystemTime,
+    duration: Duration,
+    args: Vec<String>,
+    client: SocketAddr,
+}
+
+impl Default for SlowLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlowLog {
+    const MAX_ARGS: usize = 32;
+
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        args: Vec<String>,
+        client: SocketAddr,
+        duration: Duration,
+        max_len: usize,
+    ) {
+        let mut args = args;
+        if args.len() > Self::MAX_ARGS {
+            let remaining = args.len() - Self::MAX_ARGS + 1;
+            args.truncate(Self::MAX_ARGS - 1);
+            args.push(format!("... ({remaining} more arguments)"));
+        }
+        self.entries.push_front(SlowLogEntry {
+            id: self.next_id,
+            timestamp: SystemTime::now(),
+            duration,
+            args,
+            client,
+        });
+        self.next_id += 1;
+        self.entries.truncate(max_len);
+    }
+
+    pub fn get(&self, count: usize) -> Vec<&SlowLogEntry> {
+        self.entries.iter().take(count).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn reset(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl From<&SlowLogEntry> for Resp {
+    fn from(value: &SlowLogEntry) -> Self {
+        let timestamp = value
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Resp::Array(vec![
+            Resp::Integer(value.id),
+            Resp::Integer(timestamp as i64),
+            Resp::Integer(value.duration.as_micros() as i64),
+            Resp::Array(
+                value
+                    .args
+                    .iter()
+                    .map(|arg| Resp::BulkString(arg.clone().into()))
+                    .collect(),
+            ),
+            Resp::BulkString(value.client.to_string().into()),
+            Resp::BulkString(Vec::new()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_and_newest_first() {
+        let client = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let mut slowlog = SlowLog::new();
+        for i in 0..5 {
+            slowlog.push(vec![i.to_string()], client, Duration::from_micros(i), 3);
+        }
+        assert_eq!(3, slowlog.len());
+        let ids: Vec<i64> = slowlog.get(10).iter().map(|e| e.id).collect();
+        assert_eq!(vec![4, 3, 2], ids);
+        slowlog.reset();
+        assert!(slowlog.is_empty());
+    }
+
+    #[test]
+    fn truncates_arguments() {
+        let client = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let mut slowlog = SlowLog::new();
+        let args = (0..40).map(|i| i.to_string()).collect();
+        slowlog.push(args, client, Duration::ZERO, 10);
+        let entry = slowlog.get(1)[0];
+        assert_eq!(SlowLog::MAX_ARGS, entry.args.len());
+        assert_eq!("... (9 more arguments)", entry.args[SlowLog::MAX_ARGS - 1]);
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+};
+
+use crate::dictionary::MemoryUsage;
+
+#[derive(Debug, Clone, Copy)]
+struct Score(f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+#[derive(Default)]
+pub struct SortedSet {
+    scores: HashMap<String, f64>,
+    ordered: BTreeSet<(Score, String)>,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    pub fn insert(&mut self, member: String, score: f64) -> Option<f64> {
+        let old = self.scores.insert(member.clone(), score);
+        if let Some(old) = old {
+            self.ordered.remove(&(Score(old), member.clone()));
+        }
+        self.ordered.insert((Score(score), member));
+        old
+    }
+
+    pub fn remove(&mut self, member: &str) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.ordered.remove(&(Score(score), member.to_string()));
+        Some(score)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.ordered
+            .iter()
+            .map(|(score, member)| (member.as_str(), score.0))
+    }
+}
+
+impl MemoryUsage for SortedSet {
+    fn memory_usage(&self) -> usize {
+        self.scores
+            .keys()
+            .map(|member| 2 * member.capacity() + 2 * std::mem::size_of::<f64>())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_by_score() {
+        let mut set = SortedSet::new();
+        assert_eq!(None, set.insert(String::from("b"), 2.0));
+        assert_eq!(None, set.insert(String::from("a"), 3.0));
+        assert_eq!(None, set.insert(String::from("c"), 1.0));
+        assert_eq!(Some(3.0), set.insert(String::from("a"), 0.5));
+        let members: Vec<&str> = set.iter().map(|(member, _)| member).collect();
+        assert_eq!(vec!["a", "c", "b"], members);
+        assert_eq!(Some(1.0), set.remove("c"));
+        assert_eq!(None, set.score("c"));
+        assert_eq!(2, set.len());
+    }
+}
@@ -0,0 +1,836 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    ops::Bound,
+    str::FromStr,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    rdb::{self, Listpack, ListpackEntry, Reader, Writer},
+    resp::Resp,
+};
+
+const NODE_MAX_ENTRIES: usize = 100;
+const ITEM_FLAG_DELETED: i64 = 1;
+const ITEM_FLAG_SAMEFIELDS: i64 = 2;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+
+    fn parse(s: &str, missing_seq: u64) -> Result<StreamId, Resp> {
+        let (ms, seq) = match s.split_once('-') {
+            Some((ms, seq)) => (ms, seq.parse().map_err(|_| Resp::invalid_stream_id())?),
+            None => (s, missing_seq),
+        };
+        let ms = ms.parse().map_err(|_| Resp::invalid_stream_id())?;
+        Ok(StreamId { ms, seq })
+    }
+
+    fn next(&self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId::new(self.ms, seq)),
+            None => self.ms.checked_add(1).map(|ms| StreamId::new(ms, 0)),
+        }
+    }
+}
+
+impl Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = Resp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        StreamId::parse(s, 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub enum XAddId {
+    Auto,
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+impl FromStr for XAddId {
+    type Err = Resp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(XAddId::Auto);
+        }
+        match s.split_once('-') {
+            Some((ms, "*")) => ms
+                .parse()
+                .map(XAddId::AutoSeq)
+                .map_err(|_| Resp::invalid_stream_id()),
+            _ => Ok(XAddId::Explicit(s.parse()?)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub enum RangeBound {
+    Inclusive(StreamId),
+    Exclusive(StreamId),
+}
+
+impl RangeBound {
+    pub fn start(s: &str) -> Result<RangeBound, Resp> {
+        match s {
+            "-" => Ok(RangeBound::Inclusive(StreamId::MIN)),
+            "+" => Ok(RangeBound::Inclusive(StreamId::MAX)),
+            s => match s.strip_prefix('(') {
+                Some(id) => Ok(RangeBound::Exclusive(StreamId::parse(id, 0)?)),
+                None => Ok(RangeBound::Inclusive(StreamId::parse(s, 0)?)),
+            },
+        }
+    }
+
+    pub fn end(s: &str) -> Result<RangeBound, Resp> {
+        match s {
+            "-" => Ok(RangeBound::Inclusive(StreamId::MIN)),
+            "+" => Ok(RangeBound::Inclusive(StreamId::MAX)),
+            s => match s.strip_prefix('(') {
+                Some(id) => Ok(RangeBound::Exclusive(StreamId::parse(id, u64::MAX)?)),
+                None => Ok(RangeBound::Inclusive(StreamId::parse(s, u64::MAX)?)),
+            },
+        }
+    }
+
+    fn bound(&self) -> Bound<StreamId> {
+        match self {
+            RangeBound::Inclusive(id) => Bound::Included(*id),
+            RangeBound::Exclusive(id) => Bound::Excluded(*id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+pub enum ReadId {
+    Last,
+    Undelivered,
+    Id(StreamId),
+}
+
+impl FromStr for ReadId {
+    type Err = Resp;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "$" => Ok(ReadId::Last),
+            ">" => Ok(ReadId::Undelivered),
+            s => Ok(ReadId::Id(s.parse()?)),
+        }
+    }
+}
+
+pub type Fields = Vec<(String, String)>;
+
+#[derive(Default)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, Fields>,
+    last_id: StreamId,
+    groups: HashMap<String, ConsumerGroup>,
+}
+
+struct ConsumerGroup {
+    last_delivered: StreamId,
+    pending: BTreeMap<StreamId, PendingEntry>,
+    consumers: HashMap<String, Instant>,
+}
+
+struct PendingEntry {
+    consumer: String,
+    delivered_at: Instant,
+    deliveries: u64,
+}
+
+pub struct PendingSummary {
+    pub count: usize,
+    pub range: Option<(StreamId, StreamId)>,
+    pub consumers: Vec<(String, usize)>,
+}
+
+pub struct PendingDetail {
+    pub id: StreamId,
+    pub consumer: String,
+    pub idle: u128,
+    pub deliveries: u64,
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    pub fn add(
+        &mut self,
+        id: XAddId,
+        fields: Fields,
+        maxlen: Option<usize>,
+    ) -> Result<StreamId, Resp> {
+        let id = match id {
+            XAddId::Auto => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64;
+                if now > self.last_id.ms {
+                    StreamId::new(now, 0)
+                } else {
+                    self.last_id.next().ok_or_else(Resp::stream_id_too_small)?
+                }
+            }
+            XAddId::AutoSeq(ms) if ms == self.last_id.ms => {
+                self.last_id.next().ok_or_else(Resp::stream_id_too_small)?
+            }
+            XAddId::AutoSeq(ms) if ms > self.last_id.ms => StreamId::new(ms, 0),
+            XAddId::AutoSeq(_) => return Err(Resp::stream_id_too_small()),
+            XAddId::Explicit(id) => id,
+        };
+        if id == StreamId::MIN {
+            return Err(Resp::SimpleError(String::from(
+                "ERR The ID specified in XADD must be greater than 0-0",
+            )));
+        }
+        if id <= self.last_id {
+            return Err(Resp::stream_id_too_small());
+        }
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        if let Some(maxlen) = maxlen {
+            self.trim(maxlen);
+        }
+        Ok(id)
+    }
+
+    pub fn trim(&mut self, maxlen: usize) -> usize {
+        let mut removed = 0;
+        while self.entries.len() > maxlen {
+            self.entries.pop_first();
+            removed += 1;
+        }
+        removed
+    }
+
+    pub fn range(
+        &self,
+        start: RangeBound,
+        end: RangeBound,
+        count: Option<usize>,
+        rev: bool,
+    ) -> Vec<(StreamId, &Fields)> {
+        if is_empty_range(start, end) {
+            return Vec::new();
+        }
+        let range = self.entries.range((start.bound(), end.bound()));
+        let count = count.unwrap_or(usize::MAX);
+        if rev {
+            range.rev().take(count).map(|(id, f)| (*id, f)).collect()
+        } else {
+            range.take(count).map(|(id, f)| (*id, f)).collect()
+        }
+    }
+
+    pub fn read_after(&self, id: StreamId, count: Option<usize>) -> Vec<(StreamId, &Fields)> {
+        self.range(
+            RangeBound::Exclusive(id),
+            RangeBound::Inclusive(StreamId::MAX),
+            count,
+            false,
+        )
+    }
+
+    pub fn create_group(&mut self, group: String, id: ReadId) -> Result<(), Resp> {
+        if self.groups.contains_key(&group) {
+            return Err(Resp::SimpleError(String::from(
+                "BUSYGROUP Consumer Group name already exists",
+            )));
+        }
+        let last_delivered = match id {
+            ReadId::Id(id) => id,
+            _ => self.last_id,
+        };
+        self.groups.insert(
+            group,
+            ConsumerGroup {
+                last_delivered,
+                pending: BTreeMap::new(),
+                consumers: HashMap::new(),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn destroy_group(&mut self, group: &str) -> bool {
+        self.groups.remove(group).is_some()
+    }
+
+    pub fn has_group(&self, group: &str) -> bool {
+        self.groups.contains_key(group)
+    }
+
+    pub fn read_group(
+        &mut self,
+        group: &str,
+        consumer: &str,
+        id: ReadId,
+        count: Option<usize>,
+        noack: bool,
+    ) -> Result<Vec<(StreamId, Option<Fields>)>, Resp> {
+        let group = self
+            .groups
+            .get_mut(group)
+            .ok_or_else(|| Resp::no_group(group))?;
+        let now = Instant::now();
+        group.consumers.insert(consumer.to_string(), now);
+        let count = count.unwrap_or(usize::MAX);
+        match id {
+            ReadId::Undelivered => {
+                let entries: Vec<(StreamId, Option<Fields>)> = self
+                    .entries
+                    .range((Bound::Excluded(group.last_delivered), Bound::Unbounded))
+                    .take(count)
+                    .map(|(id, fields)| (*id, Some(fields.clone())))
+                    .collect();
+                for (id, _) in entries.iter() {
+                    group.last_delivered = *id;
+                    if !noack {
+                        let pending = PendingEntry {
+                            consumer: consumer.to_string(),
+                            delivered_at: now,
+                            deliveries: 1,
+                        };
+                        group.pending.insert(*id, pending);
+                    }
+                }
+                Ok(entries)
+            }
+            ReadId::Id(start) => {
+                let mut entries = Vec::new();
+                let history = group
+                    .pending
+                    .range_mut((Bound::Excluded(start), Bound::Unbounded))
+                    .filter(|(_, pending)| pending.consumer == consumer)
+                    .take(count);
+                for (id, pending) in history {
+                    pending.delivered_at = now;
+                    pending.deliveries += 1;
+                    entries.push((*id, self.entries.get(id).cloned()));
+                }
+                Ok(entries)
+            }
+            ReadId::Last => Err(Resp::SimpleError(String::from(
+                "ERR The $ ID is meaningless in the context of XREADGROUP",
+            ))),
+        }
+    }
+
+    pub fn ack(&mut self, group: &str, ids: &[StreamId]) -> usize {
+        match self.groups.get_mut(group) {
+            Some(group) => ids
+                .iter()
+                .filter(|id| group.pending.remove(id).is_some())
+                .count(),
+            None => 0,
+        }
+    }
+
+    pub fn pending_summary(&self, group: &str) -> Result<PendingSummary, Resp> {
+        let group = self
+            .groups
+            .get(group)
+            .ok_or_else(|| Resp::no_group(group))?;
+        let range = match (group.pending.keys().next(), group.pending.keys().last()) {
+            (Some(first), Some(last)) => Some((*first, *last)),
+            _ => None,
+        };
+        let mut consumers: BTreeMap<String, usize> = BTreeMap::new();
+        for pending in group.pending.values() {
+            *consumers.entry(pending.consumer.clone()).or_default() += 1;
+        }
+        Ok(PendingSummary {
+            count: group.pending.len(),
+            range,
+            consumers: consumers.into_iter().collect(),
+        })
+    }
+
+    pub fn pending_details(
+        &self,
+        group: &str,
+        start: RangeBound,
+        end: RangeBound,
+        count: usize,
+        consumer: Option<&str>,
+        min_idle: Option<u128>,
+    ) -> Result<Vec<PendingDetail>, Resp> {
+        let group = self
+            .groups
+            .get(group)
+            .ok_or_else(|| Resp::no_group(group))?;
+        if is_empty_range(start, end) {
+            return Ok(Vec::new());
+        }
+        let details = group
+            .pending
+            .range((start.bound(), end.bound()))
+            .filter(|(_, p)| consumer.is_none_or(|c| p.consumer == c))
+            .map(|(id, p)| PendingDetail {
+                id: *id,
+                consumer: p.consumer.clone(),
+                idle: p.delivered_at.elapsed().as_millis(),
+                deliveries: p.deliveries,
+            })
+            .filter(|d| min_idle.is_none_or(|idle| d.idle >= idle))
+            .take(count)
+            .collect();
+        Ok(details)
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.entries
+            .values()
+            .flat_map(|fields| fields.iter())
+            .map(|(field, value)| field.capacity() + value.capacity())
+            .sum()
+    }
+
+    pub fn save(&self, writer: &mut Writer) {
+        let entries: Vec<(&StreamId, &Fields)> = self.entries.iter().collect();
+        let nodes = entries.chunks(NODE_MAX_ENTRIES);
+        writer.length(nodes.len() as u64);
+        for node in nodes {
+            let (master_id, master_fields) = node[0];
+            writer.string(&id_to_bytes(*master_id));
+            let mut listpack = Listpack::new();
+            listpack.integer(node.len() as i64);
+            listpack.integer(0);
+            listpack.integer(master_fields.len() as i64);
+            for (field, _) in master_fields {
+                listpack.string(field.as_bytes());
+            }
+            listpack.integer(0);
+            for (id, fields) in node {
+                let same_fields = fields.len() == master_fields.len()
+                    && fields
+                        .iter()
+                        .zip(master_fields.iter())
+                        .all(|(field, master)| field.0 == master.0);
+                let flags = if same_fields { ITEM_FLAG_SAMEFIELDS } else { 0 };
+                listpack.integer(flags);
+                listpack.integer(id.ms.wrapping_sub(master_id.ms) as i64);
+                listpack.integer(id.seq.wrapping_sub(master_id.seq) as i64);
+                if same_fields {
+                    for (_, value) in fields.iter() {
+                        listpack.string(value.as_bytes());
+                    }
+                    listpack.integer(fields.len() as i64 + 3);
+                } else {
+                    listpack.integer(fields.len() as i64);
+                    for (field, value) in fields.iter() {
+                        listpack.string(field.as_bytes());
+                        listpack.string(value.as_bytes());
+                    }
+                    listpack.integer(fields.len() as i64 * 2 + 4);
+                }
+            }
+            writer.string(&listpack.into_bytes());
+        }
+        let first_id = self.entries.keys().next().copied().unwrap_or_default();
+        for length in [
+            self.entries.len() as u64,
+            self.last_id.ms,
+            self.last_id.seq,
+            first_id.ms,
+            first_id.seq,
+            0,
+            0,
+            self.entries.len() as u64,
+            self.groups.len() as u64,
+        ] {
+            writer.length(length);
+        }
+        for (name, group) in self.groups.iter() {
+            writer.string(name.as_bytes());
+            writer.length(group.last_delivered.ms);
+            writer.length(group.last_delivered.seq);
+            let entries_read = self.entries.range(..=group.last_delivered).count();
+            writer.length(entries_read as u64);
+            writer.length(group.pending.len() as u64);
+            for (id, pending) in group.pending.iter() {
+                writer.raw(&id_to_bytes(*id));
+                writer.millis(unix_millis(pending.delivered_at));
+                writer.length(pending.deliveries);
+            }
+            writer.length(group.consumers.len() as u64);
+            for (consumer, seen) in group.consumers.iter() {
+                writer.string(consumer.as_bytes());
+                writer.millis(unix_millis(*seen));
+                writer.millis(unix_millis(*seen));
+                let ids: Vec<&StreamId> = group
+                    .pending
+                    .iter()
+                    .filter(|(_, pending)| pending.consumer == *consumer)
+                    .map(|(id, _)| id)
+                    .collect();
+                writer.length(ids.len() as u64);
+                for id in ids {
+                    writer.raw(&id_to_bytes(*id));
+                }
+            }
+        }
+    }
+
+    pub fn load(reader: &mut Reader, version: u8) -> Result<Stream, Resp> {
+        let mut stream = Stream::new();
+        for _ in 0..reader.length()? {
+            let master_id = id_from_bytes(&reader.string()?)?;
+            let listpack = rdb::parse_listpack(&reader.string()?)?;
+            let mut listpack = listpack.into_iter();
+            let count = next_integer(&mut listpack)?;
+            let deleted = next_integer(&mut listpack)?;
+            let master_fields = (0..next_integer(&mut listpack)?)
+                .map(|_| next_string(&mut listpack))
+                .collect::<Result<Vec<String>, Resp>>()?;
+            next_integer(&mut listpack)?;
+            for _ in 0..count.saturating_add(deleted) {
+                let flags = next_integer(&mut listpack)?;
+                let ms = master_id
+                    .ms
+                    .wrapping_add(next_integer(&mut listpack)? as u64);
+                let seq = master_id
+                    .seq
+                    .wrapping_add(next_integer(&mut listpack)? as u64);
+                let fields = if flags & ITEM_FLAG_SAMEFIELDS != 0 {
+                    master_fields
+                        .iter()
+                        .map(|field| Ok((field.clone(), next_string(&mut listpack)?)))
+                        .collect::<Result<Fields, Resp>>()?
+                } else {
+                    (0..next_integer(&mut listpack)?)
+                        .map(|_| Ok((next_string(&mut listpack)?, next_string(&mut listpack)?)))
+                        .collect::<Result<Fields, Resp>>()?
+                };
+                next_integer(&mut listpack)?;
+                if flags & ITEM_FLAG_DELETED == 0 {
+                    stream.entries.insert(StreamId::new(ms, seq), fields);
+                }
+            }
+            if listpack.next().is_some() {
+                return Err(rdb::bad_format());
+            }
+        }
+        reader.length()?;
+        stream.last_id = StreamId::new(reader.length()?, reader.length()?);
+        if version >= 2 {
+            for _ in 0..5 {
+                reader.length()?;
+            }
+        }
+        for _ in 0..reader.length()? {
+            let name = String::from_utf8_lossy(&reader.string()?).into_owned();
+            let last_delivered = StreamId::new(reader.length()?, reader.length()?);
+            if version >= 2 {
+                reader.length()?;
+            }
+            let mut pending = BTreeMap::new();
+            for _ in 0..reader.length()? {
+                let id = id_from_bytes(reader.raw(16)?)?;
+                let entry = PendingEntry {
+                    consumer: String::new(),
+                    delivered_at: instant_from_unix_millis(reader.millis()?),
+                    deliveries: reader.length()?,
+                };
+                pending.insert(id, entry);
+            }
+            let mut consumers = HashMap::new();
+            for _ in 0..reader.length()? {
+                let consumer = String::from_utf8_lossy(&reader.string()?).into_owned();
+                let seen = instant_from_unix_millis(reader.millis()?);
+                if version >= 3 {
+                    reader.millis()?;
+                }
+                for _ in 0..reader.length()? {
+                    let id = id_from_bytes(reader.raw(16)?)?;
+                    let entry = pending.get_mut(&id).ok_or_else(rdb::bad_format)?;
+                    entry.consumer = consumer.clone();
+                }
+                consumers.insert(consumer, seen);
+            }
+            if pending.values().any(|entry| entry.consumer.is_empty()) {
+                return Err(rdb::bad_format());
+            }
+            let group = ConsumerGroup {
+                last_delivered,
+                pending,
+                consumers,
+            };
+            stream.groups.insert(name, group);
+        }
+        Ok(stream)
+    }
+}
+
+fn id_to_bytes(id: StreamId) -> [u8; 16] {
+    let mut bytes = [0; 16];
+    bytes[..8].copy_from_slice(&id.ms.to_be_bytes());
+    bytes[8..].copy_from_slice(&id.seq.to_be_bytes());
+    bytes
+}
+
+fn id_from_bytes(bytes: &[u8]) -> Result<StreamId, Resp> {
+    if bytes.len() != 16 {
+        return Err(rdb::bad_format());
+    }
+    let mut ms = [0; 8];
+    let mut seq = [0; 8];
+    ms.copy_from_slice(&bytes[..8]);
+    seq.copy_from_slice(&bytes[8..]);
+    Ok(StreamId::new(
+        u64::from_be_bytes(ms),
+        u64::from_be_bytes(seq),
+    ))
+}
+
+fn next_integer(listpack: &mut impl Iterator<Item = ListpackEntry>) -> Result<i64, Resp> {
+    listpack.next().ok_or_else(rdb::bad_format)?.integer()
+}
+
+fn next_string(listpack: &mut impl Iterator<Item = ListpackEntry>) -> Result<String, Resp> {
+    let entry = listpack.next().ok_or_else(rdb::bad_format)?;
+    Ok(String::from_utf8_lossy(&entry.into_bytes()).into_owned())
+}
+
+fn unix_millis(instant: Instant) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    now.saturating_sub(instant.elapsed()).as_millis() as u64
+}
+
+fn instant_from_unix_millis(millis: u64) -> Instant {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let elapsed = std::time::Duration::from_millis(now.saturating_sub(millis));
+    Instant::now()
+        .checked_sub(elapsed)
+        .unwrap_or_else(Instant::now)
+}
+
+fn is_empty_range(start: RangeBound, end: RangeBound) -> bool {
+    match (start, end) {
+        (RangeBound::Inclusive(start), RangeBound::Inclusive(end)) => start > end,
+        (
+            RangeBound::Inclusive(start) | RangeBound::Exclusive(start),
+            RangeBound::Inclusive(end) | RangeBound::Exclusive(end),
+        ) => start >= end,
+    }
+}
+
+pub fn entry_to_resp(id: StreamId, fields: Option<&Fields>) -> Resp {
+    let fields = match fields {
+        Some(fields) => Resp::Array(
+            fields
+                .iter()
+                .flat_map(|(field, value)| {
+                    [
+                        Resp::BulkString(field.clone().into()),
+                        Resp::BulkString(value.clone().into()),
+                    ]
+                })
+                .collect(),
+        ),
+        None => Resp::Null,
+    };
+    Resp::Array(vec![Resp::BulkString(id.to_string().into()), fields])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Value;
+
+    fn fields(value: &str) -> Fields {
+        vec![(String::from("field"), value.to_string())]
+    }
+
+    #[test]
+    fn parse_ids() -> Result<(), Resp> {
+        assert_eq!(StreamId::new(5, 0), "5".parse()?);
+        assert_eq!(StreamId::new(5, 3), "5-3".parse()?);
+        assert_eq!(XAddId::AutoSeq(5), "5-*".parse()?);
+        assert_eq!(
+            RangeBound::Inclusive(StreamId::new(5, u64::MAX)),
+            RangeBound::end("5")?
+        );
+        assert_eq!(
+            RangeBound::Exclusive(StreamId::new(5, 1)),
+            RangeBound::start("(5-1")?
+        );
+        assert!("5-x".parse::<StreamId>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn ids_increase_monotonically() -> Result<(), Resp> {
+        let mut stream = Stream::new();
+        assert_eq!(
+            StreamId::new(1, 1),
+            stream.add("1-1".parse()?, fields("a"), None)?
+        );
+        assert_eq!(
+            StreamId::new(1, 2),
+            stream.add("1-*".parse()?, fields("b"), None)?
+        );
+        assert!(stream.add("1-2".parse()?, fields("c"), None).is_err());
+        assert!(stream.add("0-0".parse()?, fields("c"), None).is_err());
+        let auto = stream.add(XAddId::Auto, fields("c"), None)?;
+        assert!(auto > StreamId::new(1, 2));
+        stream.add(XAddId::Auto, fields("d"), Some(2))?;
+        assert_eq!(2, stream.len());
+        Ok(())
+    }
+
+    #[test]
+    fn range_queries() -> Result<(), Resp> {
+        let mut stream = Stream::new();
+        for i in 1..=5 {
+            stream.add(XAddId::Explicit(StreamId::new(i, 0)), fields("v"), None)?;
+        }
+        let ids = |entries: Vec<(StreamId, &Fields)>| -> Vec<u64> {
+            entries.into_iter().map(|(id, _)| id.ms).collect()
+        };
+        let all = stream.range(RangeBound::start("-")?, RangeBound::end("+")?, None, false);
+        assert_eq!(vec![1, 2, 3, 4, 5], ids(all));
+        let some = stream.range(RangeBound::start("(2")?, RangeBound::end("4")?, None, false);
+        assert_eq!(vec![3, 4], ids(some));
+        let rev = stream.range(
+            RangeBound::start("-")?,
+            RangeBound::end("+")?,
+            Some(2),
+            true,
+        );
+        assert_eq!(vec![5, 4], ids(rev));
+        assert!(stream
+            .range(RangeBound::start("4")?, RangeBound::end("2")?, None, false)
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn consumer_groups() -> Result<(), Resp> {
+        let mut stream = Stream::new();
+        for i in 1..=3 {
+            stream.add(XAddId::Explicit(StreamId::new(i, 0)), fields("v"), None)?;
+        }
+        stream.create_group(String::from("group"), ReadId::Id(StreamId::MIN))?;
+        assert!(stream
+            .create_group(String::from("group"), ReadId::Last)
+            .is_err());
+
+        let read = stream.read_group("group", "alice", ReadId::Undelivered, Some(2), false)?;
+        assert_eq!(2, read.len());
+        let read = stream.read_group("group", "bob", ReadId::Undelivered, None, false)?;
+        assert_eq!(
+            vec![StreamId::new(3, 0)],
+            read.iter().map(|e| e.0).collect::<Vec<_>>()
+        );
+
+        let summary = stream.pending_summary("group")?;
+        assert_eq!(3, summary.count);
+        assert_eq!(
+            vec![(String::from("alice"), 2), (String::from("bob"), 1)],
+            summary.consumers
+        );
+
+        let history =
+            stream.read_group("group", "alice", ReadId::Id(StreamId::MIN), None, false)?;
+        assert_eq!(2, history.len());
+        let details = stream.pending_details(
+            "group",
+            RangeBound::start("-")?,
+            RangeBound::end("+")?,
+            10,
+            Some("alice"),
+            None,
+        )?;
+        assert_eq!(2, details[0].deliveries);
+
+        assert_eq!(
+            1,
+            stream.ack("group", &[StreamId::new(1, 0), StreamId::new(9, 0)])
+        );
+        assert_eq!(2, stream.pending_summary("group")?.count);
+        Ok(())
+    }
+
+    #[test]
+    fn save_and_load() -> Result<(), Resp> {
+        let mut stream = Stream::new();
+        for i in 1..=150 {
+            let fields = if i % 2 == 0 {
+                fields(&i.to_string())
+            } else {
+                vec![(String::from("other"), String::from("x"))]
+            };
+            stream.add(XAddId::Explicit(StreamId::new(i, i)), fields, None)?;
+        }
+        stream.create_group(String::from("group"), ReadId::Id(StreamId::MIN))?;
+        stream.read_group("group", "alice", ReadId::Undelivered, Some(3), false)?;
+        stream.ack("group", &[StreamId::new(2, 2)]);
+
+        let loaded = match rdb::restore(&rdb::dump(&Value::Stream(stream)))? {
+            Value::Stream(loaded) => loaded,
+            _ => panic!("expected a stream"),
+        };
+        assert_eq!(150, loaded.len());
+        assert_eq!(StreamId::new(150, 150), loaded.last_id());
+        let all = loaded.range(RangeBound::start("-")?, RangeBound::end("+")?, None, false);
+        assert_eq!((StreamId::new(4, 4), &fields("4")), all[3]);
+        assert_eq!(
+            (
+                StreamId::new(5, 5),
+                &vec![(String::from("other"), String::from("x"))]
+            ),
+            all[4]
+        );
+        let summary = loaded.pending_summary("group")?;
+        assert_eq!(2, summary.count);
+        assert_eq!(vec![(String::from("alice"), 2)], summary.consumers);
+        Ok(())
+    }
+}
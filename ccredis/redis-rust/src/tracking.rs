@@ -0,0 +1,343 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    net::SocketAddr,
+};
+
+use crate::{command::TrackingOptions, resp::Resp};
+
+#[derive(Default)]
+pub struct Tracking {
+    clients: HashMap<SocketAddr, TrackingClient>,
+    keys: HashMap<String, HashSet<SocketAddr>>,
+}
+
+struct TrackingClient {
+    options: TrackingOptions,
+    redirect: Option<SocketAddr>,
+    broken_redirect: bool,
+}
+
+impl TrackingClient {
+    fn target(&self, client: SocketAddr) -> Option<SocketAddr> {
+        if self.broken_redirect {
+            return None;
+        }
+        Some(self.redirect.unwrap_or(client))
+    }
+
+    fn matches(&self, key: &str) -> bool {
+        self.options.prefixes.is_empty()
+            || self
+                .options
+                .prefixes
+                .iter()
+                .any(|prefix| key.starts_with(prefix.as_str()))
+    }
+}
+
+impl Tracking {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(
+        &mut self,
+        client: SocketAddr,
+        redirect: Option<SocketAddr>,
+        mut options: TrackingOptions,
+    ) -> Result<(), Resp> {
+        let error = |message: &str| Err(Resp::SimpleError(format!("ERR {message}")));
+        if !options.bcast && !options.prefixes.is_empty() {
+            return error("PREFIX option requires BCAST mode to be enabled");
+        }
+        if options.bcast && (options.optin || options.optout) {
+            return error("OPTIN and OPTOUT are not compatible with BCAST");
+        }
+        if options.optin && options.optout {
+            return error("You can't use both OPTIN and OPTOUT");
+        }
+        let existing = self.clients.get(&client).map(|c| &c.options);
+        if let Some(existing) = existing {
+            if existing.optin && options.optout || existing.optout && options.optin {
+                return error("You can't switch OPTIN/OPTOUT mode before disabling tracking for this client, and then re-enabling it with a different mode.");
+            }
+            if existing.bcast != options.bcast {
+                return error("You can't switch BCAST mode on/off before disabling tracking for this client, and then re-enabling it with a different mode.");
+            }
+        }
+        let existing_prefixes = existing.map_or(&[][..], |options| &options.prefixes);
+        for (i, prefix) in options.prefixes.iter().enumerate() {
+            let overlapping = existing_prefixes
+                .iter()
+                .chain(&options.prefixes[i + 1..])
+                .find(|other| prefix.starts_with(other.as_str()) || other.starts_with(prefix));
+            if let Some(other) = overlapping {
+                return error(&format!("Prefix '{prefix}' overlaps with an existing prefix '{other}'. Prefixes for a single client must not overlap."));
+            }
+        }
+        options.prefixes.extend(existing_prefixes.iter().cloned());
+        let client_state = TrackingClient {
+            options,
+            redirect,
+            broken_redirect: false,
+        };
+        self.clients.insert(client, client_state);
+        Ok(())
+    }
+
+    pub fn disable(&mut self, client: &SocketAddr) {
+        if self.clients.remove(client).is_some() {
+            self.keys.retain(|_, clients| {
+                clients.remove(client);
+                !clients.is_empty()
+            });
+        }
+    }
+
+    pub fn disconnect(&mut self, client: &SocketAddr) -> Vec<SocketAddr> {
+        self.disable(client);
+        let mut broken = Vec::new();
+        for (address, tracking) in self.clients.iter_mut() {
+            if tracking.redirect == Some(*client) && !tracking.broken_redirect {
+                tracking.broken_redirect = true;
+                broken.push(*address);
+            }
+        }
+        broken
+    }
+
+    pub fn check_caching(&self, client: &SocketAddr, yes: bool) -> Result<(), Resp> {
+        let error = |message: &str| Err(Resp::SimpleError(format!("ERR {message}")));
+        match self.clients.get(client).map(|c| &c.options) {
+            Some(options) if yes && options.optin => Ok(()),
+            Some(options) if !yes && options.optout => Ok(()),
+            Some(options) if yes && options.optout => {
+                error("CLIENT CACHING YES is only valid when tracking is enabled in OPTIN mode.")
+            }
+            Some(options) if !yes && options.optin => {
+                error("CLIENT CACHING NO is only valid when tracking is enabled in OPTOUT mode.")
+            }
+            _ => error("CLIENT CACHING can be called only when the client is in tracking mode with OPTIN or OPTOUT mode enabled"),
+        }
+    }
+
+    pub fn track(&mut self, client: SocketAddr, keys: Vec<String>, caching: Option<bool>) {
+        let Some(tracking) = self.clients.get(&client) else {
+            return;
+        };
+        let options = &tracking.options;
+        let track = if options.bcast {
+            false
+        } else if options.optin {
+            caching == Some(true)
+        } else if options.optout {
+            caching != Some(false)
+        } else {
+            true
+        };
+        if !track {
+            return;
+        }
+        for key in keys {
+            self.keys.entry(key).or_default().insert(client);
+        }
+    }
+
+    pub fn invalidate(
+        &mut self,
+        keys: Vec<String>,
+        origin: Option<SocketAddr>,
+    ) -> Vec<(SocketAddr, Vec<String>)> {
+        if self.clients.is_empty() {
+            return Vec::new();
+        }
+        let keys: BTreeSet<String> = keys.into_iter().collect();
+        let mut invalidations: HashMap<SocketAddr, Vec<String>> = HashMap::new();
+        let mut notify = |client: SocketAddr, tracking: &TrackingClient, key: &String| {
+            if tracking.options.noloop && origin == Some(client) {
+                return;
+            }
+            if let Some(target) = tracking.target(client) {
+                invalidations.entry(target).or_default().push(key.clone());
+            }
+        };
+        for key in keys.iter() {
+            for client in self.keys.remove(key).unwrap_or_default() {
+                if let Some(tracking) = self.clients.get(&client) {
+                    notify(client, tracking, key);
+                }
+            }
+            for (client, tracking) in self.clients.iter() {
+                if tracking.options.bcast && tracking.matches(key) {
+                    notify(*client, tracking, key);
+                }
+            }
+        }
+        invalidations.into_iter().collect()
+    }
+
+    pub fn flush(&mut self) -> HashSet<SocketAddr> {
+        self.keys.clear();
+        self.clients
+            .iter()
+            .filter_map(|(client, tracking)| tracking.target(*client))
+            .collect()
+    }
+
+    pub fn redirect(&self, client: &SocketAddr) -> i64 {
+        match self.clients.get(client) {
+            Some(tracking) => tracking.options.redirect.unwrap_or(0) as i64,
+            None => -1,
+        }
+    }
+
+    pub fn info(&self, client: &SocketAddr, caching: Option<bool>) -> Vec<(Resp, Resp)> {
+        let mut flags = Vec::new();
+        let mut prefixes = Vec::new();
+        match self.clients.get(client) {
+            Some(tracking) => {
+                let options = &tracking.options;
+                flags.push("on");
+                if options.bcast {
+                    flags.push("bcast");
+                }
+                if options.optin {
+                    flags.push("optin");
+                    if caching == Some(true) {
+                        flags.push("caching-yes");
+                    }
+                }
+                if options.optout {
+                    flags.push("optout");
+                    if caching == Some(false) {
+                        flags.push("caching-no");
+                    }
+                }
+                if options.noloop {
+                    flags.push("noloop");
+                }
+                if tracking.broken_redirect {
+                    flags.push("broken_redirect");
+                }
+                prefixes.extend(options.prefixes.iter().cloned());
+            }
+            None => flags.push("off"),
+        }
+        let strings = |values: Vec<String>| {
+            Resp::Array(
+                values
+                    .into_iter()
+                    .map(|v| Resp::BulkString(v.into()))
+                    .collect(),
+            )
+        };
+        vec![
+            (
+                Resp::BulkString("flags".into()),
+                strings(flags.into_iter().map(String::from).collect()),
+            ),
+            (
+                Resp::BulkString("redirect".into()),
+                Resp::Integer(self.redirect(client)),
+            ),
+            (Resp::BulkString("prefixes".into()), strings(prefixes)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn default_mode() -> Result<(), Resp> {
+        let mut tracking = Tracking::new();
+        let (client, other) = (address(1), address(2));
+        tracking.enable(client, None, TrackingOptions::default())?;
+        tracking.track(client, keys(&["a", "b"]), None);
+        tracking.track(other, keys(&["c"]), None);
+
+        let invalidations = tracking.invalidate(keys(&["a", "c", "a"]), Some(other));
+        assert_eq!(vec![(client, keys(&["a"]))], invalidations);
+        assert!(tracking.invalidate(keys(&["a"]), None).is_empty());
+
+        let noloop = TrackingOptions {
+            noloop: true,
+            ..TrackingOptions::default()
+        };
+        tracking.enable(client, None, noloop)?;
+        tracking.track(client, keys(&["b"]), None);
+        assert!(tracking.invalidate(keys(&["b"]), Some(client)).is_empty());
+
+        tracking.disable(&client);
+        assert_eq!(-1, tracking.redirect(&client));
+        assert!(tracking.keys.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn broadcast_mode() -> Result<(), Resp> {
+        let mut tracking = Tracking::new();
+        let (client, redirect) = (address(1), address(2));
+        let options = TrackingOptions {
+            redirect: Some(7),
+            bcast: true,
+            prefixes: keys(&["user:", "session:"]),
+            ..TrackingOptions::default()
+        };
+        tracking.enable(client, Some(redirect), options)?;
+        assert_eq!(7, tracking.redirect(&client));
+        let invalidations = tracking.invalidate(keys(&["user:1", "post:1", "session:2"]), None);
+        assert_eq!(
+            vec![(redirect, keys(&["session:2", "user:1"]))],
+            invalidations
+        );
+
+        let overlapping = TrackingOptions {
+            bcast: true,
+            prefixes: keys(&["user:admin:"]),
+            ..TrackingOptions::default()
+        };
+        assert!(tracking.enable(client, None, overlapping).is_err());
+
+        assert_eq!(vec![client], tracking.disconnect(&redirect));
+        assert!(tracking.invalidate(keys(&["user:1"]), None).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn opt_in_and_out() -> Result<(), Resp> {
+        let mut tracking = Tracking::new();
+        let (optin, optout) = (address(1), address(2));
+        let options = |optin| TrackingOptions {
+            optin,
+            optout: !optin,
+            ..TrackingOptions::default()
+        };
+        tracking.enable(optin, None, options(true))?;
+        tracking.enable(optout, None, options(false))?;
+        assert!(tracking.check_caching(&optin, true).is_ok());
+        assert!(tracking.check_caching(&optin, false).is_err());
+        assert!(tracking.enable(optin, None, options(false)).is_err());
+
+        tracking.track(optin, keys(&["a"]), None);
+        tracking.track(optin, keys(&["b"]), Some(true));
+        tracking.track(optout, keys(&["a"]), Some(false));
+        tracking.track(optout, keys(&["b"]), None);
+        let mut invalidations = tracking.invalidate(keys(&["a", "b"]), None);
+        invalidations.sort();
+        assert_eq!(
+            vec![(optin, keys(&["b"])), (optout, keys(&["b"]))],
+            invalidations
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use crate::{dictionary::MemoryUsage, sorted_set::SortedSet, stream::Stream};
+
+pub enum Value {
+    String(Vec<u8>),
+    List(VecDeque<Vec<u8>>),
+    Stream(Stream),
+    SortedSet(SortedSet),
+}
+
+impl Value {
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Value::String(bytes) => {
+                let integer = std::str::from_utf8(bytes)
+                    .ok()
+                    .filter(|s| s.len() <= 20)
+                    .and_then(|s| s.parse::<i64>().ok().filter(|i| i.to_string() == s));
+                if integer.is_some() {
+                    "int"
+                } else if bytes.len() <= 44 {
+                    "embstr"
+                } else {
+                    "raw"
+                }
+            }
+            Value::List(list) => {
+                if list.len() <= 128 && list.iter().all(|element| element.len() <= 64) {
+                    "listpack"
+                } else {
+                    "quicklist"
+                }
+            }
+            Value::Stream(_) => "stream",
+            Value::SortedSet(set) => {
+                if set.len() <= 128 && set.iter().all(|(member, _)| member.len() <= 64) {
+                    "listpack"
+                } else {
+                    "skiplist"
+                }
+            }
+        }
+    }
+}
+
+impl MemoryUsage for Value {
+    fn memory_usage(&self) -> usize {
+        match self {
+            Value::String(s) => s.memory_usage(),
+            Value::List(list) => list.iter().map(Vec::memory_usage).sum(),
+            Value::Stream(stream) => stream.memory_usage(),
+            Value::SortedSet(set) => set.memory_usage(),
+        }
+    }
+}
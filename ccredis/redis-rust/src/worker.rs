@@ -1,33 +1,1680 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    net::{SocketAddr, ToSocketAddrs},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
 use crate::{
-    command::Command,
-    dictionary::{self, Dictionary},
+    bitops,
+    client::Client,
+    cluster::{key_hash_slot, Cluster, Node, Redirect},
+    command::{
+        self, Command, GeoOrigin, GeoSearch, Order, PendingRange, SlotState, TrackingOptions,
+    },
+    config::{Config, MaxMemoryPolicy},
+    dictionary::{Dictionary, Entry},
+    geo,
+    hyperloglog::HyperLogLog,
+    notify::{EventClass, Notification},
+    pubsub::PubSub,
+    rdb,
     resp::Resp,
+    session::Session,
+    slowlog::SlowLog,
+    sorted_set::SortedSet,
+    stream::{entry_to_resp, ReadId, Stream},
+    tracking::Tracking,
+    value::Value,
 };
 
+const INVALIDATION_CHANNEL: &str = "__redis__:invalidate";
+
 pub struct Worker {
-    dictionary: Dictionary<String>,
+    databases: Vec<Dictionary<Value>>,
+    config: Config,
+    slowlog: SlowLog,
+    monitors: HashSet<SocketAddr>,
+    pubsub: PubSub,
+    pushes: Vec<(SocketAddr, Resp)>,
+    last_cron: Instant,
+    blocked: HashMap<SocketAddr, BlockedClient>,
+    waiting: HashMap<(usize, String), VecDeque<SocketAddr>>,
+    ready_keys: HashSet<(usize, String)>,
+    cluster: Option<Cluster>,
+    bus: Vec<(SocketAddr, Resp)>,
+    shutdown: bool,
+    clients: HashMap<u64, SocketAddr>,
+    resp3: HashSet<SocketAddr>,
+    tracking: Tracking,
+}
+
+struct BlockedClient {
+    db: usize,
+    keys: Vec<String>,
+    command: Command,
+    deadline: Option<Instant>,
 }
 
 impl Worker {
-    pub fn new(dictonary: Dictionary<String>) -> Self {
+    pub fn new(config: Config) -> Self {
         Self {
-            dictionary: dictonary,
+            databases: (0..config.databases).map(|_| Dictionary::new()).collect(),
+            config,
+            slowlog: SlowLog::new(),
+            monitors: HashSet::new(),
+            pubsub: PubSub::new(),
+            pushes: Vec::new(),
+            last_cron: Instant::now(),
+            blocked: HashMap::new(),
+            waiting: HashMap::new(),
+            ready_keys: HashSet::new(),
+            cluster: None,
+            bus: Vec::new(),
+            shutdown: false,
+            clients: HashMap::new(),
+            resp3: HashSet::new(),
+            tracking: Tracking::new(),
+        }
+    }
+
+    pub fn set_address(&mut self, address: SocketAddr) {
+        if self.config.cluster_enabled {
+            self.cluster = Some(Cluster::new(address));
+        }
+    }
+
+    pub fn is_cluster_enabled(&self) -> bool {
+        self.cluster.is_some()
+    }
+
+    pub fn serve_blocked(&mut self) {
+        if self.blocked.is_empty() {
+            self.ready_keys.clear();
+            return;
+        }
+        let now = Instant::now();
+        let ready_keys = std::mem::take(&mut self.ready_keys);
+        let mut candidates: Vec<SocketAddr> = Vec::new();
+        for key in &ready_keys {
+            for address in self.waiting.get(key).into_iter().flatten() {
+                if !candidates.contains(address) {
+                    candidates.push(*address);
+                }
+            }
+        }
+        for (address, blocked) in &self.blocked {
+            let expired = blocked.deadline.is_some_and(|deadline| deadline <= now);
+            if expired && !candidates.contains(address) {
+                candidates.push(*address);
+            }
+        }
+        for address in candidates {
+            let Some(blocked) = self.blocked.remove(&address) else {
+                continue;
+            };
+            let reply = match &blocked.command {
+                Command::XRead { count, streams, .. } => {
+                    Ok(self.xread(blocked.db, *count, streams))
+                }
+                Command::XReadGroup {
+                    group,
+                    consumer,
+                    count,
+                    noack,
+                    streams,
+                    ..
+                } => self.xreadgroup(blocked.db, group, consumer, *count, *noack, streams),
+                _ => Ok(None),
+            };
+            let reply = match reply {
+                Ok(Some(reply)) => reply,
+                Err(err) => err,
+                Ok(None) if blocked.deadline.is_some_and(|deadline| deadline <= now) => Resp::Null,
+                Ok(None) => {
+                    self.blocked.insert(address, blocked);
+                    continue;
+                }
+            };
+            self.pushes.push((address, reply));
+            self.stop_waiting(&address, &blocked);
+        }
+    }
+
+    fn stop_waiting(&mut self, address: &SocketAddr, blocked: &BlockedClient) {
+        for key in &blocked.keys {
+            let entry = (blocked.db, key.clone());
+            if let Some(queue) = self.waiting.get_mut(&entry) {
+                queue.retain(|waiter| waiter != address);
+                if queue.is_empty() {
+                    self.waiting.remove(&entry);
+                }
+            }
         }
     }
-    pub fn handle_command(&mut self, command: Command) -> Resp {
+
+    pub fn cron(&mut self) {
+        const INTERVAL: Duration = Duration::from_millis(100);
+        self.invalidate_modified_keys(None);
+        if self.last_cron.elapsed() < INTERVAL {
+            return;
+        }
+        self.last_cron = Instant::now();
+        if let Some(cluster) = &self.cluster {
+            self.bus.extend(cluster.gossip());
+        }
+        for db in 0..self.databases.len() {
+            for key in self.databases[db].remove_expired() {
+                self.notify(EventClass::Expired, "expired", key, db);
+            }
+        }
+    }
+
+    pub fn handle_request(&mut self, session: &mut Session, request: Resp) -> Option<Resp> {
+        let args = request_args(&request);
+        let command = match Command::try_from(request) {
+            Ok(command) => command,
+            Err(err) => return Some(err),
+        };
+        if !matches!(command, Command::ClusterGossip { .. }) {
+            self.feed_monitors(session, &args);
+        }
+        let asking = std::mem::take(&mut session.asking);
+        if let Err(redirect) = self.route(session, &args, asking) {
+            return Some(redirect.into());
+        }
+        let spec = command::lookup(&args[0]);
+        let denyoom = spec.is_some_and(|spec| spec.has_flag("denyoom"));
+        if denyoom && !self.perform_evictions() {
+            return Some(Resp::SimpleError(String::from(
+                "OOM command not allowed when used memory > 'maxmemory'.",
+            )));
+        }
+        let caching = matches!(command, Command::ClientCaching(_));
+        let start = Instant::now();
+        let response = self.handle_command(session, command);
+        let duration = start.elapsed();
+        if spec.is_some_and(|spec| spec.has_flag("readonly")) {
+            let keys = spec.map(|spec| spec.keys(&args)).unwrap_or_default();
+            self.tracking.track(session.address, keys, session.caching);
+        }
+        if !caching {
+            session.caching = None;
+        }
+        self.invalidate_modified_keys(Some(session.address));
+        let threshold = self.config.slowlog_log_slower_than;
+        if threshold >= 0 && duration.as_micros() >= threshold as u128 {
+            self.slowlog
+                .push(args, session.address, duration, self.config.slowlog_max_len);
+        }
+        if self.blocked.contains_key(&session.address) || self.shutdown {
+            return None;
+        }
+        Some(response)
+    }
+
+    pub fn handle_command(&mut self, session: &mut Session, command: Command) -> Resp {
+        let db = session.db;
         match command {
             Command::Ping => Resp::SimpleString("PONG".to_string()),
             Command::Echo(s) => Resp::BulkString(s),
-            Command::Get(key) => match self.dictionary.get(&key) {
-                Some(value) => Resp::BulkString(value.to_string()),
-                None => Resp::Null,
-            },
+            Command::Get(key) => {
+                self.expire_if_needed(&key, db);
+                match self.databases[db].get(&key) {
+                    Some(Value::String(value)) => Resp::BulkString(value.clone()),
+                    Some(_) => Resp::wrong_type(),
+                    None => Resp::Null,
+                }
+            }
             Command::Set { key, value } => {
-                self.dictionary.set(key, value, None, false, None);
+                self.databases[db].set(key.clone(), Value::String(value), None, false, None);
+                self.notify(EventClass::String, "set", key, db);
                 Resp::ok()
             }
-            Command::ConfigGet => Resp::Integer(0),
+            Command::Incr(key) => {
+                let created = !self.databases[db].contains_key(&key);
+                let value = match self.string_mut(db, &key, true) {
+                    Ok(Some(value)) => value,
+                    Ok(None) => return Resp::Integer(0),
+                    Err(err) => return err,
+                };
+                let current = match created {
+                    true => Some(0),
+                    false => std::str::from_utf8(value)
+                        .ok()
+                        .and_then(|s| s.parse::<i64>().ok()),
+                };
+                let Some(current) = current else {
+                    return Resp::not_an_integer();
+                };
+                let Some(incremented) = current.checked_add(1) else {
+                    return Resp::SimpleError(String::from(
+                        "ERR increment or decrement would overflow",
+                    ));
+                };
+                *value = incremented.to_string().into_bytes();
+                self.notify(EventClass::String, "incrby", key, db);
+                Resp::Integer(incremented)
+            }
+            Command::LPush { key, elements } => {
+                let list = match self.list_mut(db, &key) {
+                    Ok(list) => list,
+                    Err(err) => return err,
+                };
+                for element in elements {
+                    list.push_front(element);
+                }
+                let len = list.len();
+                self.notify(EventClass::List, "lpush", key, db);
+                Resp::Integer(len as i64)
+            }
+            Command::ConfigGet(pattern) => Resp::Array(
+                self.config
+                    .get(&pattern)
+                    .into_iter()
+                    .flat_map(|(name, value)| {
+                        [Resp::BulkString(name.into()), Resp::BulkString(value.into())]
+                    })
+                    .collect(),
+            ),
+            Command::ConfigSet { parameter, value } => match self.config.set(&parameter, &value) {
+                Ok(()) => Resp::ok(),
+                Err(err) => Resp::SimpleError(err),
+            },
             Command::Client => Resp::ok(),
+            Command::ClientId => Resp::Integer(session.id as i64),
+            Command::ClientTracking(Some(options)) => self.enable_tracking(session, options),
+            Command::ClientTracking(None) => {
+                self.tracking.disable(&session.address);
+                Resp::ok()
+            }
+            Command::ClientCaching(yes) => match self.tracking.check_caching(&session.address, yes) {
+                Ok(()) => {
+                    session.caching = Some(yes);
+                    Resp::ok()
+                }
+                Err(err) => err,
+            },
+            Command::ClientGetRedir => Resp::Integer(self.tracking.redirect(&session.address)),
+            Command::ClientTrackingInfo => {
+                let info = self.tracking.info(&session.address, session.caching);
+                map_reply(session, info)
+            }
+            Command::Hello(protover) => self.hello(session, protover),
+            Command::SlowLogGet(count) => {
+                let count = match count {
+                    Some(count) if count < 0 => self.slowlog.len(),
+                    Some(count) => count as usize,
+                    None => 10,
+                };
+                Resp::Array(
+                    self.slowlog
+                        .get(count)
+                        .into_iter()
+                        .map(Resp::from)
+                        .collect(),
+                )
+            }
+            Command::SlowLogLen => Resp::Integer(self.slowlog.len() as i64),
+            Command::SlowLogReset => {
+                self.slowlog.reset();
+                Resp::ok()
+            }
+            Command::Monitor => {
+                self.monitors.insert(session.address);
+                Resp::ok()
+            }
+            Command::Select(db) if db != 0 && self.cluster.is_some() => Resp::SimpleError(
+                String::from("ERR SELECT is not allowed in cluster mode"),
+            ),
+            Command::Select(db) => match self.db_index(db) {
+                Some(db) => {
+                    session.db = db;
+                    Resp::ok()
+                }
+                None => Resp::db_index_out_of_range(),
+            },
+            Command::Move { key, db } => match self.db_index(db) {
+                Some(db) if db == session.db => Resp::SimpleError(String::from(
+                    "ERR source and destination objects are the same",
+                )),
+                Some(db) => {
+                    if self.databases[db].contains_key(&key) {
+                        return Resp::Integer(0);
+                    }
+                    match self.databases[session.db].take(&key) {
+                        Some(entry) => {
+                            self.databases[db].insert_entry(key, entry);
+                            Resp::Integer(1)
+                        }
+                        None => Resp::Integer(0),
+                    }
+                }
+                None => Resp::db_index_out_of_range(),
+            },
+            Command::SwapDb(first, second) => match (self.db_index(first), self.db_index(second)) {
+                (Some(first), Some(second)) => {
+                    self.databases.swap(first, second);
+                    self.invalidate_all();
+                    Resp::ok()
+                }
+                _ => Resp::SimpleError(String::from("ERR invalid DB index")),
+            },
+            Command::FlushAll => {
+                self.databases.iter_mut().for_each(Dictionary::clear);
+                self.invalidate_all();
+                Resp::ok()
+            }
+            Command::FlushDb => {
+                self.databases[db].clear();
+                self.invalidate_all();
+                Resp::ok()
+            }
+            Command::DbSize => Resp::Integer(self.databases[db].len() as i64),
+            Command::Info(section) => Resp::BulkString(self.info(section).into()),
+            Command::Shutdown(_) => {
+                println!("User requested shutdown...");
+                self.shutdown = true;
+                Resp::ok()
+            }
+            Command::Del(keys) => {
+                let mut deleted = 0;
+                for key in keys {
+                    if self.databases[db].remove(&key).is_some() {
+                        self.notify(EventClass::Generic, "del", key, db);
+                        deleted += 1;
+                    }
+                }
+                Resp::Integer(deleted)
+            }
+            Command::Expire { key, seconds } => {
+                if !self.databases[db].contains_key(&key) {
+                    return Resp::Integer(0);
+                }
+                if seconds <= 0 {
+                    self.databases[db].remove(&key);
+                    self.notify(EventClass::Generic, "del", key, db);
+                    return Resp::Integer(1);
+                }
+                let Some(expires_at) =
+                    SystemTime::now().checked_add(Duration::from_secs(seconds as u64))
+                else {
+                    return Resp::SimpleError(String::from(
+                        "ERR invalid expire time in 'expire' command",
+                    ));
+                };
+                self.databases[db].set_expiry(&key, expires_at);
+                self.notify(EventClass::Generic, "expire", key, db);
+                Resp::Integer(1)
+            }
+            Command::Ttl(key) => {
+                self.expire_if_needed(&key, db);
+                match self.databases[db].ttl(&key) {
+                    None => Resp::Integer(-2),
+                    Some(None) => Resp::Integer(-1),
+                    Some(Some(ttl)) => Resp::Integer(((ttl.as_millis() + 500) / 1000) as i64),
+                }
+            }
+            Command::Publish { channel, message } => {
+                let messages = self.pubsub.publish(&channel, &message, &self.resp3);
+                let receivers = messages.len();
+                self.pushes.extend(messages);
+                Resp::Integer(receivers as i64)
+            }
+            Command::Subscribe(channels) => {
+                let replies = self.pubsub.subscribe(session.address, channels);
+                self.reply_many(session, replies)
+            }
+            Command::Unsubscribe(channels) => {
+                let replies = self.pubsub.unsubscribe(session.address, channels);
+                self.reply_many(session, replies)
+            }
+            Command::PSubscribe(patterns) => {
+                let replies = self.pubsub.psubscribe(session.address, patterns);
+                self.reply_many(session, replies)
+            }
+            Command::PUnsubscribe(patterns) => {
+                let replies = self.pubsub.punsubscribe(session.address, patterns);
+                self.reply_many(session, replies)
+            }
+            Command::XAdd {
+                key,
+                nomkstream,
+                maxlen,
+                id,
+                fields,
+            } => {
+                let stream = match self.stream_mut(db, &key, !nomkstream) {
+                    Ok(Some(stream)) => stream,
+                    Ok(None) => return Resp::Null,
+                    Err(err) => return err,
+                };
+                let result = stream.add(id, fields, maxlen);
+                let empty = stream.is_empty();
+                match result {
+                    Ok(id) => {
+                        self.notify(EventClass::Stream, "xadd", key.clone(), db);
+                        self.ready_keys.insert((db, key));
+                        Resp::BulkString(id.to_string().into())
+                    }
+                    Err(err) => {
+                        if empty {
+                            self.databases[db].remove(&key);
+                        }
+                        err
+                    }
+                }
+            }
+            Command::XLen(key) => match self.stream(db, &key) {
+                Ok(Some(stream)) => Resp::Integer(stream.len() as i64),
+                Ok(None) => Resp::Integer(0),
+                Err(err) => err,
+            },
+            Command::XRange {
+                key,
+                start,
+                end,
+                count,
+                rev,
+            } => match self.stream(db, &key) {
+                Ok(Some(stream)) => Resp::Array(
+                    stream
+                        .range(start, end, count, rev)
+                        .into_iter()
+                        .map(|(id, fields)| entry_to_resp(id, Some(fields)))
+                        .collect(),
+                ),
+                Ok(None) => Resp::Array(Vec::new()),
+                Err(err) => err,
+            },
+            Command::XRead {
+                count,
+                block,
+                mut streams,
+            } => {
+                for (key, id) in streams.iter_mut() {
+                    if *id == ReadId::Last {
+                        let last_id = match self.stream(db, key) {
+                            Ok(Some(stream)) => stream.last_id(),
+                            Ok(None) => Default::default(),
+                            Err(err) => return err,
+                        };
+                        *id = ReadId::Id(last_id);
+                    }
+                }
+                match self.xread(db, count, &streams) {
+                    Some(reply) => reply,
+                    None => {
+                        let command = Command::XRead {
+                            count,
+                            block,
+                            streams,
+                        };
+                        self.block(session, block, command)
+                    }
+                }
+            }
+            Command::XGroupCreate {
+                key,
+                group,
+                id,
+                mkstream,
+            } => match self.stream_mut(db, &key, mkstream) {
+                Ok(Some(stream)) => match stream.create_group(group, id) {
+                    Ok(()) => {
+                        self.notify(EventClass::Stream, "xgroup-create", key, db);
+                        Resp::ok()
+                    }
+                    Err(err) => err,
+                },
+                Ok(None) => Resp::SimpleError(String::from(
+                    "ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.",
+                )),
+                Err(err) => err,
+            },
+            Command::XGroupDestroy { key, group } => match self.stream_mut(db, &key, false) {
+                Ok(Some(stream)) => Resp::Integer(stream.destroy_group(&group) as i64),
+                Ok(None) => Resp::SimpleError(String::from(
+                    "ERR The XGROUP subcommand requires the key to exist.",
+                )),
+                Err(err) => err,
+            },
+            Command::XReadGroup {
+                group,
+                consumer,
+                count,
+                block,
+                noack,
+                streams,
+            } => match self.xreadgroup(db, &group, &consumer, count, noack, &streams) {
+                Ok(Some(reply)) => reply,
+                Ok(None) => {
+                    let command = Command::XReadGroup {
+                        group,
+                        consumer,
+                        count,
+                        block,
+                        noack,
+                        streams,
+                    };
+                    self.block(session, block, command)
+                }
+                Err(err) => err,
+            },
+            Command::XAck { key, group, ids } => match self.stream_mut(db, &key, false) {
+                Ok(Some(stream)) => Resp::Integer(stream.ack(&group, &ids) as i64),
+                Ok(None) => Resp::Integer(0),
+                Err(err) => err,
+            },
+            Command::XPending {
+                key,
+                group,
+                details,
+            } => {
+                let stream = match self.stream(db, &key) {
+                    Ok(Some(stream)) => stream,
+                    Ok(None) => return Resp::no_group(&group),
+                    Err(err) => return err,
+                };
+                match details {
+                    None => xpending_summary(stream, &group),
+                    Some(range) => xpending_details(stream, &group, range),
+                }
+            }
+            Command::Dump(key) => {
+                self.expire_if_needed(&key, db);
+                match self.databases[db].get(&key) {
+                    Some(value) => Resp::BulkString(rdb::dump(value)),
+                    None => Resp::Null,
+                }
+            }
+            Command::Restore {
+                key,
+                ttl,
+                payload,
+                replace,
+                absttl,
+                idletime,
+                freq,
+            } => {
+                self.expire_if_needed(&key, db);
+                if !replace && self.databases[db].contains_key(&key) {
+                    return Resp::SimpleError(String::from(
+                        "BUSYKEY Target key name already exists.",
+                    ));
+                }
+                let value = match rdb::restore(&payload) {
+                    Ok(value) => value,
+                    Err(err) => return err,
+                };
+                let expires_at = match (ttl, absttl) {
+                    (0, _) => None,
+                    (ttl, true) => Some(UNIX_EPOCH + Duration::from_millis(ttl as u64)),
+                    (ttl, false) => SystemTime::now().checked_add(Duration::from_millis(ttl as u64)),
+                };
+                let replaced = self.databases[db].remove(&key).is_some();
+                if expires_at.is_some_and(|t| t <= SystemTime::now()) {
+                    if replaced {
+                        self.notify(EventClass::Generic, "del", key, db);
+                    }
+                    return Resp::ok();
+                }
+                let stream = matches!(value, Value::Stream(_));
+                let entry = Entry::new(value, expires_at);
+                if let Some(idletime) = idletime {
+                    entry.set_idle_time(Duration::from_secs(idletime));
+                }
+                if let Some(freq) = freq {
+                    entry.set_frequency(freq);
+                }
+                self.databases[db].insert_entry(key.clone(), entry);
+                self.notify(EventClass::Generic, "restore", key.clone(), db);
+                if expires_at.is_some() {
+                    self.notify(EventClass::Generic, "expire", key.clone(), db);
+                }
+                if stream {
+                    self.ready_keys.insert((db, key));
+                }
+                Resp::ok()
+            }
+            Command::Migrate {
+                address,
+                keys,
+                db: target_db,
+                timeout,
+                copy,
+                replace,
+            } => {
+                let (result, migrated) =
+                    self.migrate(db, &address, target_db, timeout, keys, replace);
+                if !copy {
+                    for key in migrated {
+                        self.databases[db].remove(&key);
+                        self.notify(EventClass::Generic, "del", key, db);
+                    }
+                }
+                result
+            }
+            Command::ObjectEncoding(key) => match self.databases[db].entry(&key) {
+                Some(entry) => Resp::BulkString(entry.value().encoding().into()),
+                None => Resp::Null,
+            },
+            Command::ObjectIdleTime(key) => match self.databases[db].entry(&key) {
+                Some(entry) => Resp::Integer(entry.idle_time().as_secs() as i64),
+                None => Resp::Null,
+            },
+            Command::ObjectFreq(key) => match self.databases[db].entry(&key) {
+                Some(entry) => Resp::Integer(entry.frequency() as i64),
+                None => Resp::Null,
+            },
+            command @ (Command::ClusterSlots
+            | Command::ClusterShards
+            | Command::ClusterNodes
+            | Command::ClusterMyId
+            | Command::ClusterKeySlot(_)
+            | Command::ClusterAddSlots(_)
+            | Command::ClusterMeet(_)
+            | Command::ClusterSetSlot { .. }
+            | Command::ClusterGossip { .. }
+            | Command::Asking) => self.cluster_command(session, command),
+            Command::PfAdd { key, elements } => {
+                let created = !self.databases[db].contains_key(&key);
+                let value = match self.string_mut(db, &key, true) {
+                    Ok(Some(value)) => value,
+                    Ok(None) => return Resp::Integer(0),
+                    Err(err) => return err,
+                };
+                let mut hll = if created {
+                    HyperLogLog::new()
+                } else {
+                    match HyperLogLog::from_bytes(value) {
+                        Ok(hll) => hll,
+                        Err(err) => return err,
+                    }
+                };
+                let mut changed = created;
+                for element in elements {
+                    changed |= hll.add(&element);
+                }
+                if changed {
+                    *value = hll.to_bytes();
+                    self.notify(EventClass::String, "pfadd", key, db);
+                }
+                Resp::Integer(changed as i64)
+            }
+            Command::PfCount(keys) if keys.len() == 1 => {
+                let value = match self.string_mut(db, &keys[0], false) {
+                    Ok(Some(value)) => value,
+                    Ok(None) => return Resp::Integer(0),
+                    Err(err) => return err,
+                };
+                let mut hll = match HyperLogLog::from_bytes(value) {
+                    Ok(hll) => hll,
+                    Err(err) => return err,
+                };
+                let cached = hll.is_cached();
+                let count = hll.count();
+                if !cached {
+                    *value = hll.to_bytes();
+                }
+                Resp::Integer(count as i64)
+            }
+            Command::PfCount(keys) => match self.merge_hyperloglogs(db, &keys) {
+                Ok(mut hll) => Resp::Integer(hll.count() as i64),
+                Err(err) => err,
+            },
+            Command::PfMerge {
+                destination,
+                sources,
+            } => {
+                let mut keys = vec![destination.clone()];
+                keys.extend(sources);
+                let hll = match self.merge_hyperloglogs(db, &keys) {
+                    Ok(hll) => hll,
+                    Err(err) => return err,
+                };
+                match self.string_mut(db, &destination, true) {
+                    Ok(Some(value)) => *value = hll.to_bytes(),
+                    Ok(None) => return Resp::ok(),
+                    Err(err) => return err,
+                }
+                self.notify(EventClass::String, "pfadd", destination, db);
+                Resp::ok()
+            }
+            Command::SetBit { key, offset, value } => {
+                let old = match self.string_mut(db, &key, true) {
+                    Ok(Some(bytes)) => bitops::set_bit(bytes, offset, value),
+                    Ok(None) => false,
+                    Err(err) => return err,
+                };
+                self.notify(EventClass::String, "setbit", key, db);
+                Resp::Integer(old as i64)
+            }
+            Command::GetBit { key, offset } => match self.string(db, &key) {
+                Ok(bytes) => Resp::Integer(bitops::get_bit(bytes, offset) as i64),
+                Err(err) => err,
+            },
+            Command::BitCount { key, range } => match self.string(db, &key) {
+                Ok(bytes) => Resp::Integer(bitops::bit_count(bytes, range) as i64),
+                Err(err) => err,
+            },
+            Command::BitPos { key, bit, range } => match self.string(db, &key) {
+                Ok(bytes) => Resp::Integer(bitops::bit_pos(bytes, bit, range)),
+                Err(err) => err,
+            },
+            Command::BitOp {
+                op,
+                destination,
+                keys,
+            } => {
+                let mut sources = Vec::new();
+                for key in keys.iter() {
+                    match self.string(db, key) {
+                        Ok(bytes) => sources.push(bytes.to_vec()),
+                        Err(err) => return err,
+                    }
+                }
+                let sources: Vec<&[u8]> = sources.iter().map(Vec::as_slice).collect();
+                let result = bitops::bit_op(op, &sources);
+                let len = result.len();
+                if result.is_empty() {
+                    if self.databases[db].remove(&destination).is_some() {
+                        self.notify(EventClass::Generic, "del", destination, db);
+                    }
+                } else {
+                    let value = Value::String(result);
+                    self.databases[db].set(destination.clone(), value, None, false, None);
+                    self.notify(EventClass::String, "set", destination, db);
+                }
+                Resp::Integer(len as i64)
+            }
+            Command::GeoAdd {
+                key,
+                nx,
+                xx,
+                ch,
+                items,
+            } => {
+                for (longitude, latitude, _) in items.iter() {
+                    if let Err(err) = geo::validate(*longitude, *latitude) {
+                        return err;
+                    }
+                }
+                let set = match self.sorted_set_mut(db, &key, !xx) {
+                    Ok(Some(set)) => set,
+                    Ok(None) => return Resp::Integer(0),
+                    Err(err) => return err,
+                };
+                let (mut added, mut updated) = (0, 0);
+                for (longitude, latitude, member) in items {
+                    let score = geo::encode(longitude, latitude) as f64;
+                    match set.score(&member) {
+                        Some(_) if nx => {}
+                        Some(old) => {
+                            if old != score {
+                                set.insert(member, score);
+                                updated += 1;
+                            }
+                        }
+                        None if xx => {}
+                        None => {
+                            set.insert(member, score);
+                            added += 1;
+                        }
+                    }
+                }
+                if set.is_empty() {
+                    self.databases[db].remove(&key);
+                }
+                if added + updated > 0 {
+                    self.notify(EventClass::SortedSet, "zadd", key, db);
+                }
+                Resp::Integer(if ch { added + updated } else { added })
+            }
+            Command::GeoDist {
+                key,
+                first,
+                second,
+                unit,
+            } => match self.sorted_set(db, &key) {
+                Ok(Some(set)) => match (set.score(&first), set.score(&second)) {
+                    (Some(first), Some(second)) => {
+                        let first = geo::decode(first as u64);
+                        let second = geo::decode(second as u64);
+                        let distance = geo::distance(first, second);
+                        Resp::BulkString(geo::format_distance(distance, unit).into())
+                    }
+                    _ => Resp::Null,
+                },
+                Ok(None) => Resp::Null,
+                Err(err) => err,
+            },
+            Command::GeoPos { key, members } => match self.sorted_set(db, &key) {
+                Ok(set) => Resp::Array(
+                    members
+                        .iter()
+                        .map(|member| match set.and_then(|set| set.score(member)) {
+                            Some(score) => coordinates_to_resp(geo::decode(score as u64)),
+                            None => Resp::Null,
+                        })
+                        .collect(),
+                ),
+                Err(err) => err,
+            },
+            Command::GeoHash { key, members } => match self.sorted_set(db, &key) {
+                Ok(set) => Resp::Array(
+                    members
+                        .iter()
+                        .map(|member| match set.and_then(|set| set.score(member)) {
+                            Some(score) => {
+                                Resp::BulkString(geo::geohash_string(score as u64).into())
+                            }
+                            None => Resp::Null,
+                        })
+                        .collect(),
+                ),
+                Err(err) => err,
+            },
+            Command::GeoSearch { key, search } => match self.sorted_set(db, &key) {
+                Ok(Some(set)) => geosearch(set, search),
+                Ok(None) => Resp::Array(Vec::new()),
+                Err(err) => err,
+            },
+            Command::CommandCount => Resp::Integer(command::command_table().len() as i64),
+            Command::CommandInfo(names) => command_specs(names, |spec| spec.info()),
+            Command::CommandDocs(names) => {
+                let specs: Vec<_> = if names.is_empty() {
+                    command::command_table().iter().collect()
+                } else {
+                    names.iter().filter_map(|name| command::lookup(name)).collect()
+                };
+                Resp::Array(
+                    specs
+                        .into_iter()
+                        .flat_map(|spec| [Resp::BulkString(spec.name.into()), spec.docs()])
+                        .collect(),
+                )
+            }
+            Command::CommandGetKeys(args) => {
+                let Some(spec) = command::lookup(&args[0]) else {
+                    return Resp::SimpleError(String::from("ERR Invalid command specified"));
+                };
+                if !spec.check_arity(args.len()) {
+                    return Resp::SimpleError(String::from(
+                        "ERR Invalid number of arguments specified for command",
+                    ));
+                }
+                let keys = spec.keys(&args);
+                if keys.is_empty() {
+                    return Resp::SimpleError(String::from(
+                        "ERR The command has no key arguments",
+                    ));
+                }
+                Resp::Array(keys.into_iter().map(|s| Resp::BulkString(s.into())).collect())
+            }
+        }
+    }
+
+    fn migrate(
+        &mut self,
+        db: usize,
+        address: &str,
+        target_db: i64,
+        timeout: u64,
+        keys: Vec<String>,
+        replace: bool,
+    ) -> (Resp, Vec<String>) {
+        let mut payloads = Vec::new();
+        for key in keys {
+            self.expire_if_needed(&key, db);
+            let Some(entry) = self.databases[db].entry(&key) else {
+                continue;
+            };
+            let ttl = entry.expires_at().map_or(0, |expires_at| {
+                let ttl = expires_at.duration_since(SystemTime::now());
+                ttl.unwrap_or_default().as_millis().max(1) as i64
+            });
+            payloads.push((key, rdb::dump(entry.value()), ttl));
+        }
+        if payloads.is_empty() {
+            return (Resp::SimpleString(String::from("NOKEY")), Vec::new());
+        }
+        let timeout = Duration::from_millis(if timeout == 0 { 1000 } else { timeout });
+        let connected = address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .and_then(|address| Client::connect_timeout(&address, timeout).ok());
+        let Some(mut client) = connected else {
+            let err = Resp::SimpleError(String::from(
+                "IOERR error or timeout connecting to the client",
+            ));
+            return (err, Vec::new());
+        };
+        let mut pipeline = client.pipeline();
+        pipeline.command(&["SELECT", &target_db.to_string()]);
+        for (key, payload, ttl) in payloads.iter() {
+            let ttl = ttl.to_string();
+            let mut args = vec![&b"RESTORE"[..], key.as_bytes(), ttl.as_bytes(), payload];
+            if replace {
+                args.push(b"REPLACE");
+            }
+            pipeline.command(&args);
+        }
+        let replies = match pipeline.execute() {
+            Ok(replies) => replies,
+            Err(_) => {
+                let err = Resp::SimpleError(String::from(
+                    "IOERR error or timeout reading to target instance",
+                ));
+                return (err, Vec::new());
+            }
+        };
+        let target_error =
+            |err| Resp::SimpleError(format!("ERR Target instance replied with error: {err}"));
+        let mut replies = replies.into_iter();
+        if let Some(Resp::SimpleError(err)) = replies.next() {
+            return (target_error(err), Vec::new());
+        }
+        let mut result = Resp::ok();
+        let mut migrated = Vec::new();
+        for (reply, (key, _, _)) in replies.zip(payloads) {
+            match reply {
+                Resp::SimpleError(err) => result = target_error(err),
+                _ => migrated.push(key),
+            }
+        }
+        (result, migrated)
+    }
+
+    fn hello(&mut self, session: &mut Session, protover: Option<i64>) -> Resp {
+        match protover {
+            None => {}
+            Some(2) => {
+                session.protocol = 2;
+                self.resp3.remove(&session.address);
+            }
+            Some(3) => {
+                session.protocol = 3;
+                self.resp3.insert(session.address);
+            }
+            Some(_) => {
+                return Resp::SimpleError(String::from("NOPROTO unsupported protocol version"))
+            }
+        }
+        let mode = if self.cluster.is_some() {
+            "cluster"
+        } else {
+            "standalone"
+        };
+        let info = [
+            ("server", Resp::BulkString("redis".into())),
+            ("version", Resp::BulkString("7.2.0".into())),
+            ("proto", Resp::Integer(session.protocol as i64)),
+            ("id", Resp::Integer(session.id as i64)),
+            ("mode", Resp::BulkString(mode.into())),
+            ("role", Resp::BulkString("master".into())),
+            ("modules", Resp::Array(Vec::new())),
+        ];
+        let info = info
+            .into_iter()
+            .map(|(field, value)| (Resp::BulkString(field.into()), value))
+            .collect();
+        map_reply(session, info)
+    }
+
+    fn enable_tracking(&mut self, session: &Session, options: TrackingOptions) -> Resp {
+        let redirect = match options.redirect {
+            Some(id) => match self.clients.get(&id) {
+                Some(address) => Some(*address),
+                None => {
+                    return Resp::SimpleError(String::from(
+                        "ERR The client ID you want redirect to does not exist",
+                    ))
+                }
+            },
+            None => None,
+        };
+        match self.tracking.enable(session.address, redirect, options) {
+            Ok(()) => Resp::ok(),
+            Err(err) => err,
+        }
+    }
+
+    fn invalidate_modified_keys(&mut self, origin: Option<SocketAddr>) {
+        let keys: Vec<String> = self
+            .databases
+            .iter_mut()
+            .flat_map(Dictionary::take_modified)
+            .collect();
+        if keys.is_empty() {
+            return;
+        }
+        for (target, keys) in self.tracking.invalidate(keys, origin) {
+            let keys = keys.into_iter().map(|key| Resp::BulkString(key.into()));
+            self.push_invalidation(target, Resp::Array(keys.collect()));
+        }
+    }
+
+    fn invalidate_all(&mut self) {
+        for target in self.tracking.flush() {
+            self.push_invalidation(target, Resp::Null);
         }
     }
+
+    fn push_invalidation(&mut self, target: SocketAddr, keys: Resp) {
+        let message = if self.resp3.contains(&target) {
+            Resp::Push(vec![Resp::BulkString("invalidate".into()), keys])
+        } else if self.pubsub.is_subscribed(target, INVALIDATION_CHANNEL) {
+            Resp::Array(vec![
+                Resp::BulkString("message".into()),
+                Resp::BulkString(INVALIDATION_CHANNEL.into()),
+                keys,
+            ])
+        } else {
+            return;
+        };
+        self.pushes.push((target, message));
+    }
+
+    fn cluster_command(&mut self, session: &mut Session, command: Command) -> Resp {
+        let Some(cluster) = self.cluster.as_mut() else {
+            return Resp::SimpleError(String::from(
+                "ERR This instance has cluster support disabled",
+            ));
+        };
+        let result = match command {
+            Command::ClusterSlots => return cluster.slots(),
+            Command::ClusterShards => return cluster.shards(),
+            Command::ClusterNodes => return Resp::BulkString(cluster.nodes().into()),
+            Command::ClusterMyId => return Resp::BulkString(cluster.myself().into()),
+            Command::ClusterKeySlot(key) => return Resp::Integer(key_hash_slot(&key) as i64),
+            Command::ClusterAddSlots(slots) => cluster.add_slots(&slots),
+            Command::ClusterMeet(address) => {
+                cluster.meet(address);
+                Ok(())
+            }
+            Command::ClusterSetSlot { slot, state } => match state {
+                SlotState::Importing(node) => cluster.set_importing(slot, node),
+                SlotState::Migrating(node) => cluster.set_migrating(slot, node),
+                SlotState::Stable => {
+                    cluster.set_stable(slot);
+                    Ok(())
+                }
+                SlotState::Node(node) => cluster.set_node(slot, node),
+            },
+            Command::ClusterGossip { .. } if !session.bus => {
+                return Resp::SimpleError(String::from(
+                    "ERR CLUSTER GOSSIP is only accepted on the cluster bus",
+                ))
+            }
+            Command::ClusterGossip {
+                id,
+                address,
+                slots,
+                nodes,
+            } => {
+                cluster.receive_gossip(Node { id, address }, slots, nodes);
+                Ok(())
+            }
+            Command::Asking => {
+                session.asking = true;
+                Ok(())
+            }
+            _ => unreachable!("not a cluster command"),
+        };
+        match result {
+            Ok(()) => Resp::ok(),
+            Err(err) => err,
+        }
+    }
+
+    fn route(&self, session: &Session, args: &[String], asking: bool) -> Result<(), Redirect> {
+        let Some(cluster) = &self.cluster else {
+            return Ok(());
+        };
+        let keys = match command::lookup(&args[0]) {
+            Some(spec) => spec.keys(args),
+            None => return Ok(()),
+        };
+        let Some(first) = keys.first() else {
+            return Ok(());
+        };
+        let slot = key_hash_slot(first);
+        if keys.iter().any(|key| key_hash_slot(key) != slot) {
+            return Err(Redirect::CrossSlot);
+        }
+        let exists = keys
+            .iter()
+            .all(|key| self.databases[session.db].contains_key(key));
+        cluster.route(slot, exists, asking)
+    }
+
+    fn string(&mut self, db: usize, key: &str) -> Result<&[u8], Resp> {
+        self.expire_if_needed(key, db);
+        match self.databases[db].get(key) {
+            Some(Value::String(bytes)) => Ok(bytes),
+            Some(_) => Err(Resp::wrong_type()),
+            None => Ok(&[]),
+        }
+    }
+
+    fn string_mut(
+        &mut self,
+        db: usize,
+        key: &str,
+        create: bool,
+    ) -> Result<Option<&mut Vec<u8>>, Resp> {
+        self.expire_if_needed(key, db);
+        if create && !self.databases[db].contains_key(key) {
+            let value = Value::String(Vec::new());
+            self.databases[db].set(key.to_string(), value, None, false, None);
+        }
+        match self.databases[db].get_mut(key) {
+            Some(Value::String(bytes)) => Ok(Some(bytes)),
+            Some(_) => Err(Resp::wrong_type()),
+            None => Ok(None),
+        }
+    }
+
+    fn list_mut(&mut self, db: usize, key: &str) -> Result<&mut VecDeque<Vec<u8>>, Resp> {
+        self.expire_if_needed(key, db);
+        if !self.databases[db].contains_key(key) {
+            let list = Value::List(VecDeque::new());
+            self.databases[db].set(key.to_string(), list, None, false, None);
+        }
+        match self.databases[db].get_mut(key) {
+            Some(Value::List(list)) => Ok(list),
+            _ => Err(Resp::wrong_type()),
+        }
+    }
+
+    fn merge_hyperloglogs(&mut self, db: usize, keys: &[String]) -> Result<HyperLogLog, Resp> {
+        let mut merged = HyperLogLog::new();
+        for key in keys {
+            self.expire_if_needed(key, db);
+            if self.databases[db].contains_key(key) {
+                merged.merge(&HyperLogLog::from_bytes(self.string(db, key)?)?);
+            }
+        }
+        Ok(merged)
+    }
+
+    fn sorted_set(&mut self, db: usize, key: &str) -> Result<Option<&SortedSet>, Resp> {
+        self.expire_if_needed(key, db);
+        match self.databases[db].get(key) {
+            Some(Value::SortedSet(set)) => Ok(Some(set)),
+            Some(_) => Err(Resp::wrong_type()),
+            None => Ok(None),
+        }
+    }
+
+    fn sorted_set_mut(
+        &mut self,
+        db: usize,
+        key: &str,
+        create: bool,
+    ) -> Result<Option<&mut SortedSet>, Resp> {
+        self.expire_if_needed(key, db);
+        if create && !self.databases[db].contains_key(key) {
+            let set = Value::SortedSet(SortedSet::new());
+            self.databases[db].set(key.to_string(), set, None, false, None);
+        }
+        match self.databases[db].get_mut(key) {
+            Some(Value::SortedSet(set)) => Ok(Some(set)),
+            Some(_) => Err(Resp::wrong_type()),
+            None => Ok(None),
+        }
+    }
+
+    fn stream(&mut self, db: usize, key: &str) -> Result<Option<&Stream>, Resp> {
+        self.expire_if_needed(key, db);
+        match self.databases[db].get(key) {
+            Some(Value::Stream(stream)) => Ok(Some(stream)),
+            Some(_) => Err(Resp::wrong_type()),
+            None => Ok(None),
+        }
+    }
+
+    fn stream_mut(
+        &mut self,
+        db: usize,
+        key: &str,
+        create: bool,
+    ) -> Result<Option<&mut Stream>, Resp> {
+        if create && !self.databases[db].contains_key(key) {
+            let stream = Value::Stream(Stream::new());
+            self.databases[db].set(key.to_string(), stream, None, false, None);
+        }
+        match self.databases[db].get_mut(key) {
+            Some(Value::Stream(stream)) => Ok(Some(stream)),
+            Some(_) => Err(Resp::wrong_type()),
+            None => Ok(None),
+        }
+    }
+
+    fn block(&mut self, session: &Session, block: Option<u64>, command: Command) -> Resp {
+        let Some(timeout) = block else {
+            return Resp::Null;
+        };
+        let keys = match &command {
+            Command::XRead { streams, .. } | Command::XReadGroup { streams, .. } => {
+                streams.iter().map(|(key, _)| key.clone()).collect()
+            }
+            _ => Vec::new(),
+        };
+        let deadline = match timeout {
+            0 => None,
+            timeout => Some(Instant::now() + Duration::from_millis(timeout)),
+        };
+        for key in &keys {
+            self.waiting
+                .entry((session.db, key.clone()))
+                .or_default()
+                .push_back(session.address);
+        }
+        let blocked = BlockedClient {
+            db: session.db,
+            keys,
+            command,
+            deadline,
+        };
+        self.blocked.insert(session.address, blocked);
+        Resp::Null
+    }
+
+    fn xread(
+        &mut self,
+        db: usize,
+        count: Option<usize>,
+        streams: &[(String, ReadId)],
+    ) -> Option<Resp> {
+        let mut replies = Vec::new();
+        for (key, id) in streams {
+            let ReadId::Id(id) = id else {
+                continue;
+            };
+            if let Some(Value::Stream(stream)) = self.databases[db].get(key) {
+                let entries = stream.read_after(*id, count);
+                if entries.is_empty() {
+                    continue;
+                }
+                let entries = entries
+                    .into_iter()
+                    .map(|(id, fields)| entry_to_resp(id, Some(fields)))
+                    .collect();
+                replies.push(Resp::Array(vec![
+                    Resp::BulkString(key.clone().into()),
+                    Resp::Array(entries),
+                ]));
+            }
+        }
+        if replies.is_empty() {
+            None
+        } else {
+            Some(Resp::Array(replies))
+        }
+    }
+
+    fn xreadgroup(
+        &mut self,
+        db: usize,
+        group: &str,
+        consumer: &str,
+        count: Option<usize>,
+        noack: bool,
+        streams: &[(String, ReadId)],
+    ) -> Result<Option<Resp>, Resp> {
+        let mut replies = Vec::new();
+        let mut has_history = false;
+        for (key, id) in streams {
+            let stream = match self.stream_mut(db, key, false)? {
+                Some(stream) if stream.has_group(group) => stream,
+                _ => return Err(Resp::SimpleError(format!(
+                    "NOGROUP No such key '{key}' or consumer group '{group}' in XREADGROUP with GROUP option"
+                ))),
+            };
+            let entries = stream.read_group(group, consumer, *id, count, noack)?;
+            has_history |= *id != ReadId::Undelivered;
+            if entries.is_empty() && *id == ReadId::Undelivered {
+                continue;
+            }
+            let entries = entries
+                .into_iter()
+                .map(|(id, fields)| entry_to_resp(id, fields.as_ref()))
+                .collect();
+            replies.push(Resp::Array(vec![
+                Resp::BulkString(key.clone().into()),
+                Resp::Array(entries),
+            ]));
+        }
+        if replies.is_empty() && !has_history {
+            return Ok(None);
+        }
+        Ok(Some(Resp::Array(replies)))
+    }
+
+    fn reply_many(&mut self, session: &Session, mut replies: Vec<Resp>) -> Resp {
+        let first = replies.remove(0);
+        for reply in replies {
+            self.pushes.push((session.address, reply));
+        }
+        first
+    }
+
+    fn expire_if_needed(&mut self, key: &str, db: usize) {
+        if self.databases[db].remove_if_expired(key) {
+            self.notify(EventClass::Expired, "expired", key.to_string(), db);
+        }
+    }
+
+    fn notify(&mut self, class: EventClass, event: &'static str, key: String, db: usize) {
+        let notification = Notification::new(class, event, key, db);
+        for (channel, message) in notification.messages(self.config.notify_keyspace_events) {
+            let messages = self.pubsub.publish(&channel, &message, &self.resp3);
+            self.pushes.extend(messages);
+        }
+    }
+
+    fn used_memory(&self) -> usize {
+        self.databases.iter().map(Dictionary::used_memory).sum()
+    }
+
+    fn perform_evictions(&mut self) -> bool {
+        let maxmemory = self.config.maxmemory;
+        if maxmemory == 0 {
+            return true;
+        }
+        while self.used_memory() > maxmemory {
+            let candidate = self
+                .databases
+                .iter()
+                .enumerate()
+                .find_map(|(db, dictionary)| {
+                    let key = match self.config.maxmemory_policy {
+                        MaxMemoryPolicy::NoEviction => None,
+                        MaxMemoryPolicy::AllKeysRandom => dictionary.random_key(false),
+                        MaxMemoryPolicy::VolatileRandom => dictionary.random_key(true),
+                        MaxMemoryPolicy::VolatileTtl => dictionary.soonest_expiring_key(),
+                    };
+                    key.map(|key| (db, key))
+                });
+            match candidate {
+                Some((db, key)) => {
+                    self.databases[db].remove(&key);
+                    self.notify(EventClass::Evicted, "evicted", key, db);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn db_index(&self, db: i64) -> Option<usize> {
+        usize::try_from(db)
+            .ok()
+            .filter(|db| *db < self.databases.len())
+    }
+
+    fn info(&self, section: Option<String>) -> String {
+        let section = section.map(|s| s.to_lowercase());
+        let mut info = String::new();
+        if matches!(
+            section.as_deref(),
+            None | Some("all" | "default" | "cluster")
+        ) {
+            info += &format!(
+                "# Cluster\r\ncluster_enabled:{}\r\n",
+                self.cluster.is_some() as u8
+            );
+        }
+        if matches!(
+            section.as_deref(),
+            None | Some("all" | "default" | "keyspace")
+        ) {
+            info += "# Keyspace\r\n";
+            for (index, db) in self.databases.iter().enumerate() {
+                if db.is_empty() {
+                    continue;
+                }
+                info += &format!(
+                    "db{index}:keys={},expires={},avg_ttl={}\r\n",
+                    db.len(),
+                    db.expires(),
+                    db.avg_ttl().as_millis()
+                );
+            }
+        }
+        info
+    }
+
+    pub fn connect(&mut self, session: &Session) {
+        self.clients.insert(session.id, session.address);
+    }
+
+    pub fn disconnect(&mut self, client: &SocketAddr) {
+        self.monitors.remove(client);
+        if let Some(blocked) = self.blocked.remove(client) {
+            self.stop_waiting(client, &blocked);
+        }
+        self.pubsub.disconnect(client);
+        self.clients.retain(|_, address| address != client);
+        self.resp3.remove(client);
+        for broken in self.tracking.disconnect(client) {
+            if self.resp3.contains(&broken) {
+                let redirect = self.tracking.redirect(&broken);
+                let message = Resp::Push(vec![
+                    Resp::BulkString("tracking-redir-broken".into()),
+                    Resp::Integer(redirect),
+                ]);
+                self.pushes.push((broken, message));
+            }
+        }
+    }
+
+    pub fn take_pushes(&mut self) -> Vec<(SocketAddr, Resp)> {
+        std::mem::take(&mut self.pushes)
+    }
+
+    pub fn is_blocked(&self, address: &SocketAddr) -> bool {
+        self.blocked.contains_key(address)
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown
+    }
+
+    pub fn take_bus_messages(&mut self) -> Vec<(SocketAddr, Resp)> {
+        std::mem::take(&mut self.bus)
+    }
+
+    fn feed_monitors(&mut self, session: &Session, args: &[String]) {
+        if self.monitors.is_empty() {
+            return;
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let mut line = format!(
+            "{}.{:06} [{} {}]",
+            timestamp.as_secs(),
+            timestamp.subsec_micros(),
+            session.db,
+            session.address
+        );
+        for arg in args {
+            line += " ";
+            line += &quote(arg);
+        }
+        for monitor in self.monitors.iter() {
+            if *monitor != session.address {
+                self.pushes
+                    .push((*monitor, Resp::SimpleString(line.clone())));
+            }
+        }
+    }
+}
+
+fn map_reply(session: &Session, pairs: Vec<(Resp, Resp)>) -> Resp {
+    if session.protocol == 3 {
+        Resp::Map(pairs)
+    } else {
+        Resp::Array(pairs.into_iter().flat_map(|(k, v)| [k, v]).collect())
+    }
+}
+
+fn request_args(request: &Resp) -> Vec<String> {
+    match request {
+        Resp::Array(arr) => arr
+            .iter()
+            .map(|resp| match resp {
+                Resp::BulkString(s) => String::from_utf8_lossy(s).into_owned(),
+                Resp::SimpleString(s) => s.clone(),
+                other => other.to_string(),
+            })
+            .collect(),
+        other => vec![other.to_string()],
+    }
+}
+
+fn command_specs(names: Vec<String>, reply: impl Fn(&command::CommandSpec) -> Resp) -> Resp {
+    if names.is_empty() {
+        return Resp::Array(command::command_table().iter().map(reply).collect());
+    }
+    Resp::Array(
+        names
+            .iter()
+            .map(|name| command::lookup(name).map(&reply).unwrap_or(Resp::Null))
+            .collect(),
+    )
+}
+
+fn coordinates_to_resp((longitude, latitude): (f64, f64)) -> Resp {
+    Resp::Array(vec![
+        Resp::BulkString(geo::format_coordinate(longitude).into()),
+        Resp::BulkString(geo::format_coordinate(latitude).into()),
+    ])
+}
+
+fn geosearch(set: &SortedSet, search: GeoSearch) -> Resp {
+    let center = match search.origin {
+        GeoOrigin::LonLat(longitude, latitude) => (longitude, latitude),
+        GeoOrigin::Member(member) => match set.score(&member) {
+            Some(score) => geo::decode(score as u64),
+            None => {
+                return Resp::SimpleError(String::from(
+                    "ERR could not decode requested zset member",
+                ))
+            }
+        },
+    };
+    let mut matches = Vec::new();
+    for (member, score) in set.iter() {
+        let point = geo::decode(score as u64);
+        if let Some(distance) = search.shape.contains(center, point) {
+            matches.push((member, distance, score as u64, point));
+            if search.any && Some(matches.len()) == search.count {
+                break;
+            }
+        }
+    }
+    let order = match search.order {
+        None if search.count.is_some() && !search.any => Some(Order::Asc),
+        order => order,
+    };
+    match order {
+        Some(Order::Asc) => matches.sort_by(|a, b| a.1.total_cmp(&b.1)),
+        Some(Order::Desc) => matches.sort_by(|a, b| b.1.total_cmp(&a.1)),
+        None => {}
+    }
+    if let Some(count) = search.count {
+        matches.truncate(count);
+    }
+    let results = matches.into_iter().map(|(member, distance, hash, point)| {
+        if !search.withdist && !search.withhash && !search.withcoord {
+            return Resp::BulkString(member.into());
+        }
+        let mut result = vec![Resp::BulkString(member.into())];
+        if search.withdist {
+            let distance = geo::format_distance(distance, search.unit);
+            result.push(Resp::BulkString(distance.into()));
+        }
+        if search.withhash {
+            result.push(Resp::Integer(hash as i64));
+        }
+        if search.withcoord {
+            result.push(coordinates_to_resp(point));
+        }
+        Resp::Array(result)
+    });
+    Resp::Array(results.collect())
+}
+
+fn quote(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '\\' => quoted += "\\\\",
+            '"' => quoted += "\\\"",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c if c.is_control() => quoted += &format!("\\x{:02x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn xpending_summary(stream: &Stream, group: &str) -> Resp {
+    let summary = match stream.pending_summary(group) {
+        Ok(summary) => summary,
+        Err(err) => return err,
+    };
+    let Some((first, last)) = summary.range else {
+        return Resp::Array(vec![Resp::Integer(0), Resp::Null, Resp::Null, Resp::Null]);
+    };
+    let consumers = summary
+        .consumers
+        .into_iter()
+        .map(|(consumer, count)| {
+            Resp::Array(vec![
+                Resp::BulkString(consumer.into()),
+                Resp::BulkString(count.to_string().into()),
+            ])
+        })
+        .collect();
+    Resp::Array(vec![
+        Resp::Integer(summary.count as i64),
+        Resp::BulkString(first.to_string().into()),
+        Resp::BulkString(last.to_string().into()),
+        Resp::Array(consumers),
+    ])
+}
+
+fn xpending_details(stream: &Stream, group: &str, range: PendingRange) -> Resp {
+    let details = stream.pending_details(
+        group,
+        range.start,
+        range.end,
+        range.count,
+        range.consumer.as_deref(),
+        range.idle,
+    );
+    match details {
+        Ok(details) => Resp::Array(
+            details
+                .into_iter()
+                .map(|detail| {
+                    Resp::Array(vec![
+                        Resp::BulkString(detail.id.to_string().into()),
+                        Resp::BulkString(detail.consumer.into()),
+                        Resp::Integer(detail.idle as i64),
+                        Resp::Integer(detail.deliveries as i64),
+                    ])
+                })
+                .collect(),
+        ),
+        Err(err) => err,
+    }
 }